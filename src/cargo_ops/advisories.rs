@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use cargo::util::{CargoResult, Config};
+use git2::{Repository, ResetType};
+use semver::{Version, VersionReq};
+use toml::Value;
+
+use super::report::Line;
+
+const ADVISORY_DB_URL: &str = "https://github.com/rustsec/advisory-db.git";
+const ADVISORY_DB_BRANCH: &str = "main";
+
+/// A security advisory of the RustSec database
+#[derive(Debug, Clone)]
+pub struct Advisory {
+    pub id: String,
+    pub package: String,
+    /// Versions with the fix
+    patched: Vec<VersionReq>,
+    /// Versions which never had the issue
+    unaffected: Vec<VersionReq>,
+}
+
+impl Advisory {
+    /// Whether a version of the package is vulnerable
+    pub fn affects(&self, version: &Version) -> bool {
+        !self.patched.iter().any(|req| req.matches(version))
+            && !self.unaffected.iter().any(|req| req.matches(version))
+    }
+
+    /// Parse the TOML metadata of an advisory, `None` for informational
+    /// advisories, e.g. about unmaintained crates, and withdrawn ones
+    fn parse(metadata: &str) -> CargoResult<Option<Advisory>> {
+        let value: Value = ::toml::from_str(metadata)?;
+        let advisory = match value.get("advisory") {
+            Some(advisory) => advisory,
+            None => return Ok(None),
+        };
+        if advisory.get("informational").is_some() || advisory.get("withdrawn").is_some() {
+            return Ok(None);
+        }
+        let versions = value.get("versions");
+        let id = advisory.get("id").and_then(|v| v.as_str());
+        let package = advisory.get("package").and_then(|v| v.as_str());
+        Ok(match (id, package) {
+            (Some(id), Some(package)) => Some(Advisory {
+                id: id.to_owned(),
+                package: package.to_owned(),
+                // older advisories keep the versions in the `advisory` table
+                patched: requirements(advisory.get("patched_versions"))
+                    .into_iter()
+                    .chain(requirements(versions.and_then(|v| v.get("patched"))))
+                    .collect(),
+                unaffected: requirements(advisory.get("unaffected_versions"))
+                    .into_iter()
+                    .chain(requirements(versions.and_then(|v| v.get("unaffected"))))
+                    .collect(),
+            }),
+            _ => None,
+        })
+    }
+}
+
+/// Security advisories by affected crate
+#[derive(Debug, Default)]
+pub struct AdvisoryDatabase {
+    advisories: HashMap<String, Vec<Advisory>>,
+}
+
+impl AdvisoryDatabase {
+    /// Fetch the RustSec database into `advisory-db` in the cache directory,
+    /// i.e. where `cargo audit` keeps it as well by default, and load it
+    ///
+    /// A previously fetched copy is used if updating it fails
+    pub fn fetch(config: &Config, cache_dir: &Path) -> CargoResult<AdvisoryDatabase> {
+        let path = cache_dir.join("advisory-db");
+        let updated = if path.join(".git").exists() {
+            Repository::open(&path).and_then(|repo| {
+                repo.find_remote("origin")?
+                    .fetch(&[ADVISORY_DB_BRANCH], None, None)?;
+                let head = repo.revparse_single("FETCH_HEAD")?;
+                repo.reset(&head, ResetType::Hard, None)
+            })
+        } else {
+            Repository::clone(ADVISORY_DB_URL, &path).map(|_| ())
+        };
+        if let Err(e) = updated {
+            if !path.join("crates").exists() {
+                return Err(e.into());
+            }
+            config
+                .shell()
+                .warn(format!("failed to update the advisory database: {}", e))?;
+        }
+        AdvisoryDatabase::load(&path)
+    }
+
+    /// Load the advisories of a checkout of the database
+    pub fn load(path: &Path) -> CargoResult<AdvisoryDatabase> {
+        let mut db = AdvisoryDatabase::default();
+        for krate in fs::read_dir(path.join("crates"))? {
+            let krate = krate?.path();
+            if !krate.is_dir() {
+                continue;
+            }
+            for file in fs::read_dir(krate)? {
+                let file = file?.path();
+                let mut contents = String::new();
+                File::open(&file)?.read_to_string(&mut contents)?;
+                let metadata = match file.extension().and_then(|e| e.to_str()) {
+                    Some("toml") => contents.as_str(),
+                    // the metadata is the leading ```toml block of the markdown
+                    Some("md") => match front_matter(&contents) {
+                        Some(metadata) => metadata,
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                if let Some(advisory) = Advisory::parse(metadata)? {
+                    db.advisories
+                        .entry(advisory.package.clone())
+                        .or_insert_with(Vec::new)
+                        .push(advisory);
+                }
+            }
+        }
+        Ok(db)
+    }
+
+    /// Advisories affecting a version of a crate
+    pub fn affecting(&self, name: &str, version: &Version) -> Vec<&Advisory> {
+        self.advisories
+            .get(name)
+            .map(|advisories| advisories.iter().filter(|a| a.affects(version)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Record the advisories which upgrading a row would fix, i.e. which
+    /// affect the version in use but not the latest or else compatible one
+    pub fn annotate_fixes(&self, line: &mut Line) {
+        let current = match Version::parse(&line.project) {
+            Ok(current) => current,
+            Err(_) => return,
+        };
+        let candidate = Version::parse(&line.latest).or_else(|_| Version::parse(&line.compat));
+        let candidate = match candidate {
+            Ok(candidate) => candidate,
+            Err(_) => return,
+        };
+        let fixed: Vec<_> = self.affecting(line.crate_name(), &current)
+            .into_iter()
+            .filter(|advisory| !advisory.affects(&candidate))
+            .map(|advisory| advisory.id.clone())
+            .collect();
+        if !fixed.is_empty() {
+            line.fixed_advisories = Some(fixed);
+        }
+    }
+
+    /// Record the advisories affecting the version in use of a row, with
+    /// `--vulnerabilities`
+    pub fn annotate_vulnerabilities(&self, line: &mut Line) {
+        let current = match Version::parse(&line.project) {
+            Ok(current) => current,
+            Err(_) => return,
+        };
+        let advisories: Vec<_> = self.affecting(line.crate_name(), &current)
+            .into_iter()
+            .map(|advisory| advisory.id.clone())
+            .collect();
+        if !advisories.is_empty() {
+            line.advisories = Some(advisories);
+        }
+    }
+}
+
+/// Parse an array of version requirements, skipping invalid ones
+fn requirements(value: Option<&Value>) -> Vec<VersionReq> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|reqs| {
+            reqs.iter()
+                .filter_map(|r| r.as_str())
+                .filter_map(|r| VersionReq::parse(r).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn front_matter(markdown: &str) -> Option<&str> {
+    let start = markdown.find("```toml")? + "```toml".len();
+    let end = markdown[start..].find("```")?;
+    Some(&markdown[start..start + end])
+}
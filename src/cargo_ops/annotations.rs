@@ -0,0 +1,63 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use cargo::util::CargoResult;
+
+use super::OutdatedOptions;
+use super::advisories::AdvisoryDatabase;
+use super::artifact::declared_artifacts;
+use super::inheritance::inherited_dependencies;
+use super::policy::annotate_policy;
+use super::project_config::ProjectConfig;
+use super::report::Line;
+
+/// What the rows of a root are annotated with without looking anything up:
+/// its manifest, the advisory database and the project configuration
+pub struct LocalAnnotations<'a> {
+    /// Artifacts of the artifact dependencies, by kind and crate name
+    artifacts: HashMap<(String, String), String>,
+    /// Dependencies inheriting their requirement from the workspace
+    inherited: HashSet<String>,
+    advisories: Option<&'a AdvisoryDatabase>,
+    vulnerabilities: bool,
+    project_config: &'a ProjectConfig,
+    zero_major_as_breaking: bool,
+}
+
+impl<'a> LocalAnnotations<'a> {
+    pub fn new(
+        options: &OutdatedOptions,
+        manifest_path: &Path,
+        advisories: Option<&'a AdvisoryDatabase>,
+        project_config: &'a ProjectConfig,
+    ) -> CargoResult<LocalAnnotations<'a>> {
+        Ok(LocalAnnotations {
+            artifacts: declared_artifacts(manifest_path)?,
+            inherited: inherited_dependencies(manifest_path)?.into_iter().collect(),
+            advisories: advisories,
+            vulnerabilities: options.flag_vulnerabilities,
+            project_config: project_config,
+            zero_major_as_breaking: options.flag_zero_major_as_breaking,
+        })
+    }
+
+    /// Annotate every row in a single pass
+    pub fn annotate(&self, lines: &mut [Line]) {
+        for line in lines.iter_mut() {
+            // only direct dependencies are declared in this manifest
+            if !line.name.contains("->") {
+                let key = (line.kind.clone().unwrap_or_default(), line.name.clone());
+                line.artifact = self.artifacts.get(&key).cloned();
+                line.inherited = self.inherited.contains(&line.name);
+            }
+            if let Some(advisories) = self.advisories {
+                advisories.annotate_fixes(line);
+                if self.vulnerabilities {
+                    advisories.annotate_vulnerabilities(line);
+                }
+            }
+            line.reason = self.project_config.reason(line.crate_name()).map(String::from);
+            annotate_policy(self.project_config, self.zero_major_as_breaking, line);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use cargo::util::{CargoResult, Config};
+use flate2::read::GzDecoder;
+use serde_json::{self, Value};
+
+use super::crates_io::http_get;
+use super::report::Line;
+
+const DOCS_RS_URL: &str = "https://docs.rs/crate";
+
+/// Public items of a crate version by path, e.g. `clap::Arg`, with their kind
+type ApiItems = HashMap<String, String>;
+
+/// Rustdoc JSON of published versions, as built by docs.rs
+///
+/// Downloads are kept in the `rustdoc-json` directory of the cache directory
+/// as `<name>-<version>.json`, where JSON generated beforehand, e.g. for
+/// versions docs.rs has no JSON for, is picked up as well
+pub struct RustdocJson<'cfg> {
+    config: &'cfg Config,
+    dir: PathBuf,
+    cache: HashMap<(String, String), Option<ApiItems>>,
+}
+
+impl<'cfg> RustdocJson<'cfg> {
+    /// Keep the downloads under `cache_dir`, the cache directory
+    pub fn new(config: &'cfg Config, cache_dir: PathBuf) -> RustdocJson<'cfg> {
+        RustdocJson {
+            config: config,
+            dir: cache_dir.join("rustdoc-json"),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Number of public items removed or changed in kind between two
+    /// versions, `None` if the JSON of either one is not available
+    pub fn breaking_changes(
+        &mut self,
+        name: &str,
+        from: &str,
+        to: &str,
+    ) -> CargoResult<Option<usize>> {
+        self.load(name, from)?;
+        self.load(name, to)?;
+        let from = &self.cache[&(name.to_owned(), from.to_owned())];
+        let to = &self.cache[&(name.to_owned(), to.to_owned())];
+        Ok(match (from.as_ref(), to.as_ref()) {
+            (Some(from), Some(to)) => Some(
+                from.iter()
+                    .filter(|&(path, kind)| to.get(path) != Some(kind))
+                    .count(),
+            ),
+            _ => None,
+        })
+    }
+
+    fn load(&mut self, name: &str, version: &str) -> CargoResult<()> {
+        let key = (name.to_owned(), version.to_owned());
+        if self.cache.contains_key(&key) {
+            return Ok(());
+        }
+        let path = self.dir.join(format!("{}-{}.json", name, version));
+        let mut json = Vec::new();
+        if let Ok(mut file) = File::open(&path) {
+            file.read_to_end(&mut json)?;
+        } else {
+            let url = format!("{}/{}/{}/json.gz", DOCS_RS_URL, name, version);
+            if let Some(body) = http_get(self.config, &url)? {
+                GzDecoder::new(&body[..])?.read_to_end(&mut json)?;
+                fs::create_dir_all(&self.dir)?;
+                File::create(&path)?.write_all(&json)?;
+            }
+        }
+        let items = if json.is_empty() {
+            None
+        } else {
+            Some(public_items(&serde_json::from_slice(&json)?))
+        };
+        self.cache.insert(key, items);
+        Ok(())
+    }
+}
+
+/// Record the number of breaking API changes of major upgrades of direct
+/// dependencies, named in `direct`
+pub fn annotate_breaking_changes(
+    rustdoc: &mut RustdocJson,
+    direct: &HashSet<String>,
+    lines: &mut [Line],
+) -> CargoResult<()> {
+    for line in lines.iter_mut() {
+        if line.severity.as_ref().map(|s| s.as_str()) != Some("Major")
+            || !direct.contains(line.crate_name())
+        {
+            continue;
+        }
+        let name = line.crate_name().to_owned();
+        line.breaking_changes = rustdoc.breaking_changes(&name, &line.project, &line.latest)?;
+    }
+    Ok(())
+}
+
+/// Paths and kinds of the items of the documented crate itself, leaving out
+/// those of its dependencies
+fn public_items(json: &Value) -> ApiItems {
+    let mut items = HashMap::new();
+    let paths = match json.get("paths").and_then(|p| p.as_object()) {
+        Some(paths) => paths,
+        None => return items,
+    };
+    for summary in paths.values() {
+        if summary.get("crate_id").and_then(|id| id.as_u64()) != Some(0) {
+            continue;
+        }
+        let path = summary.get("path").and_then(|p| p.as_array());
+        let kind = summary.get("kind").and_then(|k| k.as_str());
+        if let (Some(path), Some(kind)) = (path, kind) {
+            let path: Vec<_> = path.iter().filter_map(|p| p.as_str()).collect();
+            items.insert(path.join("::"), kind.to_owned());
+        }
+    }
+    items
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use cargo::util::CargoResult;
+use toml::Value;
+use toml::value::Table;
+
+/// Keys of artifact dependencies (`-Z bindeps`), which the cargo version
+/// linked in doesn't know about
+const ARTIFACT_KEYS: &[&str] = &["artifact", "target", "lib"];
+
+/// Remove the artifact keys from dependency specs, so that the temporary
+/// project resolves artifact dependencies like ordinary ones
+pub fn strip_artifact_keys(dependencies: &mut Table) {
+    for spec in dependencies.values_mut() {
+        if let Value::Table(ref mut spec) = *spec {
+            for key in ARTIFACT_KEYS {
+                spec.remove(*key);
+            }
+        }
+    }
+}
+
+/// Artifacts of the artifact dependencies the manifest at `manifest_path`
+/// declares, by kind and crate name
+pub fn declared_artifacts(manifest_path: &Path) -> CargoResult<HashMap<(String, String), String>> {
+    let mut contents = String::new();
+    File::open(manifest_path)?.read_to_string(&mut contents)?;
+    let manifest: Value = ::toml::from_str(&contents)?;
+    let mut artifacts = HashMap::new();
+    collect_artifacts(&manifest, &mut artifacts);
+    let targets = manifest.get("target").and_then(|t| t.as_table());
+    for target in targets.into_iter().flat_map(|t| t.values()) {
+        collect_artifacts(target, &mut artifacts);
+    }
+    Ok(artifacts)
+}
+
+/// Artifacts of the dependency tables of a manifest or `[target]` table,
+/// by kind and crate name
+fn collect_artifacts(table: &Value, artifacts: &mut HashMap<(String, String), String>) {
+    let kinds = &[
+        ("dependencies", "Normal"),
+        ("dev-dependencies", "Development"),
+        ("build-dependencies", "Build"),
+    ];
+    for &(key, kind) in kinds {
+        let deps = table.get(key).and_then(|d| d.as_table());
+        for (name, spec) in deps.into_iter().flat_map(|d| d.iter()) {
+            let artifact = match spec.get("artifact") {
+                Some(&Value::String(ref artifact)) => artifact.clone(),
+                Some(&Value::Array(ref artifacts)) => artifacts
+                    .iter()
+                    .filter_map(|a| a.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                _ => continue,
+            };
+            let name = spec.get("package")
+                .and_then(|p| p.as_str())
+                .unwrap_or(name.as_str());
+            artifacts.insert((kind.to_owned(), name.to_owned()), artifact);
+        }
+    }
+}
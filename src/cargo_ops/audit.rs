@@ -0,0 +1,155 @@
+use std::io::{self, Write};
+
+use cargo::util::{CargoError, CargoErrorKind, CargoResult, Config};
+use semver::Version;
+use serde_json;
+use tabwriter::TabWriter;
+
+use super::pkg_status::Severity;
+use super::registry::RegistryIndex;
+
+/// A requirement of a published crate which excludes the latest version
+/// of the dependency
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditLine {
+    pub name: String,
+    pub requirement: String,
+    /// Newest version matching the requirement, i.e. what a fresh build gets
+    pub resolves_to: Option<String>,
+    pub latest: String,
+    pub kind: String,
+    pub platform: Option<String>,
+    pub severity: String,
+}
+
+/// How current the requirements of a published crate version are
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateAudit {
+    pub crate_name: String,
+    pub version: String,
+    /// Number of dependencies the version declares
+    pub dependencies: usize,
+    pub outdated: Vec<AuditLine>,
+}
+
+impl CrateAudit {
+    /// Look up `version` of `name`, the newest release if not given, in the
+    /// crates.io index and check its requirements against the newest
+    /// releases of its dependencies
+    pub fn fetch(
+        config: &Config,
+        name: &str,
+        version: Option<&str>,
+        zero_major_as_breaking: bool,
+    ) -> CargoResult<CrateAudit> {
+        let mut index = RegistryIndex::crates_io(config)?;
+        let summary = match version {
+            Some(version) => {
+                let version = Version::parse(version)?;
+                index
+                    .summaries(name)?
+                    .iter()
+                    .find(|s| *s.version() == version)
+                    .cloned()
+            }
+            None => index
+                .summaries(name)?
+                .iter()
+                .filter(|s| !s.version().is_prerelease())
+                .last()
+                .cloned(),
+        };
+        let summary = summary.ok_or_else(|| {
+            CargoError::from_kind(CargoErrorKind::Msg(format!(
+                "{} {} is not published on crates.io",
+                name,
+                version.unwrap_or("")
+            )))
+        })?;
+
+        let mut outdated = vec![];
+        for dependency in summary.dependencies() {
+            let requirement = dependency.version_req();
+            let versions: Vec<_> = index
+                .versions(dependency.name())?
+                .iter()
+                .filter(|v| !v.is_prerelease())
+                .cloned()
+                .collect();
+            let latest = match versions.last() {
+                Some(latest) if !requirement.matches(latest) => latest,
+                _ => continue,
+            };
+            let resolves_to = versions.iter().rev().find(|v| requirement.matches(v));
+            let severity = match resolves_to {
+                Some(from) => Severity::from_versions(from, latest, zero_major_as_breaking),
+                None => Severity::Major,
+            };
+            outdated.push(AuditLine {
+                name: dependency.name().to_owned(),
+                requirement: requirement.to_string(),
+                resolves_to: resolves_to.map(|v| v.to_string()),
+                latest: latest.to_string(),
+                kind: format!("{:?}", dependency.kind()),
+                platform: dependency.platform().map(|p| p.to_string()),
+                severity: severity.to_string(),
+            });
+        }
+        outdated.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(CrateAudit {
+            crate_name: name.to_owned(),
+            version: summary.version().to_string(),
+            dependencies: summary.dependencies().len(),
+            outdated: outdated,
+        })
+    }
+
+    pub fn print_list(&self) -> CargoResult<()> {
+        println!("{} {}", self.crate_name, self.version);
+        println!("================\n");
+        if self.outdated.is_empty() {
+            println!(
+                "All {} requirements accept the latest versions, yay!",
+                self.dependencies
+            );
+            return Ok(());
+        }
+        let mut tw = TabWriter::new(vec![]);
+        write!(&mut tw, "Name\tRequirement\tResolves to\tLatest\tKind\tPlatform\tSeverity\n")?;
+        write!(&mut tw, "----\t-----------\t-----------\t------\t----\t--------\t--------\n")?;
+        for line in &self.outdated {
+            write!(
+                &mut tw,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                line.name,
+                line.requirement,
+                line.resolves_to.as_ref().map(|v| v.as_str()).unwrap_or("---"),
+                line.latest,
+                line.kind,
+                line.platform.as_ref().map(|p| p.as_str()).unwrap_or("---"),
+                line.severity
+            )?;
+        }
+        tw.flush()?;
+        write!(
+            io::stdout(),
+            "{}",
+            String::from_utf8(tw.into_inner().unwrap()).unwrap()
+        )?;
+        println!(
+            "\n{} of {} requirements exclude the latest version",
+            self.outdated.len(),
+            self.dependencies
+        );
+        io::stdout().flush()?;
+        Ok(())
+    }
+
+    pub fn print_json(&self) -> CargoResult<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        serde_json::to_writer_pretty(&mut stdout, self)?;
+        writeln!(stdout)?;
+        Ok(())
+    }
+}
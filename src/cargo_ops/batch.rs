@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use cargo::util::CargoResult;
+use serde_json;
+
+use super::report::Report;
+
+/// Outcome of the analysis of one project of a batch
+#[derive(Debug, Serialize)]
+pub struct ProjectReport {
+    pub reports: Vec<Report>,
+    pub error: Option<String>,
+}
+
+/// Reports of several projects, keyed by project
+#[derive(Debug, Default, Serialize)]
+pub struct BatchReport {
+    pub projects: BTreeMap<String, ProjectReport>,
+}
+
+impl BatchReport {
+    pub fn insert(&mut self, project: String, result: CargoResult<Vec<Report>>) {
+        let report = match result {
+            Ok(reports) => ProjectReport {
+                reports: reports,
+                error: None,
+            },
+            Err(e) => ProjectReport {
+                reports: vec![],
+                error: Some(e.to_string()),
+            },
+        };
+        self.projects.insert(project, report);
+    }
+
+    /// Number of outdated dependencies in all projects
+    pub fn count(&self) -> i32 {
+        self.projects
+            .values()
+            .map(|project| Report::count(&project.reports))
+            .sum()
+    }
+
+    pub fn print_json(&self) -> CargoResult<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        serde_json::to_writer_pretty(&mut stdout, self)?;
+        writeln!(stdout)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use cargo::core::Workspace;
+use cargo::util::{CargoResult, Config};
+use semver::Version;
+use serde_json;
+use tabwriter::TabWriter;
+use toml::value::{Table, Value};
+
+use super::{is_inherited, Manifest};
+
+/// A requirement on a dependency as declared by a workspace member
+#[derive(Debug, Clone, Serialize)]
+pub struct Declaration {
+    pub member: String,
+    pub requirement: String,
+}
+
+/// A dependency declared by several members with diverging requirements
+#[derive(Debug, Clone, Serialize)]
+pub struct Consolidation {
+    pub name: String,
+    pub declarations: Vec<Declaration>,
+    /// Requirement for the `[workspace.dependencies]` entry, the one with
+    /// the highest lower bound or the entry already there
+    pub suggested: String,
+}
+
+/// Dependencies whose requirements drift apart across the members of a
+/// workspace, with the entries inheriting them would need
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsolidationReport {
+    pub consolidations: Vec<Consolidation>,
+}
+
+impl ConsolidationReport {
+    pub fn check(workspace: &Workspace) -> CargoResult<ConsolidationReport> {
+        let shared = workspace_dependencies(workspace)?;
+        let mut declared: BTreeMap<String, Vec<Declaration>> = BTreeMap::new();
+        for member in workspace.members() {
+            let mut manifest = read_manifest(member.manifest_path())?;
+            for table in manifest.dependency_tables_mut() {
+                for (name, spec) in table.iter() {
+                    if let Some(requirement) = requirement(spec) {
+                        declared
+                            .entry(name.clone())
+                            .or_insert_with(Vec::new)
+                            .push(Declaration {
+                                member: member.name().to_owned(),
+                                requirement: requirement.to_owned(),
+                            });
+                    }
+                }
+            }
+        }
+        let mut consolidations = vec![];
+        for (name, mut declarations) in declared {
+            let members: BTreeSet<_> = declarations.iter().map(|d| d.member.clone()).collect();
+            let requirements: BTreeSet<_> =
+                declarations.iter().map(|d| d.requirement.clone()).collect();
+            if members.len() < 2 || requirements.len() < 2 {
+                continue;
+            }
+            declarations.sort_by(|a, b| a.member.cmp(&b.member));
+            let suggested = match shared.get(&name) {
+                Some(requirement) => requirement.clone(),
+                None => highest_requirement(&declarations),
+            };
+            consolidations.push(Consolidation {
+                name: name,
+                declarations: declarations,
+                suggested: suggested,
+            });
+        }
+        Ok(ConsolidationReport {
+            consolidations: consolidations,
+        })
+    }
+
+    /// Add the suggested entries to `[workspace.dependencies]` and make the
+    /// members inherit them
+    ///
+    /// The manifests are rewritten from their parsed form, so that comments
+    /// and formatting are lost.
+    pub fn apply(&self, config: &Config, workspace: &Workspace) -> CargoResult<()> {
+        if self.consolidations.is_empty() {
+            return Ok(());
+        }
+        let root_path = workspace.root().join("Cargo.toml");
+        let mut manifests = BTreeMap::new();
+        manifests.insert(root_path.clone(), read_manifest(&root_path)?);
+        for member in workspace.members() {
+            let path = member.manifest_path().to_path_buf();
+            if !manifests.contains_key(&path) {
+                let manifest = read_manifest(&path)?;
+                manifests.insert(path, manifest);
+            }
+        }
+        let mut changed = BTreeSet::new();
+        {
+            let root = manifests.get_mut(&root_path).unwrap();
+            let shared = root.raw
+                .entry("workspace".to_owned())
+                .or_insert_with(|| Value::Table(Table::new()));
+            if let Value::Table(ref mut shared) = *shared {
+                let dependencies = shared
+                    .entry("dependencies".to_owned())
+                    .or_insert_with(|| Value::Table(Table::new()));
+                if let Value::Table(ref mut dependencies) = *dependencies {
+                    for consolidation in &self.consolidations {
+                        if !dependencies.contains_key(&consolidation.name) {
+                            dependencies.insert(
+                                consolidation.name.clone(),
+                                Value::String(consolidation.suggested.clone()),
+                            );
+                            changed.insert(root_path.clone());
+                        }
+                    }
+                }
+            }
+        }
+        for (path, manifest) in &mut manifests {
+            for table in manifest.dependency_tables_mut() {
+                for consolidation in &self.consolidations {
+                    if let Some(spec) = table.get_mut(&consolidation.name) {
+                        if inherit(spec) {
+                            changed.insert(path.clone());
+                        }
+                    }
+                }
+            }
+        }
+        for path in changed {
+            config.shell().status("Updating", path.display())?;
+            let mut file = File::create(&path)?;
+            write!(file, "{}", manifests[&path].to_toml()?)?;
+        }
+        Ok(())
+    }
+
+    pub fn print_list(&self) -> CargoResult<()> {
+        if self.consolidations.is_empty() {
+            println!("All requirements shared by workspace members agree, yay!");
+            return Ok(());
+        }
+        let mut tw = TabWriter::new(vec![]);
+        write!(&mut tw, "Name\tMember\tRequirement\n")?;
+        write!(&mut tw, "----\t------\t-----------\n")?;
+        for consolidation in &self.consolidations {
+            for (i, declaration) in consolidation.declarations.iter().enumerate() {
+                let name = if i == 0 { consolidation.name.as_str() } else { "" };
+                write!(
+                    &mut tw,
+                    "{}\t{}\t{}\n",
+                    name, declaration.member, declaration.requirement
+                )?;
+            }
+        }
+        tw.flush()?;
+        write!(
+            io::stdout(),
+            "{}",
+            String::from_utf8(tw.into_inner().unwrap()).unwrap()
+        )?;
+        println!();
+        println!("Suggested [workspace.dependencies] entries:");
+        for consolidation in &self.consolidations {
+            println!("{} = \"{}\"", consolidation.name, consolidation.suggested);
+        }
+        io::stdout().flush()?;
+        Ok(())
+    }
+
+    pub fn print_json(&self) -> CargoResult<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        serde_json::to_writer_pretty(&mut stdout, self)?;
+        writeln!(stdout)?;
+        Ok(())
+    }
+}
+
+fn read_manifest(path: &Path) -> CargoResult<Manifest> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Manifest::parse(&contents)
+}
+
+/// Requirements of the `[workspace.dependencies]` table of the root manifest
+fn workspace_dependencies(workspace: &Workspace) -> CargoResult<BTreeMap<String, String>> {
+    let root_path = workspace.root().join("Cargo.toml");
+    let mut requirements = BTreeMap::new();
+    if !root_path.is_file() {
+        return Ok(requirements);
+    }
+    let mut root = read_manifest(&root_path)?;
+    let dependencies = root.table_mut("workspace")
+        .and_then(|workspace| workspace.get("dependencies"))
+        .and_then(|dependencies| dependencies.as_table());
+    if let Some(dependencies) = dependencies {
+        for (name, spec) in dependencies {
+            if let Some(requirement) = requirement(spec) {
+                requirements.insert(name.clone(), requirement.to_owned());
+            }
+        }
+    }
+    Ok(requirements)
+}
+
+/// The requirement of a dependency on a registry crate, i.e. neither a path
+/// or git dependency, a renamed one or one already inherited
+fn requirement(spec: &Value) -> Option<&str> {
+    if is_inherited(spec) {
+        return None;
+    }
+    match *spec {
+        Value::String(ref requirement) => Some(requirement.as_str()),
+        Value::Table(ref spec) => {
+            let elsewhere = ["path", "git", "package", "registry"]
+                .iter()
+                .any(|key| spec.contains_key(*key));
+            if elsewhere {
+                None
+            } else {
+                spec.get("version").and_then(|v| v.as_str())
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Turn a dependency spec into one inheriting the workspace's, keeping keys
+/// like `features` or `optional`, unless it isn't on a registry crate
+fn inherit(spec: &mut Value) -> bool {
+    if requirement(spec).is_none() {
+        return false;
+    }
+    let mut inherited = match *spec {
+        Value::Table(ref spec) => spec.clone(),
+        _ => Table::new(),
+    };
+    inherited.remove("version");
+    inherited.insert("workspace".to_owned(), Value::Boolean(true));
+    *spec = Value::Table(inherited);
+    true
+}
+
+/// The requirement with the highest lower bound, the first one if none of
+/// them has one
+fn highest_requirement(declarations: &[Declaration]) -> String {
+    let mut highest: Option<(Version, &str)> = None;
+    for declaration in declarations {
+        if let Some(bound) = lower_bound(&declaration.requirement) {
+            let higher = match highest {
+                Some((ref version, _)) => bound > *version,
+                None => true,
+            };
+            if higher {
+                highest = Some((bound, &declaration.requirement));
+            }
+        }
+    }
+    match highest {
+        Some((_, requirement)) => requirement.to_owned(),
+        None => declarations[0].requirement.clone(),
+    }
+}
+
+/// The version the first comparator of a requirement starts at, with missing
+/// components being zero, e.g. `1.2` for `^1.2, <1.5`
+fn lower_bound(requirement: &str) -> Option<Version> {
+    let first = requirement.split(',').next().unwrap_or("").trim();
+    if first.starts_with('<') || first.contains('*') {
+        return None;
+    }
+    let version = first.trim_left_matches(|c: char| "^~=>".contains(c)).trim();
+    let mut parts: Vec<_> = version.splitn(3, '.').collect();
+    while parts.len() < 3 {
+        parts.push("0");
+    }
+    Version::parse(&parts.join(".")).ok()
+}
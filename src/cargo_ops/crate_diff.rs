@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use cargo::core::Summary;
+use cargo::util::{CargoError, CargoErrorKind, CargoResult, Config};
+use semver::Version;
+use serde_json;
+use tabwriter::TabWriter;
+
+use super::registry::RegistryIndex;
+
+/// A dependency whose requirement differs between two versions of a crate,
+/// `None` on the side it is not declared on
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyChange {
+    pub name: String,
+    pub kind: String,
+    pub platform: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Dependencies added, removed or re-bounded between two published versions
+/// of a crate
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateDiff {
+    pub crate_name: String,
+    pub from: String,
+    pub to: String,
+    pub changes: Vec<DependencyChange>,
+}
+
+/// Name, kind and platform of a dependency
+type Key = (String, String, Option<String>);
+
+/// Requirements of the dependencies of a version
+type Requirements = BTreeMap<Key, String>;
+
+impl CrateDiff {
+    /// Compare the dependencies of versions `from` and `to` of `name`
+    /// in the crates.io index
+    pub fn fetch(config: &Config, name: &str, from: &str, to: &str) -> CargoResult<CrateDiff> {
+        let mut index = RegistryIndex::crates_io(config)?;
+        let before = requirements(&find_version(&mut index, name, from)?);
+        let after = requirements(&find_version(&mut index, name, to)?);
+        let mut changes = vec![];
+        for (key, req) in &before {
+            if after.get(key) != Some(req) {
+                changes.push(change(key, Some(req), after.get(key)));
+            }
+        }
+        for (key, req) in &after {
+            if !before.contains_key(key) {
+                changes.push(change(key, None, Some(req)));
+            }
+        }
+        changes.sort_by(|a, b| a.name.cmp(&b.name).then(a.kind.cmp(&b.kind)));
+        Ok(CrateDiff {
+            crate_name: name.to_owned(),
+            from: from.to_owned(),
+            to: to.to_owned(),
+            changes: changes,
+        })
+    }
+
+    pub fn print_list(&self) -> CargoResult<()> {
+        if self.changes.is_empty() {
+            println!(
+                "{} {} and {} have the same dependencies",
+                self.crate_name, self.from, self.to
+            );
+            return Ok(());
+        }
+        println!("{} {} -> {}", self.crate_name, self.from, self.to);
+        println!("================\n");
+        let mut tw = TabWriter::new(vec![]);
+        write!(&mut tw, "Name\tKind\tPlatform\tFrom\tTo\n")?;
+        write!(&mut tw, "----\t----\t--------\t----\t--\n")?;
+        for change in &self.changes {
+            write!(
+                &mut tw,
+                "{}\t{}\t{}\t{}\t{}\n",
+                change.name,
+                change.kind,
+                or_dashes(&change.platform),
+                or_dashes(&change.from),
+                or_dashes(&change.to)
+            )?;
+        }
+        tw.flush()?;
+        write!(
+            io::stdout(),
+            "{}",
+            String::from_utf8(tw.into_inner().unwrap()).unwrap()
+        )?;
+        let added = self.changes.iter().filter(|c| c.from.is_none()).count();
+        let removed = self.changes.iter().filter(|c| c.to.is_none()).count();
+        println!(
+            "\n{} added, {} removed, {} re-bounded",
+            added,
+            removed,
+            self.changes.len() - added - removed
+        );
+        io::stdout().flush()?;
+        Ok(())
+    }
+
+    pub fn print_json(&self) -> CargoResult<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        serde_json::to_writer_pretty(&mut stdout, self)?;
+        writeln!(stdout)?;
+        Ok(())
+    }
+}
+
+fn find_version(index: &mut RegistryIndex, name: &str, version: &str) -> CargoResult<Summary> {
+    let parsed = Version::parse(version)?;
+    index
+        .summaries(name)?
+        .iter()
+        .find(|s| *s.version() == parsed)
+        .cloned()
+        .ok_or_else(|| {
+            CargoError::from_kind(CargoErrorKind::Msg(format!(
+                "{} {} is not published on crates.io",
+                name, version
+            )))
+        })
+}
+
+fn requirements(summary: &Summary) -> Requirements {
+    summary
+        .dependencies()
+        .iter()
+        .map(|dep| {
+            let key = (
+                dep.name().to_owned(),
+                format!("{:?}", dep.kind()),
+                dep.platform().map(|p| p.to_string()),
+            );
+            (key, dep.version_req().to_string())
+        })
+        .collect()
+}
+
+fn change(key: &Key, from: Option<&String>, to: Option<&String>) -> DependencyChange {
+    DependencyChange {
+        name: key.0.clone(),
+        kind: key.1.clone(),
+        platform: key.2.clone(),
+        from: from.cloned(),
+        to: to.cloned(),
+    }
+}
+
+fn or_dashes(value: &Option<String>) -> &str {
+    value.as_ref().map(|v| v.as_str()).unwrap_or("---")
+}
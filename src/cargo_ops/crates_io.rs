@@ -0,0 +1,229 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cargo::ops;
+use cargo::util::{CargoError, CargoErrorKind, CargoResult, Config};
+use semver::Version;
+use serde_json;
+
+const API_URL: &str = "https://crates.io/api/v1/crates";
+
+/// Metadata of a crate published on crates.io
+#[derive(Debug, Clone, Deserialize)]
+pub struct CrateMetadata {
+    #[serde(rename = "crate")]
+    pub krate: CrateInfo,
+    pub versions: Vec<VersionInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CrateInfo {
+    pub name: String,
+    #[serde(default)]
+    pub repository: Option<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+}
+
+/// Metadata of a single published version
+#[derive(Debug, Clone, Deserialize)]
+pub struct VersionInfo {
+    pub num: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub yanked: bool,
+    /// Minimum supported Rust version declared by the version
+    #[serde(default)]
+    pub rust_version: Option<String>,
+    /// SPDX license expression
+    #[serde(default)]
+    pub license: Option<String>,
+    #[serde(default)]
+    pub edition: Option<String>,
+    /// Features by name, with the features and dependencies they enable
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+    /// Unknown for versions published before crates.io recorded it
+    #[serde(default)]
+    pub published_by: Option<User>,
+}
+
+/// A crates.io account
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    pub login: String,
+}
+
+#[derive(Deserialize)]
+struct Owners {
+    users: Vec<User>,
+}
+
+impl VersionInfo {
+    /// Days elapsed since the version was published
+    pub fn age_in_days(&self) -> Option<u64> {
+        let published = days_from_date(&self.created_at)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() / 86_400;
+        Some(now.saturating_sub(published))
+    }
+}
+
+impl CrateMetadata {
+    /// Metadata of a specific version
+    pub fn version(&self, version: &Version) -> Option<&VersionInfo> {
+        self.versions
+            .iter()
+            .find(|v| Version::parse(&v.num).ok().as_ref() == Some(version))
+    }
+
+    /// Accounts which published any version up to `version`
+    pub fn publishers_until(&self, version: &Version) -> HashSet<&str> {
+        self.versions
+            .iter()
+            .filter(|v| Version::parse(&v.num).map(|v| v <= *version).unwrap_or(false))
+            .filter_map(|v| v.published_by.as_ref())
+            .map(|user| user.login.as_str())
+            .collect()
+    }
+}
+
+/// A client of the crates.io web API
+///
+/// Unlike the registry index, the API knows about publication dates and
+/// other metadata, at the cost of one request per crate, which is why
+/// responses are kept for the lifetime of the client
+pub struct CratesIo<'cfg> {
+    config: &'cfg Config,
+    cache: HashMap<String, Option<CrateMetadata>>,
+    owners: HashMap<String, Vec<String>>,
+    /// Failed requests, if they are collected rather than errors
+    failures: Option<Vec<String>>,
+}
+
+impl<'cfg> CratesIo<'cfg> {
+    pub fn new(config: &'cfg Config) -> CratesIo<'cfg> {
+        CratesIo {
+            config: config,
+            cache: HashMap::new(),
+            owners: HashMap::new(),
+            failures: None,
+        }
+    }
+
+    /// A client treating the crates it fails to query as unpublished, with
+    /// a warning, collecting the failures for `take_failures`
+    pub fn tolerant(config: &'cfg Config) -> CratesIo<'cfg> {
+        CratesIo {
+            failures: Some(vec![]),
+            ..CratesIo::new(config)
+        }
+    }
+
+    /// The failures collected since the last call
+    pub fn take_failures(&mut self) -> Vec<String> {
+        self.failures
+            .as_mut()
+            .map(|failures| failures.drain(..).collect())
+            .unwrap_or_default()
+    }
+
+    /// Metadata of a crate, `None` if it is not published on crates.io
+    pub fn crate_metadata(&mut self, name: &str) -> CargoResult<Option<&CrateMetadata>> {
+        if !self.cache.contains_key(name) {
+            let metadata = match self.get(name, &format!("{}/{}", API_URL, name))? {
+                Some(body) => Some(serde_json::from_slice(&body)?),
+                None => None,
+            };
+            self.cache.insert(name.to_owned(), metadata);
+        }
+        Ok(self.cache[name].as_ref())
+    }
+
+    /// Logins of the users and teams owning a crate
+    pub fn owners(&mut self, name: &str) -> CargoResult<&[String]> {
+        if !self.owners.contains_key(name) {
+            let owners = match self.get(name, &format!("{}/{}/owners", API_URL, name))? {
+                Some(body) => {
+                    let owners: Owners = serde_json::from_slice(&body)?;
+                    owners.users.into_iter().map(|user| user.login).collect()
+                }
+                None => vec![],
+            };
+            self.owners.insert(name.to_owned(), owners);
+        }
+        Ok(&self.owners[name])
+    }
+
+    /// Metadata of a version given as shown in the report, `None` for values
+    /// like `---` or `Removed` and for versions unknown to crates.io
+    pub fn version_info(&mut self, name: &str, version: &str) -> CargoResult<Option<&VersionInfo>> {
+        let version = match Version::parse(version) {
+            Ok(version) => version,
+            Err(_) => return Ok(None),
+        };
+        Ok(self.crate_metadata(name)?
+            .and_then(|metadata| metadata.version(&version)))
+    }
+
+    /// Nothing is fetched while the network is off, e.g. with `--offline`
+    fn get(&mut self, name: &str, url: &str) -> CargoResult<Option<Vec<u8>>> {
+        if !self.config.network_allowed() {
+            return Ok(None);
+        }
+        match (http_get(self.config, url), self.failures.as_mut()) {
+            (Err(e), Some(failures)) => {
+                let failure = format!("failed to look up {} on crates.io: {}", name, e);
+                self.config.shell().warn(&failure)?;
+                failures.push(failure);
+                Ok(None)
+            }
+            (result, _) => result,
+        }
+    }
+}
+
+/// Body of a successful GET request, following redirects, `None` on 404
+pub fn http_get(config: &Config, url: &str) -> CargoResult<Option<Vec<u8>>> {
+    let mut handle = ops::http_handle(config)?;
+    handle.get(true)?;
+    handle.follow_location(true)?;
+    handle.url(url)?;
+    let mut body = Vec::new();
+    {
+        let mut transfer = handle.transfer();
+        transfer.write_function(|data| {
+            body.extend_from_slice(data);
+            Ok(data.len())
+        })?;
+        transfer.perform()?;
+    }
+    match handle.response_code()? {
+        200 => Ok(Some(body)),
+        404 => Ok(None),
+        code => Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+            "Failed to query {}, got status code {}",
+            url, code
+        )))),
+    }
+}
+
+/// Days since the Unix epoch of the date part of an RFC 3339 timestamp
+fn days_from_date(timestamp: &str) -> Option<u64> {
+    let mut parts = timestamp.get(..10)?.split('-').map(|p| p.parse::<i64>());
+    let (y, m, d) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(y)), Some(Ok(m)), Some(Ok(d))) => (y, m, d),
+        _ => return None,
+    };
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    if days < 0 {
+        None
+    } else {
+        Some(days as u64)
+    }
+}
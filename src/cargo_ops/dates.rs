@@ -0,0 +1,23 @@
+use cargo::util::CargoResult;
+
+use super::crates_io::CratesIo;
+use super::report::Line;
+
+/// Set how old the version in use of each row is and when its latest
+/// version was released, with `--show-dates`
+///
+/// Both are unknown for crates which aren't on crates.io, and the release
+/// date is never shown for values like `---` or `Removed`.
+pub fn annotate_dates(crates_io: &mut CratesIo, lines: &mut [Line]) -> CargoResult<()> {
+    for line in lines.iter_mut() {
+        let name = line.crate_name().to_owned();
+        line.project_age_days = crates_io
+            .version_info(&name, &line.project)?
+            .and_then(|version| version.age_in_days());
+        line.latest_released = crates_io
+            .version_info(&name, &line.latest)?
+            .and_then(|version| version.created_at.get(..10))
+            .map(String::from);
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use cargo::util::CargoResult;
+use git2::Repository;
+
+/// Projects to analyze, as pairs of a key and the project directory
+///
+/// `path` is either a file listing one checkout per line, relative to the
+/// file and with `#` starting a comment, or a directory under which every
+/// directory containing a `Cargo.toml` is a project
+pub fn projects(path: &Path) -> CargoResult<Vec<(String, PathBuf)>> {
+    if path.is_dir() {
+        return projects_under(path);
+    }
+    let base = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut projects = vec![];
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        let entry = line.split('#').next().unwrap_or("").trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let mut dir = base.join(entry);
+        if dir.is_file() {
+            dir.pop();
+        }
+        projects.push((entry.to_owned(), dir));
+    }
+    Ok(projects)
+}
+
+/// Projects found under `root`, keyed by their path relative to it
+pub fn projects_under(root: &Path) -> CargoResult<Vec<(String, PathBuf)>> {
+    let mut projects = vec![];
+    discover(root, root, &mut projects)?;
+    Ok(projects)
+}
+
+/// Projects at or under `paths`, relative to `base`, keyed by the path as
+/// given, or with the path relative to it appended for projects found
+/// under a directory without a `Cargo.toml` of its own
+pub fn projects_at(base: &Path, paths: &[String]) -> CargoResult<Vec<(String, PathBuf)>> {
+    let mut projects = vec![];
+    for path in paths {
+        let mut dir = base.join(path);
+        if dir.is_file() {
+            dir.pop();
+        }
+        for (key, project) in projects_under(&dir)? {
+            let key = if key == "." {
+                path.clone()
+            } else {
+                Path::new(path).join(key).to_string_lossy().into_owned()
+            };
+            projects.push((key, project));
+        }
+    }
+    Ok(projects)
+}
+
+/// Projects inside the git submodules of the repository containing `dir`,
+/// keyed by their path relative to the repository root
+pub fn submodule_projects(dir: &Path) -> CargoResult<Vec<(String, PathBuf)>> {
+    let repo = match Repository::discover(dir) {
+        Ok(repo) => repo,
+        Err(_) => return Ok(vec![]),
+    };
+    let workdir = match repo.workdir() {
+        Some(workdir) => workdir.to_path_buf(),
+        None => return Ok(vec![]),
+    };
+    let mut projects = vec![];
+    for submodule in repo.submodules()? {
+        let path = workdir.join(submodule.path());
+        for (key, project) in projects_under(&path)? {
+            let key = if key == "." {
+                submodule.path().to_string_lossy().into_owned()
+            } else {
+                submodule.path().join(key).to_string_lossy().into_owned()
+            };
+            projects.push((key, project));
+        }
+    }
+    Ok(projects)
+}
+
+/// Packages under the workspace root `root` which are neither members nor
+/// inside a nested repository, keyed by their path relative to `root`
+///
+/// These are typically examples, tools or fuzz targets that are excluded
+/// from the workspace and hence tend to be forgotten
+pub fn standalone_projects(
+    root: &Path,
+    members: &HashSet<PathBuf>,
+) -> CargoResult<Vec<(String, PathBuf)>> {
+    let mut projects = vec![];
+    discover_standalone(root, root, members, &mut projects)?;
+    Ok(projects)
+}
+
+fn discover_standalone(
+    root: &Path,
+    dir: &Path,
+    members: &HashSet<PathBuf>,
+    projects: &mut Vec<(String, PathBuf)>,
+) -> CargoResult<()> {
+    for entry in subdirectories(dir)? {
+        if entry.join(".git").exists() {
+            continue;
+        }
+        if entry.join("Cargo.toml").is_file() && !members.contains(&entry) {
+            projects.push((relative_key(root, &entry), entry));
+        } else {
+            discover_standalone(root, &entry, members, projects)?;
+        }
+    }
+    Ok(())
+}
+
+/// Collect project directories, without descending into projects, since
+/// nested packages belong to the workspace of the enclosing one or are
+/// excluded from it
+fn discover(root: &Path, dir: &Path, projects: &mut Vec<(String, PathBuf)>) -> CargoResult<()> {
+    if dir.join("Cargo.toml").is_file() {
+        projects.push((relative_key(root, dir), dir.to_path_buf()));
+        return Ok(());
+    }
+    for entry in subdirectories(dir)? {
+        discover(root, &entry, projects)?;
+    }
+    Ok(())
+}
+
+fn relative_key(root: &Path, dir: &Path) -> String {
+    match dir.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_owned(),
+        Ok(relative) => relative.to_string_lossy().into_owned(),
+        Err(_) => dir.to_string_lossy().into_owned(),
+    }
+}
+
+/// Sorted subdirectories, except for build and hidden directories
+fn subdirectories(dir: &Path) -> CargoResult<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.retain(|entry| {
+        let skip = entry
+            .file_name()
+            .map(|name| {
+                let name = name.to_string_lossy();
+                name.starts_with('.') || name == "target"
+            })
+            .unwrap_or(true);
+        !skip && entry.is_dir()
+    });
+    entries.sort();
+    Ok(entries)
+}
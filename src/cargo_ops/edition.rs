@@ -0,0 +1,55 @@
+use std::fs::File;
+use std::io::Read;
+
+use cargo::util::CargoResult;
+use toml::Value;
+
+use super::crates_io::CratesIo;
+use super::elaborate_workspace::ElaborateWorkspace;
+use super::report::Line;
+
+/// Edition of packages which don't declare one
+const DEFAULT_EDITION: &str = "2015";
+
+/// The newest edition any package in the graph is written in
+///
+/// Editions are years, so that they compare as strings.
+pub fn newest_edition(ela: &ElaborateWorkspace) -> CargoResult<String> {
+    let mut newest = DEFAULT_EDITION.to_owned();
+    for pkg in ela.pkgs.values() {
+        let mut contents = String::new();
+        File::open(pkg.manifest_path())?.read_to_string(&mut contents)?;
+        let manifest: Value = ::toml::from_str(&contents)?;
+        let edition = manifest
+            .get("package")
+            .and_then(|p| p.get("edition"))
+            .and_then(|e| e.as_str());
+        if let Some(edition) = edition {
+            if edition > newest.as_str() {
+                newest = edition.to_owned();
+            }
+        }
+    }
+    Ok(newest)
+}
+
+/// Mark rows whose latest version is written in an edition newer than
+/// `newest`, the newest edition in the graph
+pub fn annotate_editions(
+    crates_io: &mut CratesIo,
+    newest: &str,
+    lines: &mut [Line],
+) -> CargoResult<()> {
+    for line in lines.iter_mut() {
+        let name = line.crate_name().to_owned();
+        // unknown for versions published before crates.io recorded it
+        let edition = crates_io
+            .version_info(&name, &line.latest)?
+            .and_then(|version| version.edition.clone());
+        line.latest_edition = match edition {
+            Some(edition) if edition.as_str() > newest => Some(edition),
+            _ => None,
+        };
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use cargo::util::CargoResult;
+use semver::Version;
+
+use super::crates_io::CratesIo;
+use super::report::Line;
+
+/// Skipped versions beyond this many are not told apart
+const MAX_SKIPPED: u32 = 10;
+
+/// Fill in a rough estimate of the work upgrading each row takes
+///
+/// The score adds up the size of the bump, the number of versions skipped,
+/// features the latest version dropped, a newer edition or `rust-version`
+/// required, and the number of packages depending on the crate, given by
+/// `dependents`. It only means something relative to other rows.
+pub fn annotate_effort(
+    crates_io: &mut CratesIo,
+    dependents: &HashMap<String, usize>,
+    lines: &mut [Line],
+) -> CargoResult<()> {
+    for line in lines.iter_mut() {
+        let bump = match line.severity.as_ref().map(|s| s.as_str()) {
+            Some("Major") => 5,
+            Some("Minor") => 2,
+            Some("Patch") => 1,
+            _ => continue,
+        };
+        let name = line.crate_name().to_owned();
+        let mut effort = bump + dependents.get(&name).cloned().unwrap_or(0) as u32;
+        if line.latest_rust_version.is_some() || line.compat_rust_version.is_some() {
+            effort += 2;
+        }
+        let current = Version::parse(&line.project);
+        let target = Version::parse(&line.latest).or_else(|_| Version::parse(&line.compat));
+        if let (Ok(current), Ok(target)) = (current, target) {
+            if let Some(metadata) = crates_io.crate_metadata(&name)? {
+                let skipped = metadata
+                    .versions
+                    .iter()
+                    .filter(|v| !v.yanked)
+                    .filter_map(|v| Version::parse(&v.num).ok())
+                    .filter(|v| v.pre.is_empty() && *v > current && *v < target)
+                    .count() as u32;
+                effort += skipped.min(MAX_SKIPPED);
+                if let (Some(from), Some(to)) =
+                    (metadata.version(&current), metadata.version(&target))
+                {
+                    effort += from.features
+                        .keys()
+                        .filter(|feature| !to.features.contains_key(*feature))
+                        .count() as u32;
+                    // unknown for versions published before crates.io recorded it
+                    if from.edition.is_some() && to.edition.is_some()
+                        && from.edition != to.edition
+                    {
+                        effort += 2;
+                    }
+                }
+            }
+        }
+        line.effort = Some(effort);
+    }
+    Ok(())
+}
@@ -1,13 +1,25 @@
-use std::io::{self, Write};
-use std::collections::{HashMap, HashSet};
+use std::io::{self, Read, Write};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::path::Path;
 
 use cargo::core::{Dependency, Package, PackageId, Workspace};
+use cargo::core::dependency::Kind;
+use cargo::core::shell::Verbosity;
 use cargo::ops::{self, Packages};
 use cargo::util::{CargoError, CargoErrorKind, CargoResult, Config};
+use semver::Version;
 use tabwriter::TabWriter;
+use toml::Value;
+use toml::value::Table;
 
-use super::Options;
+use super::{dependency_tables, OutdatedOptions};
 use super::pkg_status::*;
+use super::platform::TargetInfo;
+use super::report::{GraphChange, Line};
+use super::registry::RegistryIndex;
+use super::stats::Stats;
+use super::successors;
 
 /// An elaborate workspace containing resolved dependencies and
 /// the update status of packages
@@ -23,13 +35,31 @@ pub struct ElaborateWorkspace<'ela> {
     pub pkg_status: HashMap<(Option<PackageId>, Option<PackageId>, PackageId), PkgStatus>,
     /// Whether using workspace mode
     pub workspace_mode: bool,
+    /// Names of direct dependencies missing from the registry index
+    pub unknown_deps: HashSet<String>,
+    /// Newest versions of the direct dependencies from alternative
+    /// registries, `None` for those missing from the index of theirs
+    pub alternative_latest: HashMap<String, Option<Version>>,
+    /// Path dependencies the temporary projects leave out as their path
+    /// can't be resolved, with the reason
+    pub skipped_paths: HashMap<String, String>,
+    /// Latest status of git and local fork packages compared against crates.io
+    published_latest: Option<HashMap<PackageId, Status>>,
+    /// Packages from crates.io whose version in use was yanked
+    yanked: Option<HashSet<PackageId>>,
+    /// Crates overridden by the `[patch]` and `[replace]` sections of the
+    /// root manifest, which are marked as such
+    patched: HashSet<String>,
+    /// Whether patched crates are regarded as up to date, with
+    /// `--patched-as-current`
+    patched_as_current: bool,
 }
 
 impl<'ela> ElaborateWorkspace<'ela> {
     /// Elaborate a `Workspace`
     pub fn from_workspace(
         workspace: &'ela Workspace,
-        options: &Options,
+        options: &OutdatedOptions,
     ) -> CargoResult<ElaborateWorkspace<'ela>> {
         let specs = Packages::All.into_package_id_specs(workspace)?;
         let (packages, resolve) = ops::resolve_ws_precisely(
@@ -40,6 +70,12 @@ impl<'ela> ElaborateWorkspace<'ela> {
             options.flag_no_default_features,
             &specs,
         )?;
+        // dependencies for other platforms are only followed if asked for
+        let target = match options.flag_target {
+            _ if options.flag_all_targets_platforms => None,
+            Some(ref triple) => Some(TargetInfo::target(workspace.config(), triple)?),
+            None => Some(TargetInfo::host(workspace.config())?),
+        };
         let mut pkgs = HashMap::new();
         let mut pkg_deps = HashMap::new();
         for pkg_id in packages.package_ids() {
@@ -48,11 +84,11 @@ impl<'ela> ElaborateWorkspace<'ela> {
             let deps = pkg.dependencies();
             let mut dep_map = HashMap::new();
             for dep_id in resolve.deps(pkg_id) {
-                for d in deps {
-                    if d.matches_id(dep_id) {
-                        dep_map.insert(dep_id.clone(), d.clone());
-                        break;
-                    }
+                let dep = deps.iter().find(|d| {
+                    d.matches_id(dep_id) && target.as_ref().map(|t| t.matches(d)).unwrap_or(true)
+                });
+                if let Some(d) = dep {
+                    dep_map.insert(dep_id.clone(), d.clone());
                 }
             }
             pkg_deps.insert(pkg_id.clone(), dep_map);
@@ -63,12 +99,33 @@ impl<'ela> ElaborateWorkspace<'ela> {
             pkgs: pkgs,
             pkg_deps: pkg_deps,
             pkg_status: HashMap::new(),
-            workspace_mode: options.flag_workspace || workspace.current().is_err(),
+            workspace_mode: options.flag_workspace || !options.flag_members.is_empty()
+                || workspace.current().is_err(),
+            unknown_deps: HashSet::new(),
+            alternative_latest: HashMap::new(),
+            skipped_paths: HashMap::new(),
+            published_latest: None,
+            yanked: None,
+            patched: patched_crates(&workspace.root().join("Cargo.toml"))?,
+            patched_as_current: options.flag_patched_as_current,
         })
     }
 
+    /// Package id specs like `foo:1.2.3` of the packages of the graph named
+    /// `names`, for `cargo update -p`, which refuses ambiguous names
+    pub fn package_specs(&self, names: &[String]) -> Vec<String> {
+        let mut specs: Vec<_> = self.pkgs
+            .keys()
+            .filter(|id| names.iter().any(|name| name == id.name()))
+            .map(|id| format!("{}:{}", id.name(), id.version()))
+            .collect();
+        specs.sort();
+        specs.dedup();
+        specs
+    }
+
     /// Determine root package based on current workspace and CLI options
-    pub fn determine_root(&self, options: &Options) -> CargoResult<PackageId> {
+    pub fn determine_root(&self, options: &OutdatedOptions) -> CargoResult<PackageId> {
         if let Some(ref root_name) = options.flag_root {
             if let Ok(workspace_root) = self.workspace.current() {
                 if root_name == workspace_root.name() {
@@ -93,6 +150,118 @@ impl<'ela> ElaborateWorkspace<'ela> {
         }
     }
 
+    /// Warn about direct dependencies of workspace members whose requirements
+    /// accept any version (`*`) or any version of a major series (`1`),
+    /// as no release of those crates can ever be incompatible
+    pub fn warn_wildcard_requirements(&self, config: &Config) -> CargoResult<()> {
+        for member in self.workspace.members() {
+            let mut deps: Vec<_> = self.pkg_deps[member.package_id()]
+                .values()
+                .filter(|dep| dep.source_id().is_registry())
+                .collect();
+            deps.sort_by(|a, b| a.name().cmp(b.name()));
+            for dep in deps {
+                let requirement = dep.version_req().to_string();
+                if is_wildcard_requirement(&requirement) {
+                    config.shell().warn(format!(
+                        "{} depends on {} with requirement `{}`, \
+                         newer versions are always considered compatible",
+                        member.name(),
+                        dep.name(),
+                        requirement
+                    ))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compare git sourced packages, as well as local forks, i.e. path
+    /// dependencies other than workspace members, against their releases
+    /// on crates.io, and the members themselves with `--include-members`,
+    /// those with `publish = false` only if `--unpublished-members compare`
+    fn resolve_published_latest(
+        &self,
+        options: &OutdatedOptions,
+    ) -> CargoResult<HashMap<PackageId, Status>> {
+        let mut published_latest = HashMap::new();
+        let members: HashSet<_> = self.workspace
+            .members()
+            .map(|m| m.package_id().clone())
+            .collect();
+        let include_members = options.flag_include_members;
+        // a crate of the same name on crates.io is most likely another one
+        // if the member is not meant to be published
+        let compare_unpublished = options.flag_unpublished_members == "compare";
+        let pkgs: Vec<_> = self.pkgs
+            .keys()
+            .filter(|id| {
+                if members.contains(id) {
+                    include_members && (compare_unpublished || self.pkgs[id].publish())
+                } else {
+                    id.source_id().is_git() || id.source_id().is_path()
+                }
+            })
+            .collect();
+        if pkgs.is_empty() {
+            return Ok(published_latest);
+        }
+        let mut index = RegistryIndex::crates_io_updated(self.workspace.config())?;
+        for pkg_id in pkgs {
+            let status = match index.versions(pkg_id.name())?.last() {
+                Some(published) if published > pkg_id.version() => {
+                    Status::Version(published.clone())
+                }
+                Some(_) => Status::Unchanged,
+                None if pkg_id.source_id().is_git() => Status::Unknown,
+                // most path dependencies are simply not meant to be published
+                None => continue,
+            };
+            published_latest.insert(pkg_id.clone(), status);
+        }
+        Ok(published_latest)
+    }
+
+    /// Find the packages from crates.io whose version in use was yanked
+    ///
+    /// cargo never resolves to a yanked version unless the lock file holds
+    /// it, so that the compat and latest versions are never yanked and the
+    /// versions in use are the only ones to check. The index is the copy
+    /// the temporary projects updated, which only crates missing from it
+    /// update again.
+    fn resolve_yanked(&self) -> CargoResult<HashSet<PackageId>> {
+        let mut yanked = HashSet::new();
+        let pkgs: Vec<_> = self.pkgs
+            .keys()
+            .filter(|id| id.source_id().is_default_registry())
+            .collect();
+        if pkgs.is_empty() {
+            return Ok(yanked);
+        }
+        let mut index = RegistryIndex::crates_io_updated(self.workspace.config())?;
+        for pkg_id in pkgs {
+            if index.yanked(pkg_id.name())?.contains(pkg_id.version()) {
+                yanked.insert(pkg_id.clone());
+            }
+        }
+        Ok(yanked)
+    }
+
+    /// Whether the version in use of a package was yanked
+    fn is_yanked(&self, pkg_id: &PackageId) -> bool {
+        self.yanked
+            .as_ref()
+            .map(|yanked| yanked.contains(pkg_id))
+            .unwrap_or(false)
+    }
+
+    /// Whether a package is the one an override of the root manifest puts
+    /// in place, rather than a copy of the crate from the registry which
+    /// nothing overrides
+    fn is_patched(&self, pkg_id: &PackageId) -> bool {
+        self.patched.contains(pkg_id.name()) && !pkg_id.source_id().is_registry()
+    }
+
     /// Find a member based on member name
     fn find_member(&self, member: &PackageId) -> CargoResult<PackageId> {
         for m in self.workspace.members() {
@@ -112,11 +281,18 @@ impl<'ela> ElaborateWorkspace<'ela> {
         &mut self,
         compat: &ElaborateWorkspace,
         latest: &ElaborateWorkspace,
-        options: &Options,
+        options: &OutdatedOptions,
         config: &Config,
         root: &PackageId,
     ) -> CargoResult<()> {
         self.pkg_status.clear();
+        if self.published_latest.is_none() {
+            self.published_latest =
+                Some(self.resolve_published_latest(options)?);
+        }
+        if self.yanked.is_none() {
+            self.yanked = Some(self.resolve_yanked()?);
+        }
         let root_parent = if self.workspace_mode || root == self.workspace.current()?.package_id() {
             None
         } else {
@@ -162,20 +338,71 @@ impl<'ela> ElaborateWorkspace<'ela> {
             return Ok(());
         }
         let self_pkg = self.pkgs.get(self_pkg).cloned().unwrap();
-        let pkg_status = PkgStatus {
-            compat: Status::from_versions(
-                self_pkg.version(),
-                compat_pkg
-                    .and_then(|id| compat.pkgs.get(id))
-                    .map(|p| p.version()),
-            ),
-            latest: Status::from_versions(
-                self_pkg.version(),
-                latest_pkg
-                    .and_then(|id| latest.pkgs.get(id))
-                    .map(|p| p.version()),
-            ),
+        let published_latest = self.published_latest
+            .as_ref()
+            .and_then(|published_latest| published_latest.get(self_pkg.package_id()));
+        let pkg_status = if self.is_patched(self_pkg.package_id()) && self.patched_as_current {
+            PkgStatus {
+                compat: Status::Unchanged,
+                latest: Status::Unchanged,
+            }
+        } else if let Some(published_latest) = published_latest {
+            let source_id = self_pkg.package_id().source_id();
+            PkgStatus {
+                compat: if source_id.is_git() {
+                    let rev = source_id.precise().unwrap_or("");
+                    Status::Git(rev.chars().take(8).collect())
+                } else {
+                    Status::Unchanged
+                },
+                latest: published_latest.clone(),
+            }
+        } else {
+            PkgStatus {
+                compat: Status::from_versions(
+                    self_pkg.version(),
+                    compat_pkg
+                        .and_then(|id| compat.pkgs.get(id))
+                        .map(|p| p.version()),
+                ),
+                latest: if latest.unknown_deps.contains(self_pkg.name()) {
+                    Status::Unknown
+                } else if let Some(newest) = latest.alternative_latest.get(self_pkg.name()) {
+                    match *newest {
+                        Some(ref newest) if newest > self_pkg.version() => {
+                            Status::Version(newest.clone())
+                        }
+                        Some(_) => Status::Unchanged,
+                        None => Status::Unknown,
+                    }
+                } else {
+                    Status::from_versions(
+                        self_pkg.version(),
+                        latest_pkg
+                            .and_then(|id| latest.pkgs.get(id))
+                            .map(|p| p.version()),
+                    )
+                },
+            }
         };
+        // traced once per package, however many paths lead to it
+        if config.shell().verbosity() == Verbosity::Verbose
+            && !self.pkg_status
+                .keys()
+                .any(|&(_, _, ref id)| id == self_pkg.package_id())
+        {
+            verbose!(
+                config,
+                "Resolved",
+                format!(
+                    "{} {}, compat: {}, latest: {}",
+                    self_pkg.name(),
+                    self_pkg.version(),
+                    pkg_status.compat.to_string(),
+                    pkg_status.latest.to_string()
+                )
+            );
+        }
         debug!(
             config,
             "UPDATE, self: {:?}, key: {:?}, status: {:?}\n",
@@ -249,13 +476,120 @@ impl<'ela> ElaborateWorkspace<'ela> {
         Ok(())
     }
 
-    /// Print package status to `TabWriter`
-    pub fn print_list(
+    /// Crates that upgrading each outdated direct dependency of `root` to its
+    /// latest version would add to or remove from the dependency graph
+    ///
+    /// The latest graph has every dependency upgraded at once, so a crate
+    /// only counts as removed if nothing else pulls it in after the upgrade
+    pub fn graph_changes(
         &self,
-        options: &Options,
+        latest: &ElaborateWorkspace,
+        options: &OutdatedOptions,
         root: &PackageId,
-        preceding_line: bool,
-    ) -> CargoResult<i32> {
+    ) -> CargoResult<Vec<GraphChange>> {
+        let latest_root = if self.workspace_mode {
+            latest.find_member(root)?
+        } else {
+            latest.determine_root(options)?
+        };
+        let curr_names = self.graph_names();
+        let latest_names = latest.graph_names();
+        let mut deps: Vec<_> = self.pkg_deps[root].keys().collect();
+        deps.sort();
+        let mut changes = vec![];
+        for dep in deps {
+            let name = dep.name();
+            let latest_dep = latest.pkg_deps[&latest_root]
+                .keys()
+                .find(|id| id.name() == name);
+            let latest_dep = match latest_dep {
+                Some(latest_dep) if latest_dep.version() != dep.version() => latest_dep,
+                _ => continue,
+            };
+            let added: Vec<_> = latest
+                .reachable_names(latest_dep)
+                .into_iter()
+                .filter(|n| !curr_names.contains(n))
+                .collect();
+            let removed: Vec<_> = self.reachable_names(dep)
+                .into_iter()
+                .filter(|n| !latest_names.contains(n))
+                .collect();
+            if added.is_empty() && removed.is_empty() {
+                continue;
+            }
+            changes.push(GraphChange {
+                name: name.to_owned(),
+                version: latest_dep.version().to_string(),
+                added: added,
+                removed: removed,
+            });
+        }
+        Ok(changes)
+    }
+
+    /// Names of the packages reachable from any workspace member
+    fn graph_names(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        for member in self.workspace.members() {
+            names.extend(self.reachable_names(member.package_id()));
+        }
+        names
+    }
+
+    /// Names of the packages reachable from `pkg_id`, excluding itself
+    fn reachable_names(&self, pkg_id: &PackageId) -> BTreeSet<String> {
+        self.reachable_ids(pkg_id)
+            .into_iter()
+            .map(|id| id.name().to_owned())
+            .collect()
+    }
+
+    /// Packages reachable from `pkg_id`, excluding itself
+    fn reachable_ids<'a>(&'a self, pkg_id: &'a PackageId) -> HashSet<&'a PackageId> {
+        let mut visited = HashSet::new();
+        let mut pending = vec![pkg_id];
+        while let Some(id) = pending.pop() {
+            if let Some(deps) = self.pkg_deps.get(id) {
+                for dep in deps.keys() {
+                    if visited.insert(dep) {
+                        pending.push(dep);
+                    }
+                }
+            }
+        }
+        visited.remove(pkg_id);
+        visited
+    }
+
+    /// Crates present in several versions in the dependency graph, with
+    /// the workspace members pulling in each version
+    fn duplicates(&self) -> HashMap<String, BTreeMap<Version, Vec<String>>> {
+        let mut versions: HashMap<String, BTreeMap<Version, Vec<String>>> = HashMap::new();
+        for member in self.workspace.members() {
+            for id in self.reachable_ids(member.package_id()) {
+                versions
+                    .entry(id.name().to_owned())
+                    .or_insert_with(BTreeMap::new)
+                    .entry(id.version().clone())
+                    .or_insert_with(Vec::new)
+                    .push(member.name().to_owned());
+            }
+        }
+        versions.retain(|_, versions| versions.len() > 1);
+        for members in versions.values_mut().flat_map(|versions| versions.values_mut()) {
+            members.sort();
+            members.dedup();
+        }
+        versions
+    }
+
+    /// Collect the rows of outdated dependencies of `root`
+    pub fn list_lines(
+        &self,
+        options: &OutdatedOptions,
+        root: &PackageId,
+    ) -> CargoResult<Vec<Line>> {
         let mut lines = vec![];
         let root_parent = if self.workspace_mode || root == self.workspace.current()?.package_id() {
             None
@@ -263,105 +597,444 @@ impl<'ela> ElaborateWorkspace<'ela> {
             Some(self.workspace.current()?.package_id())
         };
         {
-            let mut printed = HashSet::new();
-            self.print_list_recursive(
+            let shipped = self.shipped_packages();
+            let duplicates = self.duplicates();
+            let mut visited = HashSet::new();
+            self.list_lines_recursive(
                 options,
                 None,
                 root_parent,
                 root,
                 options.flag_depth,
+                None,
+                &shipped,
+                &duplicates,
+                &mut vec![],
                 &mut lines,
-                &mut printed,
+                &mut visited,
             )?;
         }
-        lines.sort();
-        lines.dedup();
-        let lines_len = lines.len();
+        // the rows of the root dependencies are those its manifest declares
+        if options.flag_depth == 1 {
+            let declared = declared_dependencies(self.pkgs[root].manifest_path())?;
+            lines.retain(|line| declared.contains(line.crate_name()));
+        }
+        Ok(order_lines(lines, options))
+    }
 
-        if lines.is_empty() {
-            if !self.workspace_mode {
-                println!("All dependencies are up to date, yay!");
+    /// Packages reachable from the workspace members without going through
+    /// a dev-dependency, i.e. those which end up in what the members ship
+    fn shipped_packages(&self) -> HashSet<PackageId> {
+        let mut shipped = HashSet::new();
+        let mut queue: Vec<_> = self.workspace
+            .members()
+            .map(|m| m.package_id().clone())
+            .collect();
+        while let Some(pkg_id) = queue.pop() {
+            if !shipped.insert(pkg_id.clone()) {
+                continue;
             }
-        } else {
-            if preceding_line {
-                println!();
+            for (dep_id, dependency) in &self.pkg_deps[&pkg_id] {
+                if dependency.kind() != Kind::Development && !shipped.contains(dep_id) {
+                    queue.push(dep_id.clone());
+                }
             }
-            if self.workspace_mode {
-                println!("{}\n================", root.name());
+        }
+        shipped
+    }
+
+    /// Number of packages of the resolved graph, members aside, which are
+    /// up to date wherever they appear in it
+    pub fn up_to_date_count(&self, options: &OutdatedOptions) -> usize {
+        let mut up_to_date = HashMap::new();
+        for (&(_, _, ref pkg_id), pkg_status) in &self.pkg_status {
+            if self.workspace.members().any(|m| m.package_id() == pkg_id)
+                || options.flag_ignore.contains(&pkg_id.name().to_string())
+            {
+                continue;
             }
-            let mut tw = TabWriter::new(vec![]);
-            write!(&mut tw, "Name\tProject\tCompat\tLatest\tKind\tPlatform\n")?;
-            write!(&mut tw, "----\t-------\t------\t------\t----\t--------\n")?;
-            for line in lines {
-                write!(&mut tw, "{}", line)?;
+            let unchanged = !pkg_status.compat.is_changed() && !pkg_status.latest.is_changed()
+                && !self.is_yanked(pkg_id);
+            *up_to_date.entry(pkg_id).or_insert(true) &= unchanged;
+        }
+        up_to_date.values().filter(|&&unchanged| unchanged).count()
+    }
+
+    /// Feed the resolved status of dependencies into `stats`
+    pub fn collect_stats(&self, stats: &mut Stats) {
+        for (&(_, ref parent, ref pkg_id), pkg_status) in &self.pkg_status {
+            if let Some(ref parent) = *parent {
+                let kind = format!("{:?}", self.pkg_deps[parent][pkg_id].kind());
+                let latest = match pkg_status.latest {
+                    Status::Version(ref v) => Some(v),
+                    _ => None,
+                };
+                stats.add(kind, pkg_id, latest);
+            }
+        }
+    }
+
+    /// Print everything known about the packages named `name`:
+    /// their compatible and latest versions, the packages requiring them
+    /// and the requirements standing in the way of the latest version
+    pub fn explain(
+        &self,
+        name: &str,
+        compat: &ElaborateWorkspace,
+        latest: &ElaborateWorkspace,
+    ) -> CargoResult<()> {
+        let mut pkg_ids: Vec<_> = self.pkgs.keys().filter(|id| id.name() == name).collect();
+        if pkg_ids.is_empty() {
+            return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+                "Package {} is not in the dependency graph",
+                name
+            ))));
+        }
+        pkg_ids.sort();
+        let newest = latest
+            .pkgs
+            .keys()
+            .filter(|id| id.name() == name)
+            .map(|id| id.version())
+            .max();
+
+        let mut tw = TabWriter::new(vec![]);
+        for (i, pkg_id) in pkg_ids.into_iter().enumerate() {
+            if i > 0 {
+                write!(&mut tw, "\n")?;
             }
-            tw.flush()?;
             write!(
-                io::stdout(),
-                "{}",
-                String::from_utf8(tw.into_inner().unwrap()).unwrap()
+                &mut tw,
+                "{} v{} ({})\n",
+                name,
+                pkg_id.version(),
+                pkg_id.source_id().url()
             )?;
-            io::stdout().flush()?;
+            let mut dependents: Vec<_> = self.pkg_deps
+                .iter()
+                .filter_map(|(parent, deps)| deps.get(pkg_id).map(|dep| (parent, dep)))
+                .collect();
+            dependents.sort_by(|a, b| a.0.cmp(b.0));
+
+            let compat_versions: Vec<_> = dependents
+                .iter()
+                .filter_map(|&(parent, _)| compat.dependency_version(parent.name(), name))
+                .collect();
+            let status = PkgStatus {
+                compat: Status::from_versions(
+                    pkg_id.version(),
+                    compat_versions.iter().max().cloned(),
+                ),
+                latest: Status::from_versions(pkg_id.version(), newest),
+            };
+            write!(&mut tw, "Compat:\t{}\n", status.compat.to_string())?;
+            write!(&mut tw, "Latest:\t{}\n", status.latest.to_string())?;
+
+            if !dependents.is_empty() {
+                write!(&mut tw, "Required by:\n")?;
+                for &(parent, dep) in &dependents {
+                    write!(
+                        &mut tw,
+                        "    {} v{}\t{}\t{:?}\n",
+                        parent.name(),
+                        parent.version(),
+                        dep.version_req(),
+                        dep.kind()
+                    )?;
+                }
+            }
+            if let Some(newest) = newest {
+                let blockers: Vec<_> = dependents
+                    .iter()
+                    .filter(|&&(_, dep)| !dep.version_req().matches(newest))
+                    .collect();
+                if !blockers.is_empty() && newest != pkg_id.version() {
+                    write!(&mut tw, "Blocking {}:\n", newest)?;
+                    for &&(parent, dep) in &blockers {
+                        write!(
+                            &mut tw,
+                            "    {} v{}\trequires {}\n",
+                            parent.name(),
+                            parent.version(),
+                            dep.version_req()
+                        )?;
+                    }
+                }
+            }
         }
+        tw.flush()?;
+        write!(
+            io::stdout(),
+            "{}",
+            String::from_utf8(tw.into_inner().unwrap()).unwrap()
+        )?;
+        io::stdout().flush()?;
+        Ok(())
+    }
+
+    /// Version of the dependency `dep_name` of the first package named `parent_name`
+    fn dependency_version(&self, parent_name: &str, dep_name: &str) -> Option<&Version> {
+        self.pkgs
+            .keys()
+            .filter(|id| id.name() == parent_name)
+            .filter_map(|id| self.pkg_deps.get(id))
+            .flat_map(|deps| deps.keys())
+            .find(|dep_id| dep_id.name() == dep_name)
+            .map(|dep_id| dep_id.version())
+    }
 
-        Ok(lines_len as i32)
+    /// Print the dependency tree of `root`, annotating outdated packages
+    pub fn print_tree(&self, options: &OutdatedOptions, root: &PackageId) -> CargoResult<()> {
+        let root_parent = if self.workspace_mode || root == self.workspace.current()?.package_id() {
+            None
+        } else {
+            Some(self.workspace.current()?.package_id())
+        };
+        let mut lines = vec![];
+        self.print_tree_recursive(
+            None,
+            root_parent,
+            root,
+            options.flag_depth,
+            &mut vec![],
+            &mut HashSet::new(),
+            &mut lines,
+        );
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        for line in lines {
+            writeln!(stdout, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Print the dependency graph of `roots` in the DOT format of Graphviz,
+    /// with the outdated packages filled and labeled with their latest
+    /// version, and the members as boxes
+    pub fn print_dot(&self, roots: &[PackageId], depth: i32) -> CargoResult<()> {
+        let mut nodes = BTreeSet::new();
+        let mut edges = BTreeSet::new();
+        let mut queue: Vec<_> = roots.iter().map(|root| (root.clone(), depth)).collect();
+        while let Some((pkg_id, depth)) = queue.pop() {
+            if !nodes.insert(pkg_id.clone()) || depth == 0 {
+                continue;
+            }
+            for dep in self.pkg_deps[&pkg_id].keys() {
+                edges.insert((pkg_id.clone(), dep.clone()));
+                queue.push((dep.clone(), depth - 1));
+            }
+        }
+        // a package reached on several paths is outdated if it is on any
+        let mut outdated = HashMap::new();
+        for (&(_, _, ref pkg_id), pkg_status) in &self.pkg_status {
+            if pkg_status.compat.is_changed() || pkg_status.latest.is_changed() {
+                outdated.insert(pkg_id, pkg_status);
+            }
+        }
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        let node = |pkg_id: &PackageId| format!("\"{} {}\"", pkg_id.name(), pkg_id.version());
+        writeln!(stdout, "digraph dependencies {{")?;
+        writeln!(stdout, "    node [shape=ellipse];")?;
+        for pkg_id in &nodes {
+            let mut attributes = vec![];
+            if self.workspace.members().any(|m| m.package_id() == pkg_id) {
+                attributes.push("shape=box".to_owned());
+            }
+            if let Some(pkg_status) = outdated.get(pkg_id) {
+                let latest = if pkg_status.latest.is_changed() {
+                    &pkg_status.latest
+                } else {
+                    &pkg_status.compat
+                };
+                attributes.push(format!(
+                    "label=\"{} {} → {}\"",
+                    pkg_id.name(),
+                    pkg_id.version(),
+                    latest.to_string()
+                ));
+                attributes.push("style=filled, fillcolor=salmon".to_owned());
+            }
+            if attributes.is_empty() {
+                writeln!(stdout, "    {};", node(pkg_id))?;
+            } else {
+                writeln!(stdout, "    {} [{}];", node(pkg_id), attributes.join(", "))?;
+            }
+        }
+        for &(ref from, ref to) in &edges {
+            writeln!(stdout, "    {} -> {};", node(from), node(to))?;
+        }
+        writeln!(stdout, "}}")?;
+        Ok(())
     }
 
     #[allow(unknown_lints)]
     #[allow(too_many_arguments)]
-    fn print_list_recursive(
+    fn print_tree_recursive(
         &self,
-        options: &Options,
         grand: Option<&PackageId>,
         parent: Option<&PackageId>,
         pkg_id: &PackageId,
         depth: i32,
+        last_flags: &mut Vec<bool>,
+        expanded: &mut HashSet<PackageId>,
         lines: &mut Vec<String>,
-        printed: &mut HashSet<(Option<PackageId>, Option<PackageId>, PackageId)>,
+    ) {
+        let mut line = String::new();
+        if let Some((&is_last, ancestors)) = last_flags.split_last() {
+            for &ancestor_is_last in ancestors {
+                line.push_str(if ancestor_is_last { "    " } else { "│   " });
+            }
+            line.push_str(if is_last { "└── " } else { "├── " });
+        }
+        let pkg = &self.pkgs[pkg_id];
+        line.push_str(&format!("{} v{}", pkg.name(), pkg.version()));
+        let pkg_status_key = (grand.cloned(), parent.cloned(), pkg_id.clone());
+        if let Some(pkg_status) = self.pkg_status.get(&pkg_status_key) {
+            if pkg_status.compat.is_changed() || pkg_status.latest.is_changed() {
+                line.push_str(&format!(
+                    " (compat: {}, latest: {})",
+                    pkg_status.compat.to_string(),
+                    pkg_status.latest.to_string()
+                ));
+            }
+        }
+
+        let mut deps: Vec<_> = self.pkg_deps[pkg_id]
+            .keys()
+            .filter(|dep| {
+                // see list_lines_recursive
+                !(self.workspace_mode && self.workspace.members().any(|m| m.package_id() == *dep))
+            })
+            .collect();
+        // like cargo tree, a subtree is only expanded at its first occurrence
+        let first_occurrence = expanded.insert(pkg_id.clone());
+        if !first_occurrence && !deps.is_empty() {
+            line.push_str(" (*)");
+        }
+        lines.push(line);
+        if !first_occurrence || depth == 0 {
+            return;
+        }
+
+        deps.sort();
+        let deps_len = deps.len();
+        for (i, dep) in deps.into_iter().enumerate() {
+            last_flags.push(i + 1 == deps_len);
+            self.print_tree_recursive(
+                parent,
+                Some(pkg_id),
+                dep,
+                depth - 1,
+                last_flags,
+                expanded,
+                lines,
+            );
+            last_flags.pop();
+        }
+    }
+
+    #[allow(unknown_lints)]
+    #[allow(too_many_arguments)]
+    fn list_lines_recursive(
+        &self,
+        options: &OutdatedOptions,
+        grand: Option<&PackageId>,
+        parent: Option<&PackageId>,
+        pkg_id: &PackageId,
+        depth: i32,
+        parent_status: Option<&PkgStatus>,
+        shipped: &HashSet<PackageId>,
+        duplicates: &HashMap<String, BTreeMap<Version, Vec<String>>>,
+        chain: &mut Vec<String>,
+        lines: &mut Vec<Line>,
+        visited: &mut HashSet<(Option<PackageId>, Option<PackageId>, PackageId)>,
     ) -> CargoResult<()> {
         let pkg_status_key = (grand.cloned(), parent.cloned(), pkg_id.clone());
-        if printed.contains(&pkg_status_key) {
+        if visited.contains(&pkg_status_key) {
             return Ok(());
         }
-        printed.insert(pkg_status_key.clone());
+        visited.insert(pkg_status_key.clone());
 
         let pkg = &self.pkgs[pkg_id];
         let pkg_status = &self.pkg_status[&pkg_status_key];
+        let is_yanked = self.is_yanked(pkg_id);
+        // members depended upon by other members are only listed if asked for
+        let is_member_dep =
+            parent.is_some() && self.workspace.members().any(|m| m.package_id() == pkg_id);
+        let is_listed = if is_member_dep && options.flag_include_members {
+            // members without a release to compare with are unpublished
+            let is_published = self.published_latest
+                .as_ref()
+                .map(|published_latest| published_latest.contains_key(pkg_id))
+                .unwrap_or(false);
+            is_published || options.flag_unpublished_members != "hide"
+        } else if is_member_dep && options.flag_exclude_members_as_deps {
+            false
+        } else if options.flag_check_lockfile {
+            pkg_status.compat.is_changed() || is_yanked
+        } else {
+            // yanked versions in use are listed even if nothing is newer
+            pkg_status.compat.is_changed() || pkg_status.latest.is_changed() || is_yanked
+        };
+        // with --duplicates-only, every version of a duplicate is listed, up
+        // to date or not, so that the rows show what to consolidate
+        let versions = duplicates.get(pkg.name());
+        let is_listed = if options.flag_duplicates_only {
+            versions.is_some() && (is_listed || !is_member_dep)
+        } else {
+            is_listed
+        };
 
-        if (pkg_status.compat.is_changed() || pkg_status.latest.is_changed())
+        if is_listed
             && (options.flag_packages.is_empty()
                 || options.flag_packages.contains(&pkg.name().to_string()))
+            && !options.flag_ignore.contains(&pkg.name().to_string())
         {
-            // name version compatible latest kind platform
-            if let Some(parent) = parent {
-                let dependency = &self.pkg_deps[parent][pkg_id];
-                let label =
-                    if self.workspace_mode || parent == self.workspace.current()?.package_id() {
-                        pkg.name().to_owned()
-                    } else {
-                        format!("{}->{}", self.pkgs[parent].name(), pkg.name())
-                    };
-                let line = format!(
-                    "{}\t{}\t{}\t{}\t{:?}\t{}\n",
-                    label,
-                    pkg.version(),
-                    pkg_status.compat.to_string(),
-                    pkg_status.latest.to_string(),
-                    dependency.kind(),
-                    dependency
-                        .platform()
-                        .map(|p| p.to_string())
-                        .unwrap_or_else(|| "---".to_owned())
-                );
-                lines.push(line);
+            let zero_major_as_breaking = options.flag_zero_major_as_breaking;
+            // with --check-lockfile, of the update cargo update would make
+            let severity = if options.flag_check_lockfile {
+                pkg_status.compat.severity(pkg.version(), zero_major_as_breaking)
             } else {
-                let line = format!(
-                    "{}\t{}\t{}\t{}\t---\t---\n",
-                    pkg.name(),
-                    pkg.version(),
+                pkg_status.severity(pkg.version(), zero_major_as_breaking)
+            };
+            let severity = severity.map(|s| s.to_string());
+            let mut line = Line {
+                severity: severity,
+                successor: successors::successor(pkg.name()).map(String::from),
+                dev_only: !shipped.contains(pkg_id),
+                patched: self.is_patched(pkg_id),
+                yanked: is_yanked,
+                duplicates: versions.map(|versions| versions.len()),
+                pulled_in_by: versions.and_then(|versions| versions.get(pkg.version()).cloned()),
+                ..Line::new(
+                    pkg.name().to_owned(),
+                    pkg.version().to_string(),
                     pkg_status.compat.to_string(),
-                    pkg_status.latest.to_string()
-                );
+                    pkg_status.latest.to_string(),
+                )
+            };
+            if options.flag_why {
+                let mut path = chain.clone();
+                path.push(pkg.name().to_owned());
+                line.path = Some(path);
+            }
+            if pkg_status.compat.is_removed() || pkg_status.latest.is_removed() {
+                line.removed_reason = Some(match self.skipped_paths.get(pkg.name()) {
+                    Some(reason) => format!("its path can't be resolved: {}", reason),
+                    None => self.removed_reason(parent, parent_status, pkg_status),
+                });
+            }
+            if let Some(parent) = parent {
+                let dependency = &self.pkg_deps[parent][pkg_id];
+                if !self.workspace_mode && parent != self.workspace.current()?.package_id() {
+                    line.name = format!("{}->{}", self.pkgs[parent].name(), pkg.name());
+                }
+                line.kind = Some(format!("{:?}", dependency.kind()));
+                line.platform = dependency.platform().map(|p| p.to_string());
+            }
+            if line.has_severity(&options.flag_only) {
                 lines.push(line);
             }
         }
@@ -370,23 +1043,154 @@ impl<'ela> ElaborateWorkspace<'ela> {
             return Ok(());
         }
 
-        for dep in self.pkg_deps[pkg_id].keys() {
+        // in a fixed order, so that rows come out the same without sorting
+        let mut deps: Vec<_> = self.pkg_deps[pkg_id].keys().collect();
+        deps.sort();
+        chain.push(pkg.name().to_owned());
+        for dep in deps {
+            // with --kind, the direct dependencies of the root decide which
+            // subtrees are walked
+            if parent_status.is_none() && !options.flag_kind.is_empty() {
+                let kind = kind_name(self.pkg_deps[pkg_id][dep].kind());
+                if !options.flag_kind.iter().any(|k| k == kind) {
+                    continue;
+                }
+            }
             // if executed against a virtual manifest, we should stop if a dependency
             // is another member to prevent duplicated output
-            if self.workspace_mode && self.workspace.members().any(|m| m.package_id() == dep) {
-                continue;
-            }
-            self.print_list_recursive(
+            let depth = if self.workspace_mode
+                && self.workspace.members().any(|m| m.package_id() == dep)
+            {
+                if !options.flag_include_members {
+                    continue;
+                }
+                // the row of the member only, its dependencies are listed
+                // under its own report
+                1
+            } else {
+                depth
+            };
+            self.list_lines_recursive(
                 options,
                 parent,
                 Some(pkg_id),
                 dep,
                 depth - 1,
+                Some(pkg_status),
+                shipped,
+                duplicates,
+                chain,
                 lines,
-                printed,
+                visited,
             )?;
         }
+        chain.pop();
 
         Ok(())
     }
+
+    /// Why a package is missing from the compat or latest resolution,
+    /// which happens when the corresponding version of its parent
+    /// does not depend on it anymore
+    fn removed_reason(
+        &self,
+        parent: Option<&PackageId>,
+        parent_status: Option<&PkgStatus>,
+        pkg_status: &PkgStatus,
+    ) -> String {
+        let (parent, parent_status) = match (parent, parent_status) {
+            (Some(parent), Some(parent_status)) => (&self.pkgs[parent], parent_status),
+            _ => return "it is no longer required".to_owned(),
+        };
+        let parent_status = if pkg_status.latest.is_removed() {
+            &parent_status.latest
+        } else {
+            &parent_status.compat
+        };
+        match *parent_status {
+            Status::Removed => format!("{} is removed as well", parent.name()),
+            Status::Version(ref v) => format!("{} {} no longer depends on it", parent.name(), v),
+            _ => format!(
+                "{} {} no longer depends on it",
+                parent.name(),
+                parent.version()
+            ),
+        }
+    }
+}
+
+/// The name of a dependency kind as given to `--kind`
+fn kind_name(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Normal => "normal",
+        Kind::Development => "dev",
+        Kind::Build => "build",
+    }
+}
+
+/// Names of the crates overridden in the `[patch]` tables and the
+/// `[replace]` table of a manifest
+fn patched_crates(manifest_path: &Path) -> CargoResult<HashSet<String>> {
+    let mut contents = String::new();
+    File::open(manifest_path)?.read_to_string(&mut contents)?;
+    let manifest: Value = ::toml::from_str(&contents)?;
+    let mut patched = HashSet::new();
+    let registries = manifest.get("patch").and_then(|p| p.as_table());
+    for crates in registries.into_iter().flat_map(|r| r.values()) {
+        for (name, patch) in crates.as_table().into_iter().flat_map(|c| c.iter()) {
+            // `package` renames the patched crate
+            let name = patch
+                .get("package")
+                .and_then(|p| p.as_str())
+                .unwrap_or(name.as_str());
+            patched.insert(name.to_owned());
+        }
+    }
+    // keyed by package id specs like `foo:1.0.0`
+    let replacements = manifest.get("replace").and_then(|r| r.as_table());
+    for spec in replacements.into_iter().flat_map(|r| r.keys()) {
+        let name = spec.split(':').next().unwrap_or(spec);
+        patched.insert(name.to_owned());
+    }
+    Ok(patched)
+}
+
+/// Names of the crates a manifest declares in its dependency tables, those
+/// of `[target]` tables included
+fn declared_dependencies(manifest_path: &Path) -> CargoResult<HashSet<String>> {
+    let mut contents = String::new();
+    File::open(manifest_path)?.read_to_string(&mut contents)?;
+    let mut manifest: Table = ::toml::from_str(&contents)?;
+    let mut declared = HashSet::new();
+    for dependencies in dependency_tables(&mut manifest, true) {
+        for (name, spec) in dependencies.iter() {
+            // `package` renames the dependency
+            let name = spec.get("package")
+                .and_then(|p| p.as_str())
+                .unwrap_or(name.as_str());
+            declared.insert(name.to_owned());
+        }
+    }
+    Ok(declared)
+}
+
+/// Whether a requirement floats over all versions or a whole major series
+fn is_wildcard_requirement(requirement: &str) -> bool {
+    let requirement = requirement.trim_left_matches('^');
+    let major = requirement.trim_right_matches(".*");
+    requirement == "*" || !major.contains('.') && major.parse::<u64>().is_ok()
+}
+
+/// Sort rows by name, then by the version in use, unless `--no-sort` asks
+/// for the order they were found in, walking the graph depth first with
+/// the dependencies of each package in the order of their ids
+fn order_lines(mut lines: Vec<Line>, options: &OutdatedOptions) -> Vec<Line> {
+    if options.flag_no_sort {
+        let mut seen = BTreeSet::new();
+        lines.retain(|line| seen.insert(line.clone()));
+    } else {
+        lines.sort();
+        lines.dedup();
+    }
+    lines
 }
@@ -0,0 +1,137 @@
+use std::process::Command;
+
+use cargo::util::{CargoResult, Config};
+
+use super::report::{Line, Report};
+
+/// Placeholders of `--exec` along with the environment variables carrying
+/// their values
+const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("name", "CARGO_OUTDATED_NAME"),
+    ("current", "CARGO_OUTDATED_CURRENT"),
+    ("compat", "CARGO_OUTDATED_COMPAT"),
+    ("latest", "CARGO_OUTDATED_LATEST"),
+    ("kind", "CARGO_OUTDATED_KIND"),
+    ("severity", "CARGO_OUTDATED_SEVERITY"),
+    ("root", "CARGO_OUTDATED_ROOT"),
+];
+
+/// Run `command` through the shell once per outdated dependency, with the
+/// placeholders `{name}`, `{current}`, `{compat}`, `{latest}`, `{kind}`,
+/// `{severity}` and `{root}` substituted
+///
+/// The values are passed in environment variables, which the placeholders
+/// are replaced by references to, so that the shell never parses them:
+/// a git dependency's `abc1234 (branch)` or a branch name like `$(rm -rf)`
+/// stay plain text. A failing command is warned about, the remaining ones
+/// still run.
+pub fn exec_per_dependency(config: &Config, command: &str, reports: &[Report]) -> CargoResult<()> {
+    let script = substitute(command);
+    for report in reports {
+        for line in &report.dependencies {
+            let mut shell = shell_command(&script);
+            for &(placeholder, var) in PLACEHOLDERS {
+                shell.env(var, value(placeholder, &report.crate_name, line));
+            }
+            match shell.status() {
+                Ok(ref status) if status.success() => {}
+                Ok(status) => config.shell().warn(format!(
+                    "`{}` failed for {} with {}",
+                    command, line.name, status
+                ))?,
+                Err(e) => config.shell().warn(format!(
+                    "`{}` could not be run for {}: {}",
+                    command, line.name, e
+                ))?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The value of a placeholder for a row
+fn value(placeholder: &str, root: &str, line: &Line) -> String {
+    let or_dashes = |value: &Option<String>| value.clone().unwrap_or_else(|| "---".to_owned());
+    match placeholder {
+        "name" => line.crate_name().to_owned(),
+        "current" => line.project.clone(),
+        "compat" => line.compat.clone(),
+        "latest" => line.latest.clone(),
+        "kind" => or_dashes(&line.kind),
+        "severity" => or_dashes(&line.severity),
+        _ => root.to_owned(),
+    }
+}
+
+/// Replace the placeholders by references to their variables in a single
+/// pass, leaving unknown `{...}` alone
+fn substitute(command: &str) -> String {
+    let mut script = String::with_capacity(command.len());
+    let mut rest = command;
+    while let Some(start) = rest.find('{') {
+        script.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let known = rest.find('}').and_then(|end| {
+            PLACEHOLDERS
+                .iter()
+                .find(|&&(placeholder, _)| placeholder == &rest[1..end])
+                .map(|&(_, var)| (end, var))
+        });
+        match known {
+            Some((end, var)) => {
+                script.push_str(&variable(var));
+                rest = &rest[end + 1..];
+            }
+            None => {
+                script.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    script.push_str(rest);
+    script
+}
+
+/// A reference to an environment variable, expanded by `cmd` only after
+/// the command is parsed thanks to delayed expansion
+#[cfg(windows)]
+fn variable(var: &str) -> String { format!("!{}!", var) }
+
+#[cfg(windows)]
+fn shell_command(script: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/V:ON").arg("/C").arg(script);
+    shell
+}
+
+/// A reference to an environment variable, which the shell expands without
+/// parsing the value as code
+#[cfg(not(windows))]
+fn variable(var: &str) -> String { format!("${{{}}}", var) }
+
+#[cfg(not(windows))]
+fn shell_command(script: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(script);
+    shell
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::substitute;
+
+    #[test]
+    fn placeholders_become_variables() {
+        assert_eq!(
+            substitute("echo \"{root}: {name} {current} -> {latest}\""),
+            "echo \"${CARGO_OUTDATED_ROOT}: ${CARGO_OUTDATED_NAME} \
+             ${CARGO_OUTDATED_CURRENT} -> ${CARGO_OUTDATED_LATEST}\""
+        );
+    }
+
+    #[test]
+    fn unknown_braces_are_kept() {
+        assert_eq!(substitute("f() { echo {x}; }; f {kind"), "f() { echo {x}; }; f {kind");
+        assert_eq!(substitute("{{name}}"), "{${CARGO_OUTDATED_NAME}}");
+    }
+}
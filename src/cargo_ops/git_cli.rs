@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use cargo::core::Workspace;
+use cargo::sources::git::canonicalize_url;
+use cargo::util::{short_hash, CargoError, CargoErrorKind, CargoResult, Config, ToUrl};
+use toml::value::{Table, Value};
+
+use super::lockfile;
+
+/// Whether git is run as the system binary, as `net.git-fetch-with-cli`
+/// says, e.g. for environments which only allow it through the credential
+/// helpers and proxies of the system git
+pub fn fetches_with_cli(config: &Config) -> CargoResult<bool> {
+    Ok(config
+        .get_bool("net.git-fetch-with-cli")?
+        .map(|v| v.val)
+        .unwrap_or(false))
+}
+
+/// The references a remote advertises, as `(name, commit)` pairs, listed
+/// by `git ls-remote`
+pub fn ls_remote(url: &str) -> CargoResult<Vec<(String, String)>> {
+    let output = run(Command::new("git").arg("ls-remote").arg(url))?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            match (fields.next(), fields.next()) {
+                (Some(oid), Some(name)) => Some((name.to_owned(), oid.to_owned())),
+                _ => None,
+            }
+        })
+        .collect())
+}
+
+/// Fetch the git repositories the lock file of `workspace` locks packages
+/// of with the git binary, if `net.git-fetch-with-cli` is set, so that
+/// resolving the workspace doesn't fetch them with the built-in client
+pub fn fetch_locked_sources(workspace: &Workspace) -> CargoResult<()> {
+    let config = workspace.config();
+    let lock = workspace.root().join("Cargo.lock");
+    if !fetches_with_cli(config)? || !lock.is_file() {
+        return Ok(());
+    }
+    for url in lockfile::git_sources(&lock)? {
+        fetch_database(config, &url)?;
+    }
+    Ok(())
+}
+
+/// Fetch the branches and tags of `url` with the git binary into the
+/// database cargo keeps for it in the cargo home, returning its path
+///
+/// cargo then finds the revisions locked in `Cargo.lock` there without
+/// fetching on its own. Without network access the database is left as is.
+pub fn fetch_database(config: &Config, url: &str) -> CargoResult<PathBuf> {
+    let git_dir = config.git_path();
+    let _lock = git_dir.open_rw(".cargo-lock-git", config, "the git checkouts")?;
+    let db = git_dir.join("db").join(ident(url)?).into_path_unlocked();
+    if !config.network_allowed() {
+        return Ok(db);
+    }
+    if !db.join("HEAD").is_file() {
+        run(Command::new("git").arg("init").arg("--bare").arg(&db))?;
+    }
+    config
+        .shell()
+        .status("Updating", format!("git repository `{}` with git", url))?;
+    run(Command::new("git")
+        .arg("--git-dir")
+        .arg(&db)
+        .args(&["fetch", "--force", "--update-head-ok", url])
+        .args(&["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"]))?;
+    Ok(db)
+}
+
+/// Fetch the remotes of the git dependencies of `dependencies` with the git
+/// binary and point the dependencies at the local copies, so that cargo
+/// only ever fetches from the disk, keeping the copies in `databases`
+///
+/// Commits of a `rev` no branch or tag points to are fetched on their own.
+pub fn use_local_databases(
+    config: &Config,
+    dependencies: &mut Table,
+    databases: &mut HashMap<String, PathBuf>,
+) -> CargoResult<()> {
+    for spec in dependencies.values_mut() {
+        let spec = match *spec {
+            Value::Table(ref mut spec) => spec,
+            _ => continue,
+        };
+        let url = match spec.get("git") {
+            Some(&Value::String(ref url)) => url.clone(),
+            _ => continue,
+        };
+        if !databases.contains_key(&url) {
+            let db = fetch_database(config, &url)?;
+            databases.insert(url.clone(), db);
+        }
+        let db = &databases[&url];
+        if let Some(&Value::String(ref rev)) = spec.get("rev") {
+            fetch_commit(config, db, &url, rev);
+        }
+        let local = db.as_path().to_url()?;
+        spec.insert("git".to_owned(), Value::String(local.to_string()));
+    }
+    Ok(())
+}
+
+/// Fetch a single commit, which servers may refuse and which may be in
+/// the database already, so that failing is left to the resolution
+fn fetch_commit(config: &Config, db: &Path, url: &str, rev: &str) {
+    let has_commit = run(Command::new("git")
+        .arg("--git-dir")
+        .arg(db)
+        .args(&["cat-file", "-e", &format!("{}^{{commit}}", rev)])).is_ok();
+    if !has_commit && config.network_allowed() {
+        let _ = run(Command::new("git")
+            .arg("--git-dir")
+            .arg(db)
+            .args(&["fetch", url, rev]));
+    }
+}
+
+/// The directory name cargo gives the database of a git remote, the last
+/// segment of its path along with a hash of the URL
+fn ident(url: &str) -> CargoResult<String> {
+    let url = canonicalize_url(&url.to_url()?)?;
+    let name = url.path_segments()
+        .and_then(|mut s| s.next_back())
+        .map(|name| name.to_owned())
+        .unwrap_or_default();
+    let name = if name.is_empty() { "_empty".to_owned() } else { name };
+    Ok(format!("{}-{}", name, short_hash(&url)))
+}
+
+/// Run a git command, returning its output
+fn run(command: &mut Command) -> CargoResult<String> {
+    let output = command.output().map_err(|e| {
+        CargoError::from_kind(CargoErrorKind::Msg(format!("could not run git: {}", e)))
+    })?;
+    if !output.status.success() {
+        return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+            "git failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,159 @@
+use cargo::core::{GitReference, PackageId};
+use cargo::util::{CargoResult, Config};
+use git2::{Direction, Oid, Repository};
+use semver::Version;
+use tempdir::TempDir;
+
+use super::elaborate_workspace::ElaborateWorkspace;
+use super::git_cli;
+use super::report::Line;
+
+/// A reference advertised by a git remote
+struct RemoteRef {
+    name: String,
+    oid: Oid,
+}
+
+/// Rows of the direct git dependencies of `root` which are behind their
+/// remote, with `--git`
+///
+/// A dependency on a branch is behind if the branch moved on from the
+/// commit in the lock file, one on a tag if there is a newer semver tag,
+/// and one on a `rev` if the newest semver tag is another commit. Remotes
+/// which can't be reached are warned about and skipped, adding to `errors`,
+/// unless `strict`, which makes them an error.
+pub fn git_lines(
+    config: &Config,
+    ela: &ElaborateWorkspace,
+    root: &PackageId,
+    strict: bool,
+    errors: &mut Vec<String>,
+) -> CargoResult<Vec<Line>> {
+    let mut deps: Vec<_> = ela.pkg_deps[root]
+        .iter()
+        .filter(|&(id, _)| id.source_id().is_git())
+        .collect();
+    deps.sort_by(|a, b| a.0.cmp(b.0));
+    let mut lines = vec![];
+    for (dep_id, dependency) in deps {
+        let source_id = dep_id.source_id();
+        let url = source_id.url().to_string();
+        verbose!(config, "Fetching", format!("references of {}", url));
+        let refs = match remote_refs(config, &url) {
+            Ok(refs) => refs,
+            Err(e) => {
+                if strict {
+                    return Err(e);
+                }
+                let error = format!("skipping git dependency {}: {}", dep_id.name(), e);
+                config.shell().warn(&error)?;
+                errors.push(error);
+                continue;
+            }
+        };
+        let locked = source_id.precise().unwrap_or("");
+        let (current, latest) = match source_id.git_reference() {
+            Some(&GitReference::Tag(ref tag)) => match (tag_version(tag), newest_tag(&refs)) {
+                (Some(ref current), Some((ref name, ref version, _))) if version > current => {
+                    (tag.clone(), name.clone())
+                }
+                _ => continue,
+            },
+            Some(&GitReference::Rev(ref rev)) => match newest_tag(&refs) {
+                Some((ref name, _, oid)) if !oid.to_string().starts_with(rev.as_str()) => {
+                    (rev.clone(), name.clone())
+                }
+                _ => continue,
+            },
+            Some(&GitReference::Branch(ref branch)) => {
+                let head = format!("refs/heads/{}", branch);
+                match refs.iter().find(|r| r.name == head) {
+                    Some(head) if head.oid.to_string() != locked => {
+                        (short(locked), format!("{} ({})", short(&head.oid.to_string()), branch))
+                    }
+                    _ => continue,
+                }
+            }
+            None => continue,
+        };
+        lines.push(Line {
+            kind: Some(format!("{:?}", dependency.kind())),
+            platform: dependency.platform().map(|p| p.to_string()),
+            ..Line::new(dep_id.name().to_owned(), current, "---".to_owned(), latest)
+        });
+    }
+    Ok(lines)
+}
+
+/// List the references of a remote without fetching any objects, with the
+/// git binary if `net.git-fetch-with-cli` is set
+fn remote_refs(config: &Config, url: &str) -> CargoResult<Vec<RemoteRef>> {
+    if git_cli::fetches_with_cli(config)? {
+        let mut refs = vec![];
+        for (name, oid) in git_cli::ls_remote(url)? {
+            refs.push(RemoteRef {
+                name: name,
+                oid: Oid::from_str(&oid)?,
+            });
+        }
+        return Ok(refs);
+    }
+    // an anonymous remote needs a repository, if an empty one
+    let dir = TempDir::new("cargo-outdated-git")?;
+    let repo = Repository::init_bare(dir.path())?;
+    let mut remote = repo.remote_anonymous(url)?;
+    remote.connect(Direction::Fetch)?;
+    let refs = remote
+        .list()?
+        .iter()
+        .map(|head| RemoteRef {
+            name: head.name().to_owned(),
+            oid: head.oid(),
+        })
+        .collect();
+    Ok(refs)
+}
+
+/// Names of the tags of a remote, e.g. `v1.2.0`
+pub fn tag_names(config: &Config, url: &str) -> CargoResult<Vec<String>> {
+    Ok(remote_refs(config, url)?
+        .into_iter()
+        .filter(|r| r.name.starts_with("refs/tags/") && !r.name.ends_with("^{}"))
+        .map(|r| r.name.trim_left_matches("refs/tags/").to_owned())
+        .collect())
+}
+
+/// The newest tag named like a version, e.g. `v1.2.0` or `1.2.0`, along
+/// with the commit it points to
+fn newest_tag(refs: &[RemoteRef]) -> Option<(String, Version, Oid)> {
+    let mut newest: Option<(String, Version, Oid)> = None;
+    for r in refs {
+        let name = match r.name.trim_left_matches("refs/tags/") {
+            name if name == r.name => continue,
+            name => name,
+        };
+        // annotated tags are listed once more peeled to their commit
+        let (name, oid) = match name.trim_right_matches("^{}") {
+            peeled if peeled != name => (peeled, r.oid),
+            _ => {
+                let peeled = format!("refs/tags/{}^{{}}", name);
+                let oid = refs.iter().find(|p| p.name == peeled).map(|p| p.oid);
+                (name, oid.unwrap_or(r.oid))
+            }
+        };
+        let version = match tag_version(name) {
+            Some(ref version) if version.is_prerelease() => continue,
+            Some(version) => version,
+            None => continue,
+        };
+        if newest.as_ref().map(|n| version > n.1).unwrap_or(true) {
+            newest = Some((name.to_owned(), version, oid));
+        }
+    }
+    newest
+}
+
+fn tag_version(tag: &str) -> Option<Version> { Version::parse(tag.trim_left_matches('v')).ok() }
+
+/// Abbreviate a commit hash like git does
+fn short(oid: &str) -> String { oid.chars().take(7).collect() }
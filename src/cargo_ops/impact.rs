@@ -0,0 +1,251 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{self, Write};
+use std::path::Path;
+
+use cargo::util::{CargoError, CargoErrorKind, CargoResult};
+use serde_json;
+use tabwriter::TabWriter;
+
+use OutdatedOptions;
+use super::{ElaborateWorkspace, TempProject};
+
+/// A package whose resolved versions differ between two resolutions,
+/// `None` on a side it is missing from
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionChange {
+    pub name: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Consequences of changing the requirements on a single crate on the rest
+/// of the graph
+#[derive(Debug, Clone, Serialize)]
+pub struct Impact {
+    pub crate_name: String,
+    /// The requirement resolved with, `*` for an unconstrained upgrade
+    pub requirement: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Every other package whose version changes along
+    pub changes: Vec<VersionChange>,
+    /// Why the resolution failed, in which case nothing changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<String>,
+}
+
+impl Impact {
+    /// Re-resolve the workspace with only `name` unconstrained, i.e. with
+    /// direct requirements on it replaced by `*` and the lock file kept for
+    /// everything else, and compare the result with the current graph
+    pub fn preview(
+        ela_curr: &ElaborateWorkspace,
+        curr_manifest: &Path,
+        options: &OutdatedOptions,
+        name: &str,
+    ) -> CargoResult<Impact> {
+        let mut impact = Impact::new(ela_curr, name, "*")?;
+        let impact_versions = resolve(ela_curr, curr_manifest, options, name, "*")?;
+        impact.compare(ela_curr, &impact_versions);
+        Ok(impact)
+    }
+
+    /// Re-resolve the workspace as if the direct requirements on `name`
+    /// were `requirement`, recording the error if there is no solution
+    pub fn what_if(
+        ela_curr: &ElaborateWorkspace,
+        curr_manifest: &Path,
+        options: &OutdatedOptions,
+        name: &str,
+        requirement: &str,
+    ) -> CargoResult<Impact> {
+        let direct = ela_curr.workspace.members().any(|member| {
+            ela_curr.pkg_deps[member.package_id()]
+                .keys()
+                .any(|id| id.name() == name)
+        });
+        if !direct {
+            return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+                "{} is not a direct dependency of any workspace member",
+                name
+            ))));
+        }
+        let mut impact = Impact::new(ela_curr, name, requirement)?;
+        match resolve(ela_curr, curr_manifest, options, name, requirement) {
+            Ok(impact_versions) => impact.compare(ela_curr, &impact_versions),
+            Err(e) => {
+                let messages: Vec<_> = e.iter().map(|cause| cause.to_string()).collect();
+                impact.conflict = Some(messages.join("\n"));
+            }
+        }
+        Ok(impact)
+    }
+
+    fn new(ela_curr: &ElaborateWorkspace, name: &str, requirement: &str) -> CargoResult<Impact> {
+        let from = match versions_by_name(ela_curr).get(name) {
+            Some(versions) => join(versions),
+            None => {
+                return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+                    "{} is not in the dependency graph",
+                    name
+                ))))
+            }
+        };
+        Ok(Impact {
+            crate_name: name.to_owned(),
+            requirement: requirement.to_owned(),
+            from: from.clone(),
+            to: from,
+            changes: vec![],
+            conflict: None,
+        })
+    }
+
+    /// Fill in the changes from the current graph to `impact_versions`
+    fn compare(&mut self, ela_curr: &ElaborateWorkspace, impact_versions: &Versions) {
+        let mut changes = changes_between(&versions_by_name(ela_curr), impact_versions);
+        if let Some(i) = changes.iter().position(|change| change.name == self.crate_name) {
+            let change = changes.remove(i);
+            self.from = change.from;
+            self.to = change.to;
+        }
+        self.changes = changes;
+    }
+
+    pub fn print_list(&self) -> CargoResult<()> {
+        let subject = if self.requirement == "*" {
+            format!("Upgrading {}", self.crate_name)
+        } else {
+            format!("With {} = \"{}\", {}", self.crate_name, self.requirement, self.crate_name)
+        };
+        if let Some(ref conflict) = self.conflict {
+            println!(
+                "{} = \"{}\" fails to resolve:\n\n{}",
+                self.crate_name, self.requirement, conflict
+            );
+            return Ok(());
+        }
+        if self.from == self.to {
+            println!(
+                "{} stays at {}, nothing changes",
+                self.crate_name,
+                or_dashes(&self.from)
+            );
+            return Ok(());
+        }
+        if self.changes.is_empty() {
+            println!(
+                "{} from {} to {} changes no other package",
+                subject,
+                or_dashes(&self.from),
+                or_dashes(&self.to)
+            );
+            return Ok(());
+        }
+        println!(
+            "{} from {} to {} also changes:\n",
+            subject,
+            or_dashes(&self.from),
+            or_dashes(&self.to)
+        );
+        print_changes(&self.changes)
+    }
+
+    pub fn print_json(&self) -> CargoResult<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        serde_json::to_writer_pretty(&mut stdout, self)?;
+        writeln!(stdout)?;
+        Ok(())
+    }
+}
+
+/// Print version changes to `TabWriter`
+pub fn print_changes(changes: &[VersionChange]) -> CargoResult<()> {
+    let mut tw = TabWriter::new(vec![]);
+    write!(&mut tw, "Name\tFrom\tTo\n")?;
+    write!(&mut tw, "----\t----\t--\n")?;
+    for change in changes {
+        write!(
+            &mut tw,
+            "{}\t{}\t{}\n",
+            change.name,
+            or_dashes(&change.from),
+            or_dashes(&change.to)
+        )?;
+    }
+    tw.flush()?;
+    write!(
+        io::stdout(),
+        "{}",
+        String::from_utf8(tw.into_inner().unwrap()).unwrap()
+    )?;
+    io::stdout().flush()?;
+    Ok(())
+}
+
+type Versions = BTreeMap<String, BTreeSet<String>>;
+
+/// Versions of the graph resolved with `requirement` on `name`, updating
+/// just `name` in the lock file
+fn resolve(
+    ela_curr: &ElaborateWorkspace,
+    curr_manifest: &Path,
+    options: &OutdatedOptions,
+    name: &str,
+    requirement: &str,
+) -> CargoResult<Versions> {
+    let proj = TempProject::from_workspace(ela_curr, curr_manifest, options)?;
+    let mut requirements = HashMap::new();
+    requirements.insert(name.to_owned(), requirement.to_owned());
+    proj.write_manifest_requirements(&requirements)?;
+    proj.cargo_update_packages(&[name.to_owned()])?;
+    let workspace = proj.workspace.borrow();
+    let ela = ElaborateWorkspace::from_workspace(workspace.as_ref().unwrap(), options)?;
+    Ok(versions_by_name(&ela))
+}
+
+/// Versions of every package in the graph, a crate may be there more than once
+fn versions_by_name(ela: &ElaborateWorkspace) -> Versions {
+    let mut versions = BTreeMap::new();
+    for id in ela.pkgs.keys() {
+        versions
+            .entry(id.name().to_owned())
+            .or_insert_with(BTreeSet::new)
+            .insert(id.version().to_string());
+    }
+    versions
+}
+
+/// Packages whose versions differ between two graphs
+pub fn changes_between(before: &Versions, after: &Versions) -> Vec<VersionChange> {
+    let names: BTreeSet<_> = before.keys().chain(after.keys()).collect();
+    let empty = BTreeSet::new();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let from = before.get(name).unwrap_or(&empty);
+            let to = after.get(name).unwrap_or(&empty);
+            if from == to {
+                return None;
+            }
+            Some(VersionChange {
+                name: name.clone(),
+                from: join(&from.difference(to).cloned().collect()),
+                to: join(&to.difference(from).cloned().collect()),
+            })
+        })
+        .collect()
+}
+
+fn join(versions: &BTreeSet<String>) -> Option<String> {
+    if versions.is_empty() {
+        None
+    } else {
+        Some(versions.iter().cloned().collect::<Vec<_>>().join(", "))
+    }
+}
+
+fn or_dashes(version: &Option<String>) -> &str {
+    version.as_ref().map(|v| v.as_str()).unwrap_or("---")
+}
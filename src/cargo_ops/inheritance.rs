@@ -0,0 +1,24 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use cargo::util::CargoResult;
+
+use super::{is_inherited, Manifest};
+
+/// Names of the dependencies of a manifest which inherit their requirement
+/// from the workspace
+pub fn inherited_dependencies(manifest_path: &Path) -> CargoResult<Vec<String>> {
+    let mut contents = String::new();
+    File::open(manifest_path)?.read_to_string(&mut contents)?;
+    let mut manifest = Manifest::parse(&contents)?;
+    let mut inherited = vec![];
+    for table in manifest.dependency_tables_mut() {
+        for (name, spec) in table.iter() {
+            if is_inherited(spec) {
+                inherited.push(name.clone());
+            }
+        }
+    }
+    Ok(inherited)
+}
@@ -0,0 +1,130 @@
+use std::fs::File;
+use std::io::Read;
+
+use cargo::util::{CargoError, CargoErrorKind, CargoResult, Config};
+use semver::{Version, VersionReq};
+use toml::Value;
+
+use super::OutdatedOptions;
+use super::pkg_status::{is_candidate, Status};
+use super::registry::{stale_index_secs, RegistryIndex};
+use super::report::{Line, Report};
+
+/// A package installed with `cargo install`
+struct Installed {
+    name: String,
+    version: Version,
+}
+
+/// Report `cargo install`ed packages from crates.io with newer versions,
+/// or whose version was yanked
+pub fn installed_report(options: &OutdatedOptions, config: &Config) -> CargoResult<Report> {
+    let mut index = RegistryIndex::crates_io(config)?;
+    let mut lines = vec![];
+    let mut up_to_date = 0;
+    for installed in installed_packages(config)? {
+        let yanked = index.yanked(&installed.name)?.contains(&installed.version);
+        let versions = index.versions(&installed.name)?;
+        let current = &installed.version;
+        let compat_req = VersionReq::parse(&format!("^{}", current))?;
+        let candidates: Vec<_> = versions
+            .iter()
+            .filter(|v| is_candidate(v, current, options.flag_pre_release))
+            .collect();
+        let compat = match candidates.iter().rev().find(|v| compat_req.matches(v)) {
+            Some(compat) if *compat > current => Status::Version((*compat).clone()),
+            _ => Status::Unchanged,
+        };
+        // --within narrows the latest version down to a series
+        let latest = match options.flag_within.get(&installed.name) {
+            Some(within) => {
+                let within_req = VersionReq::parse(within)?;
+                candidates.iter().rev().find(|v| within_req.matches(v)).cloned()
+            }
+            None => candidates.last().cloned(),
+        };
+        let latest = match latest {
+            Some(latest) if latest > current => Status::Version(latest.clone()),
+            _ if candidates.is_empty() => Status::Unknown,
+            _ => Status::Unchanged,
+        };
+        if !compat.is_changed() && !latest.is_changed() && !yanked {
+            up_to_date += 1;
+            continue;
+        }
+        if !options.flag_packages.is_empty() && !options.flag_packages.contains(&installed.name)
+        {
+            continue;
+        }
+        if options.flag_ignore.contains(&installed.name) {
+            continue;
+        }
+        let severity = latest
+            .severity(current, options.flag_zero_major_as_breaking)
+            .or_else(|| compat.severity(current, options.flag_zero_major_as_breaking))
+            .map(|s| s.to_string());
+        lines.push(Line {
+            severity: severity,
+            yanked: yanked,
+            ..Line::new(
+                installed.name.clone(),
+                current.to_string(),
+                compat.to_string(),
+                latest.to_string(),
+            )
+        });
+    }
+    lines.retain(|line| line.has_severity(&options.flag_only));
+    if !options.flag_no_sort {
+        lines.sort();
+    }
+    Ok(Report {
+        crate_name: "installed".to_owned(),
+        project: None,
+        dependencies: lines,
+        graph_changes: vec![],
+        index_age_secs: stale_index_secs(config),
+        latest_unavailable: false,
+        up_to_date: up_to_date,
+        errors: vec![],
+    })
+}
+
+/// Packages from crates.io recorded in `$CARGO_HOME/.crates.toml`
+fn installed_packages(config: &Config) -> CargoResult<Vec<Installed>> {
+    let path = config.home().as_path_unlocked().join(".crates.toml");
+    let mut contents = String::new();
+    match File::open(&path) {
+        Ok(mut file) => file.read_to_string(&mut contents)?,
+        // nothing has been installed yet
+        Err(_) => return Ok(vec![]),
+    };
+    let value: Value = ::toml::from_str(&contents)?;
+    let packages = match value.get("v1").and_then(|v| v.as_table()) {
+        Some(packages) => packages,
+        None => return Ok(vec![]),
+    };
+    let mut installed = vec![];
+    for key in packages.keys() {
+        // e.g. `ripgrep 0.6.0 (registry+https://github.com/rust-lang/crates.io-index)`
+        let mut parts = key.splitn(3, ' ');
+        let (name, version, source) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(name), Some(version), Some(source)) => (name, version, source),
+            _ => {
+                return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+                    "Invalid package `{}` in {}",
+                    key,
+                    path.display()
+                ))))
+            }
+        };
+        if !source.starts_with("(registry+") {
+            continue;
+        }
+        installed.push(Installed {
+            name: name.to_owned(),
+            version: Version::parse(version)?,
+        });
+    }
+    Ok(installed)
+}
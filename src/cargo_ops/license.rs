@@ -0,0 +1,87 @@
+use cargo::util::CargoResult;
+
+use super::crates_io::CratesIo;
+use super::report::Line;
+
+/// Licenses upgrades may or may not pull into the tree, as patterns
+/// where `*` matches any sequence of characters
+#[derive(Debug, Default)]
+pub struct LicensePolicy {
+    /// When not empty, every license has to match one of these
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl LicensePolicy {
+    pub fn is_empty(&self) -> bool { self.allow.is_empty() && self.deny.is_empty() }
+
+    /// Whether software under an SPDX expression like `MIT OR Apache-2.0`
+    /// can be used, i.e. any alternative only consists of allowed licenses
+    pub fn is_allowed(&self, expression: &str) -> bool {
+        let expression = expression.replace('(', " ").replace(')', " ");
+        // `/` is the deprecated form of `OR` still used by old crates
+        expression
+            .replace('/', " OR ")
+            .split(" OR ")
+            .any(|alternative| {
+                alternative
+                    .split(" AND ")
+                    .map(|license| license.trim())
+                    .all(|license| self.is_license_allowed(license))
+            })
+    }
+
+    fn is_license_allowed(&self, license: &str) -> bool {
+        (self.allow.is_empty() || self.allow.iter().any(|p| matches_pattern(p, license)))
+            && !self.deny.iter().any(|p| matches_pattern(p, license))
+    }
+}
+
+/// Mark rows whose latest version has a license denied by `policy`
+pub fn annotate_licenses(
+    crates_io: &mut CratesIo,
+    policy: &LicensePolicy,
+    lines: &mut [Line],
+) -> CargoResult<()> {
+    if policy.is_empty() {
+        return Ok(());
+    }
+    for line in lines.iter_mut() {
+        let name = line.crate_name().to_owned();
+        let license = crates_io
+            .version_info(&name, &line.latest)?
+            .and_then(|version| version.license.clone());
+        line.denied_license = match license {
+            Some(license) => if policy.is_allowed(&license) {
+                None
+            } else {
+                Some(license)
+            },
+            None => None,
+        };
+    }
+    Ok(())
+}
+
+/// Match a license against a pattern like `GPL-3.0*`
+fn matches_pattern(pattern: &str, license: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    if !license.starts_with(first) {
+        return false;
+    }
+    let mut rest = &license[first.len()..];
+    let parts: Vec<_> = parts.collect();
+    match parts.split_last() {
+        None => rest.is_empty(),
+        Some((last, middle)) => {
+            for part in middle {
+                match rest.find(part) {
+                    Some(i) => rest = &rest[i + part.len()..],
+                    None => return false,
+                }
+            }
+            rest.ends_with(last)
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use cargo::util::{CargoResult, Config};
+
+use super::crates_io::CratesIo;
+use super::git_deps::tag_names;
+use super::report::Line;
+
+/// Fill in the repository, or else the homepage, crates.io has for every
+/// row, along with a compare URL between the tags of the version in use and
+/// the latest one for repositories on GitHub, with `--show-links`
+///
+/// Repositories whose tags can't be listed only get the link.
+pub fn annotate_links(
+    config: &Config,
+    crates_io: &mut CratesIo,
+    lines: &mut [Line],
+) -> CargoResult<()> {
+    for line in lines.iter_mut() {
+        let name = line.crate_name().to_owned();
+        let (repository, homepage) = match crates_io.crate_metadata(&name)? {
+            Some(metadata) => (
+                metadata.krate.repository.clone(),
+                metadata.krate.homepage.clone(),
+            ),
+            None => continue,
+        };
+        line.compare_url = match repository.as_ref().and_then(|r| github_repository(r)) {
+            Some(repository) => compare_url(config, &repository, &name, line)?,
+            None => None,
+        };
+        line.link = repository.or(homepage);
+    }
+    Ok(())
+}
+
+/// `https://github.com/owner/repo` of a repository URL on GitHub, e.g.
+/// `https://github.com/owner/repo.git` or `https://github.com/owner/repo/tree/master/sub`
+fn github_repository(url: &str) -> Option<String> {
+    let path = url.trim_left_matches("https://")
+        .trim_left_matches("http://")
+        .trim_left_matches("www.");
+    if !path.starts_with("github.com/") {
+        return None;
+    }
+    let parts: Vec<_> = path.split('/').skip(1).take(2).collect();
+    if parts.len() < 2 || parts.iter().any(|part| part.is_empty()) {
+        return None;
+    }
+    Some(format!(
+        "https://github.com/{}/{}",
+        parts[0],
+        parts[1].trim_right_matches(".git")
+    ))
+}
+
+/// The URL comparing the tags of the version in use and the latest one, if
+/// the repository has both
+fn compare_url(
+    config: &Config,
+    repository: &str,
+    name: &str,
+    line: &Line,
+) -> CargoResult<Option<String>> {
+    verbose!(config, "Fetching", format!("tags of {}", repository));
+    let tags = match tag_names(config, repository) {
+        Ok(tags) => tags,
+        Err(_) => return Ok(None),
+    };
+    Ok(
+        match (version_tag(&tags, name, &line.project), version_tag(&tags, name, &line.latest)) {
+            (Some(current), Some(latest)) => {
+                Some(format!("{}/compare/{}...{}", repository, current, latest))
+            }
+            _ => None,
+        },
+    )
+}
+
+/// The tag of a version, named like `v1.2.0`, `1.2.0` or, in repositories
+/// of several crates, `foo-v1.2.0` or `foo-1.2.0`
+fn version_tag<'a>(tags: &'a [String], name: &str, version: &str) -> Option<&'a String> {
+    let candidates = [
+        format!("v{}", version),
+        version.to_owned(),
+        format!("{}-v{}", name, version),
+        format!("{}-{}", name, version),
+    ];
+    candidates
+        .iter()
+        .filter_map(|candidate| tags.iter().find(|tag| *tag == candidate))
+        .next()
+}
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use cargo::util::errors::CargoResultExt;
+use cargo::util::{CargoError, CargoErrorKind, CargoResult};
+use toml::Value;
+use toml::value::Table;
+
+/// Copy a lock file, converting it to the encoding this version of cargo understands
+///
+/// Lock files come in four formats:
+///
+/// - v1 lists every dependency as `name version (source)` and keeps
+///   checksums in a `[metadata]` table
+/// - v2 stores checksums inline and only spells out the version and
+///   source of a dependency when its name alone is ambiguous
+/// - v3 adds a top-level `version = 3` marker
+/// - v4 additionally percent-encodes the query of git sources
+///
+/// All of them are rewritten in the v1 format
+pub fn copy_normalized<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> CargoResult<()> {
+    let mut buf = String::new();
+    File::open(from)?.read_to_string(&mut buf)?;
+    let mut lockfile: Value = ::toml::from_str(&buf)?;
+    let normalized = match lockfile {
+        Value::Table(ref mut table) if !is_v1(table) => {
+            normalize(table)?;
+            None
+        }
+        _ => Some(buf),
+    };
+    let normalized = match normalized {
+        Some(normalized) => normalized,
+        None => serialize(&lockfile)?,
+    };
+    let mut file = File::create(to)?;
+    write!(file, "{}", normalized)?;
+    Ok(())
+}
+
+/// The lock file as TOML, which fails on values TOML can't hold, like
+/// arrays of mixed types
+fn serialize(lockfile: &Value) -> CargoResult<String> {
+    ::toml::to_string(lockfile).chain_err(|| "Failed to serialize Cargo.lock")
+}
+
+/// URLs of the git repositories packages in a lock file come from, without
+/// the reference and the locked commit
+pub fn git_sources<P: AsRef<Path>>(path: P) -> CargoResult<Vec<String>> {
+    let mut buf = String::new();
+    File::open(path)?.read_to_string(&mut buf)?;
+    let lockfile: Table = ::toml::from_str(&buf)?;
+    let mut urls: Vec<_> = packages(&lockfile)
+        .into_iter()
+        .filter_map(|pkg| str_field(pkg, "source"))
+        .filter(|source| source.starts_with("git+"))
+        .map(|source| {
+            let url = &source["git+".len()..];
+            url[..url.find(|c| c == '?' || c == '#').unwrap_or(url.len())].to_owned()
+        })
+        .collect();
+    urls.sort();
+    urls.dedup();
+    Ok(urls)
+}
+
+fn is_v1(lockfile: &Table) -> bool {
+    !lockfile.contains_key("version") && packages(lockfile).iter().all(|pkg| {
+        !pkg.contains_key("checksum") && dependencies(pkg).iter().all(|dep| dep.contains(' '))
+    })
+}
+
+fn packages(lockfile: &Table) -> Vec<&Table> {
+    match lockfile.get("package") {
+        Some(&Value::Array(ref packages)) => packages
+            .iter()
+            .filter_map(|pkg| match *pkg {
+                Value::Table(ref pkg) => Some(pkg),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn dependencies(pkg: &Table) -> Vec<&str> {
+    match pkg.get("dependencies") {
+        Some(&Value::Array(ref deps)) => deps.iter().filter_map(|dep| dep.as_str()).collect(),
+        _ => vec![],
+    }
+}
+
+fn str_field<'a>(pkg: &'a Table, key: &str) -> Option<&'a str> {
+    pkg.get(key).and_then(|v| v.as_str())
+}
+
+fn normalize(lockfile: &mut Table) -> CargoResult<()> {
+    let format_version = lockfile
+        .remove("version")
+        .and_then(|v| v.as_integer())
+        .unwrap_or(2);
+    if format_version > 4 {
+        return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+            "Lock file version {} is not supported",
+            format_version
+        ))));
+    }
+
+    // name -> [(version, source)]
+    let mut known: HashMap<String, Vec<(String, Option<String>)>> = HashMap::new();
+    for pkg in packages(lockfile) {
+        if let (Some(name), Some(version)) = (str_field(pkg, "name"), str_field(pkg, "version")) {
+            let source = str_field(pkg, "source").map(|s| decode_source(s, format_version));
+            known
+                .entry(name.to_owned())
+                .or_insert_with(Vec::new)
+                .push((version.to_owned(), source));
+        }
+    }
+
+    let mut metadata = match lockfile.remove("metadata") {
+        Some(Value::Table(metadata)) => metadata,
+        _ => Table::new(),
+    };
+    if let Some(&mut Value::Array(ref mut pkgs)) = lockfile.get_mut("package") {
+        for pkg in pkgs.iter_mut() {
+            if let Value::Table(ref mut pkg) = *pkg {
+                normalize_package(pkg, &known, &mut metadata, format_version)?;
+            }
+        }
+    }
+    if !metadata.is_empty() {
+        lockfile.insert("metadata".to_owned(), Value::Table(metadata));
+    }
+    Ok(())
+}
+
+fn normalize_package(
+    pkg: &mut Table,
+    known: &HashMap<String, Vec<(String, Option<String>)>>,
+    metadata: &mut Table,
+    format_version: i64,
+) -> CargoResult<()> {
+    let source = str_field(pkg, "source").map(|s| decode_source(s, format_version));
+    if let Some(ref source) = source {
+        pkg.insert("source".to_owned(), Value::String(source.clone()));
+    }
+    if let Some(Value::String(checksum)) = pkg.remove("checksum") {
+        let id = encode_id(
+            str_field(pkg, "name").unwrap_or_default(),
+            str_field(pkg, "version").unwrap_or_default(),
+            source.as_ref().map(|s| s.as_str()),
+        );
+        metadata.insert(format!("checksum {}", id), Value::String(checksum));
+    }
+    let deps = dependencies(pkg)
+        .into_iter()
+        .map(|dep| expand_dependency(dep, known, format_version))
+        .collect::<CargoResult<Vec<_>>>()?;
+    if pkg.contains_key("dependencies") {
+        pkg.insert(
+            "dependencies".to_owned(),
+            Value::Array(deps.into_iter().map(Value::String).collect()),
+        );
+    }
+    Ok(())
+}
+
+/// Expand an abbreviated `name [version] [(source)]` dependency entry
+fn expand_dependency(
+    dep: &str,
+    known: &HashMap<String, Vec<(String, Option<String>)>>,
+    format_version: i64,
+) -> CargoResult<String> {
+    let mut parts = dep.splitn(3, ' ');
+    let name = parts.next().unwrap_or_default();
+    let version = parts.next();
+    let source = parts
+        .next()
+        .map(|s| decode_source(s.trim_left_matches('(').trim_right_matches(')'), format_version));
+    let candidates = known.get(name).map(|c| c.as_slice()).unwrap_or(&[]);
+    let matched: Vec<_> = candidates
+        .iter()
+        .filter(|&&(ref v, ref s)| {
+            version.map_or(true, |version| version == v.as_str())
+                && source.as_ref().map_or(true, |source| Some(source) == s.as_ref())
+        })
+        .collect();
+    match matched.len() {
+        1 => Ok(encode_id(
+            name,
+            &matched[0].0,
+            matched[0].1.as_ref().map(|s| s.as_str()),
+        )),
+        _ => Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+            "Dependency `{}` in lock file is ambiguous or missing",
+            dep
+        )))),
+    }
+}
+
+fn encode_id(name: &str, version: &str, source: Option<&str>) -> String {
+    match source {
+        Some(source) => format!("{} {} ({})", name, version, source),
+        None => format!("{} {}", name, version),
+    }
+}
+
+/// Undo the percent-encoding of git source queries introduced in v4
+fn decode_source(source: &str, format_version: i64) -> String {
+    if format_version < 4 || !source.starts_with("git+") {
+        return source.to_owned();
+    }
+    let (url, fragment) = match source.find('#') {
+        Some(i) => source.split_at(i),
+        None => (source, ""),
+    };
+    match url.find('?') {
+        Some(i) => format!("{}{}{}", &url[..i + 1], percent_decode(&url[i + 1..]), fragment),
+        None => source.to_owned(),
+    }
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let byte = ::std::str::from_utf8(&bytes[i + 1..i + 3])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            if let Some(byte) = byte {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::path::Path;
+
+    use tempdir::TempDir;
+    use toml::Value;
+    use toml::value::Table;
+
+    use super::{copy_normalized, decode_source, git_sources, normalize, percent_decode,
+                serialize};
+
+    const V1: &str = include_str!("../../tests/fixtures/lockfiles/v1.lock");
+    const V2: &str = include_str!("../../tests/fixtures/lockfiles/v2.lock");
+    const V3: &str = include_str!("../../tests/fixtures/lockfiles/v3.lock");
+    const V4: &str = include_str!("../../tests/fixtures/lockfiles/v4.lock");
+
+    fn parse(lockfile: &str) -> Table { ::toml::from_str(lockfile).unwrap() }
+
+    fn write(path: &Path, lockfile: &str) {
+        File::create(path)
+            .unwrap()
+            .write_all(lockfile.as_bytes())
+            .unwrap();
+    }
+
+    /// Copy a lock file the way the temporary projects get theirs
+    fn copy(lockfile: &str) -> String {
+        let dir = TempDir::new("cargo-outdated-test").unwrap();
+        let from = dir.path().join("from.lock");
+        let to = dir.path().join("Cargo.lock");
+        write(&from, lockfile);
+        copy_normalized(&from, &to).unwrap();
+        let mut copied = String::new();
+        File::open(&to).unwrap().read_to_string(&mut copied).unwrap();
+        copied
+    }
+
+    #[test]
+    fn v1_is_copied_as_is() {
+        assert_eq!(copy(V1), V1);
+    }
+
+    #[test]
+    fn newer_formats_are_copied_as_v1() {
+        for lockfile in &[V2, V3, V4] {
+            assert_eq!(parse(&copy(lockfile)), parse(V1));
+        }
+    }
+
+    #[test]
+    fn format_marker_is_removed() {
+        let mut lockfile = parse(V3);
+        normalize(&mut lockfile).unwrap();
+        assert!(!lockfile.contains_key("version"));
+    }
+
+    #[test]
+    fn newer_formats_fail() {
+        let mut lockfile = parse(&V4.replace("version = 4", "version = 5"));
+        assert!(normalize(&mut lockfile).is_err());
+    }
+
+    #[test]
+    fn ambiguous_dependencies_fail() {
+        let mut lockfile = parse(&V2.replace("\"rand 0.7.3\"", "\"rand\""));
+        assert!(normalize(&mut lockfile).is_err());
+        let mut lockfile = parse(&V2.replace("\"serde\",\n]", "\"serde 2.0.0\",\n]"));
+        assert!(normalize(&mut lockfile).is_err());
+    }
+
+    #[test]
+    fn git_sources_are_decoded_from_v4_only() {
+        let encoded = "git+https://github.com/owner/foo?branch=feature%2Fx#0123abc";
+        assert_eq!(
+            decode_source(encoded, 4),
+            "git+https://github.com/owner/foo?branch=feature/x#0123abc"
+        );
+        assert_eq!(decode_source(encoded, 3), encoded);
+        let fragment = "git+https://github.com/owner/foo#a%2Fb";
+        assert_eq!(decode_source(fragment, 4), fragment);
+        let registry = "registry+https://example.com/index?a=b%2Fc";
+        assert_eq!(decode_source(registry, 4), registry);
+    }
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(percent_decode("feature%2Fx"), "feature/x");
+        assert_eq!(percent_decode("%41%42c"), "ABc");
+        assert_eq!(percent_decode("%e2%9c%93"), "\u{2713}");
+        assert_eq!(percent_decode("%ff"), "\u{fffd}");
+        // not an escape
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%4"), "%4");
+        assert_eq!(percent_decode("%zz%41"), "%zzA");
+        assert_eq!(percent_decode(""), "");
+    }
+
+    #[test]
+    fn unserializable_lock_files_are_an_error() {
+        let mut lockfile = Table::new();
+        lockfile.insert(
+            "package".to_owned(),
+            Value::Array(vec![Value::String("a".to_owned()), Value::Integer(1)]),
+        );
+        assert!(serialize(&Value::Table(lockfile)).is_err());
+    }
+
+    #[test]
+    fn git_sources_of_every_format() {
+        let dir = TempDir::new("cargo-outdated-test").unwrap();
+        for lockfile in &[V1, V2, V3, V4] {
+            let path = dir.path().join("Cargo.lock");
+            write(&path, lockfile);
+            assert_eq!(git_sources(&path).unwrap(), vec!["https://github.com/owner/foo"]);
+        }
+    }
+}
@@ -1,40 +1,395 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use cargo::util::{CargoError, CargoErrorKind, CargoResult, Config};
 use toml::value::{Table, Value};
-use super::Options;
+use super::OutdatedOptions;
 
+mod advisories;
+mod annotations;
+mod artifact;
+mod api_diff;
+mod audit;
+mod consolidate;
 mod pkg_status;
+mod policy;
+mod project_config;
+mod publishers;
 mod temp_project;
 mod elaborate_workspace;
+mod crates_io;
+mod crate_diff;
+mod dates;
+mod edition;
+mod effort;
+mod exec;
+pub mod discovery;
+mod git_cli;
+mod git_deps;
+mod license;
+mod links;
+mod impact;
+mod inheritance;
+mod installed;
+mod lockfile;
+mod offline;
+mod platform;
+pub mod printer;
+mod registry;
+mod batch;
+pub mod report;
+mod report_cache;
+mod report_diff;
+mod rust_version;
+mod self_version;
+mod stats;
+mod successors;
+mod upgrade;
 pub use self::pkg_status::*;
+pub use self::printer::write_atomically;
+pub use self::advisories::AdvisoryDatabase;
+pub use self::annotations::LocalAnnotations;
+pub use self::api_diff::{annotate_breaking_changes, RustdocJson};
+pub use self::audit::CrateAudit;
+pub use self::project_config::{Defaults, ProjectConfig};
+pub use self::publishers::{annotate_owners, annotate_publishers};
 pub use self::temp_project::TempProject;
 pub use self::elaborate_workspace::ElaborateWorkspace;
+pub use self::batch::BatchReport;
+pub use self::consolidate::ConsolidationReport;
+pub use self::crates_io::CratesIo;
+pub use self::crate_diff::CrateDiff;
+pub use self::dates::annotate_dates;
+pub use self::edition::{annotate_editions, newest_edition};
+pub use self::effort::annotate_effort;
+pub use self::exec::exec_per_dependency;
+pub use self::git_cli::fetch_locked_sources;
+pub use self::git_deps::git_lines;
+pub use self::license::{annotate_licenses, LicensePolicy};
+pub use self::links::annotate_links;
+pub use self::impact::Impact;
+pub use self::installed::installed_report;
+pub use self::registry::{annotate_series, describe_age, stale_index_secs, RegistryIndex};
+pub use self::report::Report;
+pub use self::report_cache::ReportCache;
+pub use self::report_diff::ReportDiff;
+pub use self::rust_version::{annotate_newest_releases, annotate_rust_versions, RustVersions};
+pub use self::self_version::SelfVersionReport;
+pub use self::stats::Stats;
+pub use self::upgrade::{prompt_upgrade, write_requirements};
+
+/// Directory cargo-outdated keeps downloads in, e.g. rustdoc JSON and the
+/// advisory database, `dir` if given, else `$CARGO_OUTDATED_CACHE_DIR` if
+/// set and the user's cache directory otherwise
+pub fn cache_dir(config: &Config, dir: Option<&str>) -> PathBuf {
+    match dir.map(OsString::from).or_else(|| env::var_os("CARGO_OUTDATED_CACHE_DIR")) {
+        Some(dir) => config.cwd().join(dir),
+        None => user_cache_dir()
+            .map(|dir| dir.join("cargo-outdated"))
+            .unwrap_or_else(|| config.home().as_path_unlocked().join("cargo-outdated")),
+    }
+}
+
+/// `$XDG_CACHE_HOME`, or else `~/.cache`
+#[cfg(not(windows))]
+fn user_cache_dir() -> Option<PathBuf> {
+    // relative ones are to be ignored, as the XDG specification says
+    let absolute = |var: &str| {
+        env::var_os(var)
+            .map(PathBuf::from)
+            .and_then(|dir| if dir.is_absolute() { Some(dir) } else { None })
+    };
+    absolute("XDG_CACHE_HOME").or_else(|| absolute("HOME").map(|home| home.join(".cache")))
+}
+
+/// `%LOCALAPPDATA%`
+#[cfg(windows)]
+fn user_cache_dir() -> Option<PathBuf> { env::var_os("LOCALAPPDATA").map(PathBuf::from) }
+
+/// Keys of the tables holding dependency specs
+const DEPENDENCY_KEYS: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
 
-/// A continent struct for quick parsing and manipulating manifest
-#[derive(Debug, Serialize, Deserialize)]
+/// A manifest kept as the raw TOML table, so that sections and keys this
+/// version knows nothing about, like `[lints]`, survive being rewritten
+#[derive(Debug)]
 struct Manifest {
-    pub package: Value,
-    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "opt_tables_last")]
-    pub dependencies: Option<Table>,
-    #[serde(rename = "dev-dependencies", skip_serializing_if = "Option::is_none",
-            serialize_with = "opt_tables_last")]
-    pub dev_dependencies: Option<Table>,
-    #[serde(rename = "build-dependencies", skip_serializing_if = "Option::is_none",
-            serialize_with = "opt_tables_last")]
-    pub build_dependencies: Option<Table>,
-    pub lib: Option<Table>,
-    pub bin: Option<Vec<Table>>,
-    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "opt_tables_last")]
-    pub workspace: Option<Table>,
-    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "opt_tables_last")]
-    pub target: Option<Table>,
-    pub features: Option<Value>,
+    raw: Table,
+}
+
+impl Manifest {
+    fn parse(contents: &str) -> CargoResult<Manifest> {
+        Ok(Manifest {
+            raw: ::toml::from_str(contents)?,
+        })
+    }
+
+    fn to_toml(&self) -> CargoResult<String> {
+        // a `Value` puts plain values before tables, unlike a bare `Table`
+        Ok(::toml::to_string(&Value::Table(self.raw.clone()))?)
+    }
+
+    fn table_mut(&mut self, key: &str) -> Option<&mut Table> {
+        match self.raw.get_mut(key) {
+            Some(&mut Value::Table(ref mut table)) => Some(table),
+            _ => None,
+        }
+    }
+
+    fn package_mut(&mut self) -> Option<&mut Table> { self.table_mut("package") }
+
+    fn lib_mut(&mut self) -> Option<&mut Table> { self.table_mut("lib") }
+
+    /// Replace all binary targets by a single one
+    fn set_bin(&mut self, bin: Table) {
+        self.raw
+            .insert("bin".to_owned(), Value::Array(vec![Value::Table(bin)]));
+    }
+
+    fn remove(&mut self, key: &str) { self.raw.remove(key); }
+
+    /// Replace `package` fields inherited with `field.workspace = true` by
+    /// the values of the workspace root's `[workspace.package]` table
+    fn inherit_package_fields(&mut self, workspace_package: Option<&Table>) {
+        let package = match self.package_mut() {
+            Some(package) => package,
+            None => return,
+        };
+        let inherited: Vec<_> = package
+            .iter()
+            .filter(|&(_, value)| is_inherited(value))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in inherited {
+            match workspace_package.and_then(|p| p.get(&key)) {
+                Some(value) => package.insert(key, value.clone()),
+                // the version is the only mandatory field
+                None if key == "version" => {
+                    package.insert(key, Value::String("0.0.0".to_owned()))
+                }
+                None => package.remove(&key),
+            };
+        }
+    }
+
+    /// Replace dependency specs inherited with `dep.workspace = true` by the
+    /// entries of the workspace root's `[workspace.dependencies]` table,
+    /// removing and returning the dependencies whose path can't be resolved
+    /// along with the reason
+    ///
+    /// `features` add to those of the entry and other keys, like
+    /// `optional`, override it. Relative paths of the entries are relative
+    /// to the root and get replaced by what `resolve_path` makes of them.
+    fn inherit_dependencies(
+        &mut self,
+        workspace_dependencies: Option<&Table>,
+        resolve_path: &Fn(&str) -> Result<String, String>,
+    ) -> CargoResult<Vec<(String, String)>> {
+        let mut unresolvable = vec![];
+        for dependencies in self.dependency_tables_mut() {
+            let inherited: Vec<_> = dependencies
+                .iter()
+                .filter(|&(_, spec)| is_inherited(spec))
+                .map(|(name, _)| name.clone())
+                .collect();
+            for name in inherited {
+                let mut resolved = match workspace_dependencies.and_then(|d| d.get(&name)) {
+                    Some(&Value::String(ref requirement)) => {
+                        let mut resolved = Table::new();
+                        resolved.insert("version".to_owned(), Value::String(requirement.clone()));
+                        resolved
+                    }
+                    Some(&Value::Table(ref entry)) => entry.clone(),
+                    _ => {
+                        return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+                            "dependency {} is inherited from the workspace, which has no \
+                             entry for it in [workspace.dependencies]",
+                            name
+                        ))))
+                    }
+                };
+                if let Some(Value::String(path)) = resolved.get("path").cloned() {
+                    match resolve_path(&path) {
+                        Ok(path) => resolved.insert("path".to_owned(), Value::String(path)),
+                        Err(reason) => {
+                            dependencies.remove(&name);
+                            unresolvable.push((name, reason));
+                            continue;
+                        }
+                    };
+                }
+                if let Some(&Value::Table(ref spec)) = dependencies.get(&name) {
+                    for (key, value) in spec {
+                        match (key.as_str(), value) {
+                            ("workspace", _) => {}
+                            ("features", &Value::Array(ref added)) => {
+                                let mut features = match resolved.get("features") {
+                                    Some(&Value::Array(ref features)) => features.clone(),
+                                    _ => vec![],
+                                };
+                                features.extend(added.iter().cloned());
+                                resolved.insert(key.clone(), Value::Array(features));
+                            }
+                            _ => {
+                                resolved.insert(key.clone(), value.clone());
+                            }
+                        }
+                    }
+                }
+                dependencies.insert(name, Value::Table(resolved));
+            }
+        }
+        Ok(unresolvable)
+    }
+
+    /// Keep the features of `cargo-features` for which `supported` holds,
+    /// returning the others
+    fn retain_cargo_features(&mut self, supported: &Fn(&str) -> bool) -> Vec<String> {
+        let mut unsupported = vec![];
+        if let Some(&mut Value::Array(ref mut features)) = self.raw.get_mut("cargo-features") {
+            features.retain(|feature| match feature.as_str() {
+                Some(f) if !supported(f) => {
+                    unsupported.push(f.to_owned());
+                    false
+                }
+                _ => true,
+            });
+        }
+        let is_empty = match self.raw.get("cargo-features") {
+            Some(&Value::Array(ref features)) => features.is_empty(),
+            _ => false,
+        };
+        if is_empty {
+            self.remove("cargo-features");
+        }
+        unsupported
+    }
+
+    /// Rewrite `[features]` in the syntax of the cargo version linked in,
+    /// i.e. `dep:foo` becomes `foo` and `foo?/bar` becomes `foo/bar`
+    ///
+    /// Older versions reject features named like a dependency, which enabling
+    /// the optional dependency, an implicit feature there, stands in for. What
+    /// such a feature enables on top is folded into the features enabling it.
+    fn normalize_features(&mut self) {
+        let mut dependencies = HashSet::new();
+        for table in self.dependency_tables_mut() {
+            dependencies.extend(table.keys().cloned());
+        }
+        let features = match self.table_mut("features") {
+            Some(features) => features,
+            None => return,
+        };
+        let mut shadowing = HashMap::new();
+        for name in dependencies {
+            if let Some(Value::Array(enabled)) = features.remove(&name) {
+                shadowing.insert(name, enabled);
+            }
+        }
+        for enabled in features.values_mut() {
+            if let Value::Array(ref mut enabled) = *enabled {
+                let mut folded = vec![];
+                fold_features(enabled, &shadowing, &mut HashSet::new(), &mut folded);
+                enabled.clear();
+                for feature in folded {
+                    let feature = Value::String(feature);
+                    if !enabled.contains(&feature) {
+                        enabled.push(feature);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drop the entries of `[features]` enabling `dependency` or one of its
+    /// features, after the dependency itself was removed
+    fn remove_feature_references(&mut self, dependency: &str) {
+        let features = match self.table_mut("features") {
+            Some(features) => features,
+            None => return,
+        };
+        let prefix = format!("{}/", dependency);
+        for enabled in features.values_mut() {
+            if let Value::Array(ref mut enabled) = *enabled {
+                enabled.retain(|feature| match feature.as_str() {
+                    Some(f) => f != dependency && !f.starts_with(&prefix),
+                    None => true,
+                });
+            }
+        }
+    }
+
+    /// Dependency tables, including those of `[target]` tables
+    fn dependency_tables_mut(&mut self) -> Vec<&mut Table> {
+        dependency_tables(&mut self.raw, true)
+    }
+
+    /// Tables overriding dependencies, `[patch.<registry>]` and `[replace]`
+    fn override_tables_mut(&mut self) -> Vec<&mut Table> {
+        let mut tables = vec![];
+        for (key, value) in self.raw.iter_mut() {
+            match (key.as_str(), value) {
+                ("patch", &mut Value::Table(ref mut registries)) => {
+                    for registry in registries.values_mut() {
+                        if let Value::Table(ref mut patches) = *registry {
+                            tables.push(patches);
+                        }
+                    }
+                }
+                ("replace", &mut Value::Table(ref mut replacements)) => tables.push(replacements),
+                _ => {}
+            }
+        }
+        tables
+    }
+}
+
+/// The entries of a feature in the syntax of the cargo version linked in,
+/// followed by those of the features named like a dependency it enables,
+/// each of which is only folded in once
+fn fold_features(
+    enabled: &[Value],
+    shadowing: &HashMap<String, Vec<Value>>,
+    folded_in: &mut HashSet<String>,
+    folded: &mut Vec<String>,
+) {
+    for feature in enabled {
+        let feature = match feature.as_str() {
+            Some(feature) => feature,
+            None => continue,
+        };
+        folded.push(feature.trim_left_matches("dep:").replace("?/", "/"));
+        if let Some(shadowed) = shadowing.get(feature) {
+            if folded_in.insert(feature.to_owned()) {
+                fold_features(shadowed, shadowing, folded_in, folded);
+            }
+        }
+    }
+}
+
+/// Whether a value is `{ workspace = true }`
+fn is_inherited(value: &Value) -> bool {
+    value
+        .get("workspace")
+        .and_then(|w| w.as_bool())
+        .unwrap_or(false)
 }
 
-pub fn opt_tables_last<S>(data: &Option<Table>, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: ::serde::ser::Serializer,
-{
-    match *data {
-        Some(ref d) => ::toml::ser::tables_last(d, serializer),
-        None => unreachable!(),
+fn dependency_tables(table: &mut Table, with_targets: bool) -> Vec<&mut Table> {
+    let mut tables = vec![];
+    for (key, value) in table.iter_mut() {
+        if let Value::Table(ref mut inner) = *value {
+            if DEPENDENCY_KEYS.contains(&key.as_str()) {
+                tables.push(inner);
+            } else if with_targets && key.as_str() == "target" {
+                for target in inner.values_mut() {
+                    if let Value::Table(ref mut target) = *target {
+                        tables.extend(dependency_tables(target, false));
+                    }
+                }
+            }
+        }
     }
+    tables
 }
@@ -0,0 +1,77 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use cargo::core::SourceId;
+use cargo::util::{short_hash, CargoError, CargoErrorKind, CargoResult, Config};
+use toml::value::{Table, Value};
+
+/// Name of the source replacing crates.io with `--offline`
+const OFFLINE_SOURCE: &str = "cargo-outdated-offline";
+
+/// `[source]` settings replacing crates.io by a local registry under `root`
+/// made of the cached copy of its index and the crates downloaded so far,
+/// so that resolving the temporary project never touches the network
+///
+/// Fails if there is no cached copy of the index. Crates which were never
+/// downloaded make the resolution fail later on.
+pub fn replacement_sources(config: &Config, root: &Path) -> CargoResult<Table> {
+    let source_id = SourceId::crates_io(config)?;
+    // the directory name cargo keeps the data of the registry under
+    let name = format!(
+        "{}-{}",
+        source_id.url().host_str().unwrap_or(""),
+        short_hash(&source_id)
+    );
+    let index = config.registry_index_path().into_path_unlocked().join(&name);
+    if !index.is_dir() {
+        return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+            "--offline needs a local copy of the crates.io index, but there is none in {}, \
+             run cargo outdated once without it first",
+            index.display()
+        ))));
+    }
+    let registry = root.join(".cargo").join(OFFLINE_SOURCE);
+    fs::create_dir_all(&registry)?;
+    link_dir(&index, &registry.join("index"))?;
+    let cache = config.registry_cache_path().into_path_unlocked().join(&name);
+    if let Ok(crates) = fs::read_dir(cache) {
+        for krate in crates {
+            let krate = krate?.path();
+            if let Some(file_name) = krate.file_name() {
+                link_file(&krate, &registry.join(file_name))?;
+            }
+        }
+    }
+
+    let mut crates_io = Table::new();
+    crates_io.insert(
+        "replace-with".to_owned(),
+        Value::String(OFFLINE_SOURCE.to_owned()),
+    );
+    let mut offline = Table::new();
+    offline.insert(
+        "local-registry".to_owned(),
+        Value::String(registry.to_string_lossy().into_owned()),
+    );
+    let mut sources = Table::new();
+    sources.insert("crates-io".to_owned(), Value::Table(crates_io));
+    sources.insert(OFFLINE_SOURCE.to_owned(), Value::Table(offline));
+    Ok(sources)
+}
+
+#[cfg(unix)]
+fn link_dir(from: &Path, to: &Path) -> io::Result<()> { ::std::os::unix::fs::symlink(from, to) }
+
+#[cfg(windows)]
+fn link_dir(from: &Path, to: &Path) -> io::Result<()> {
+    ::std::os::windows::fs::symlink_dir(from, to)
+}
+
+#[cfg(unix)]
+fn link_file(from: &Path, to: &Path) -> io::Result<()> { ::std::os::unix::fs::symlink(from, to) }
+
+// symlinks need extra privileges on Windows, the crates are small enough
+// to be copied, unlike the index
+#[cfg(windows)]
+fn link_file(from: &Path, to: &Path) -> io::Result<()> { fs::copy(from, to).map(|_| ()) }
@@ -1,10 +1,14 @@
 use semver::Version;
 
 /// Enum which represents the update status of a package
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Status {
     Unchanged,
     Removed,
+    /// The package is not found in the registry index
+    Unknown,
+    /// The package is sourced from git, locked at the given revision
+    Git(String),
     Version(Version),
 }
 
@@ -27,6 +31,23 @@ impl Status {
             _ => true,
         }
     }
+
+    pub fn is_removed(&self) -> bool {
+        match *self {
+            Status::Removed => true,
+            _ => false,
+        }
+    }
+
+    /// Severity of the update from `from`, if there is a new version
+    pub fn severity(&self, from: &Version, zero_major_as_breaking: bool) -> Option<Severity> {
+        match *self {
+            Status::Version(ref to) => {
+                Some(Severity::from_versions(from, to, zero_major_as_breaking))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl ::std::string::ToString for Status {
@@ -34,13 +55,133 @@ impl ::std::string::ToString for Status {
         match *self {
             Status::Unchanged => "---".to_owned(),
             Status::Removed => "Removed".to_owned(),
+            Status::Unknown => "Unknown".to_owned(),
+            Status::Git(ref rev) => format!("git ({})", rev),
             Status::Version(ref v) => v.to_string(),
         }
     }
 }
 
+/// Whether `candidate` counts as a newer version of `current` when looking
+/// for the latest one
+///
+/// Pre-releases only count when the version in use is one too, as cargo
+/// never picks them otherwise, unless `pre_release` is set by
+/// `--pre-release`.
+pub fn is_candidate(candidate: &Version, current: &Version, pre_release: bool) -> bool {
+    !candidate.is_prerelease() || current.is_prerelease() || pre_release
+}
+
 #[derive(Debug)]
 pub struct PkgStatus {
     pub compat: Status,
     pub latest: Status,
 }
+
+impl PkgStatus {
+    /// Severity of the most significant update available
+    pub fn severity(&self, from: &Version, zero_major_as_breaking: bool) -> Option<Severity> {
+        self.latest
+            .severity(from, zero_major_as_breaking)
+            .or_else(|| self.compat.severity(from, zero_major_as_breaking))
+    }
+}
+
+/// SemVer classification of an update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Severity {
+    /// Classify the update from `from` to `to`
+    ///
+    /// When `zero_major_as_breaking` is set, versions below 1.0.0 are treated
+    /// the way cargo treats them, i.e. 0.4 -> 0.5 and 0.0.3 -> 0.0.4 are
+    /// incompatible and therefore major updates
+    pub fn from_versions(from: &Version, to: &Version, zero_major_as_breaking: bool) -> Severity {
+        if from.major != to.major {
+            Severity::Major
+        } else if zero_major_as_breaking && from.major == 0
+            && (from.minor != to.minor || from.minor == 0 && from.patch != to.patch)
+        {
+            Severity::Major
+        } else if from.minor != to.minor {
+            Severity::Minor
+        } else {
+            Severity::Patch
+        }
+    }
+}
+
+impl ::std::string::ToString for Severity {
+    fn to_string(&self) -> String {
+        match *self {
+            Severity::Patch => "Patch".to_owned(),
+            Severity::Minor => "Minor".to_owned(),
+            Severity::Major => "Major".to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use super::is_candidate;
+
+    const RELEASES: &[&str] = &[
+        "1.0.0",
+        "1.1.0",
+        "2.0.0-alpha.1",
+        "2.0.0-beta.1",
+        "2.0.0-beta.2",
+        "2.0.0",
+        "3.0.0-rc.1",
+    ];
+
+    /// The newest of `RELEASES` newer than `current` which counts as a
+    /// candidate, as the Latest column picks it
+    fn latest(current: &str, pre_release: bool) -> Option<String> {
+        let current = Version::parse(current).unwrap();
+        RELEASES
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .filter(|v| is_candidate(v, &current, pre_release) && *v > current)
+            .max()
+            .map(|v| v.to_string())
+    }
+
+    #[test]
+    fn stable_versions_skip_pre_releases() {
+        assert_eq!(latest("1.0.0", false), Some("2.0.0".to_owned()));
+        assert_eq!(latest("2.0.0", false), None);
+        let current = Version::parse("1.0.0").unwrap();
+        assert!(!is_candidate(&Version::parse("2.0.0-beta.1").unwrap(), &current, false));
+        assert!(is_candidate(&Version::parse("1.1.0").unwrap(), &current, false));
+    }
+
+    #[test]
+    fn pre_releases_move_on_to_newer_pre_releases() {
+        assert_eq!(latest("2.0.0-alpha.1", false), Some("3.0.0-rc.1".to_owned()));
+        let current = Version::parse("2.0.0-beta.1").unwrap();
+        assert!(is_candidate(&Version::parse("2.0.0-beta.2").unwrap(), &current, false));
+    }
+
+    #[test]
+    fn pre_releases_move_on_to_stable_ones() {
+        let current = Version::parse("2.0.0-beta.2").unwrap();
+        assert!(is_candidate(&Version::parse("2.0.0").unwrap(), &current, false));
+        assert!(Version::parse("2.0.0").unwrap() > current);
+    }
+
+    #[test]
+    fn pre_release_flag_considers_every_pre_release() {
+        assert_eq!(latest("1.0.0", true), Some("3.0.0-rc.1".to_owned()));
+        assert_eq!(latest("2.0.0", true), Some("3.0.0-rc.1".to_owned()));
+        let current = Version::parse("1.0.0").unwrap();
+        assert!(is_candidate(&Version::parse("2.0.0-alpha.1").unwrap(), &current, true));
+    }
+}
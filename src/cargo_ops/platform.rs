@@ -0,0 +1,46 @@
+use std::str::FromStr;
+
+use cargo::core::Dependency;
+use cargo::util::{Cfg, CargoResult, Config};
+
+/// The platform dependencies are evaluated for
+pub struct TargetInfo {
+    triple: String,
+    cfgs: Vec<Cfg>,
+}
+
+impl TargetInfo {
+    /// The platform `rustc` compiles for by default
+    pub fn host(config: &Config) -> CargoResult<TargetInfo> {
+        let host = config.rustc()?.host.clone();
+        TargetInfo::target(config, &host)
+    }
+
+    /// A platform given by its target triple, with `--target`
+    pub fn target(config: &Config, triple: &str) -> CargoResult<TargetInfo> {
+        let output = config
+            .rustc()?
+            .process()
+            .arg("--print=cfg")
+            .arg("--target")
+            .arg(triple)
+            .exec_with_output()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let cfgs = stdout
+            .lines()
+            .map(Cfg::from_str)
+            .collect::<CargoResult<Vec<_>>>()?;
+        Ok(TargetInfo {
+            triple: triple.to_owned(),
+            cfgs: cfgs,
+        })
+    }
+
+    /// Whether a dependency applies to this platform
+    pub fn matches(&self, dependency: &Dependency) -> bool {
+        dependency
+            .platform()
+            .map(|p| p.matches(&self.triple, Some(&self.cfgs)))
+            .unwrap_or(true)
+    }
+}
@@ -0,0 +1,68 @@
+use semver::Version;
+
+use super::pkg_status::Severity;
+use super::project_config::ProjectConfig;
+use super::report::Line;
+
+/// Which updates of a dependency a project is willing to take
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Policy {
+    Any,
+    MinorOnly,
+    PatchOnly,
+}
+
+impl Policy {
+    pub fn allows(&self, severity: Severity) -> bool {
+        match *self {
+            Policy::Any => true,
+            Policy::MinorOnly => severity <= Severity::Minor,
+            Policy::PatchOnly => severity == Severity::Patch,
+        }
+    }
+}
+
+impl ::std::string::ToString for Policy {
+    fn to_string(&self) -> String {
+        match *self {
+            Policy::Any => "any".to_owned(),
+            Policy::MinorOnly => "minor-only".to_owned(),
+            Policy::PatchOnly => "patch-only".to_owned(),
+        }
+    }
+}
+
+/// Mark a row whose most significant update goes beyond the policy
+/// configured for the crate, along with the newest update the policy allows,
+/// if any
+pub fn annotate_policy(
+    project_config: &ProjectConfig,
+    zero_major_as_breaking: bool,
+    line: &mut Line,
+) {
+    let policy = match project_config.policy.get(line.crate_name()) {
+        Some(policy) => *policy,
+        None => return,
+    };
+    let current = match Version::parse(&line.project) {
+        Ok(current) => current,
+        Err(_) => return,
+    };
+    // `None` for cells without a newer version
+    let allowed = |candidate: &str| match Version::parse(candidate) {
+        Ok(ref candidate) if *candidate > current => Some(policy.allows(
+            Severity::from_versions(&current, candidate, zero_major_as_breaking),
+        )),
+        _ => None,
+    };
+    if allowed(&line.latest) != Some(false) {
+        return;
+    }
+    line.blocked_by = Some(policy.to_string());
+    line.allowed = if allowed(&line.compat) == Some(true) {
+        Some(line.compat.clone())
+    } else {
+        None
+    };
+}
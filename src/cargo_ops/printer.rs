@@ -0,0 +1,181 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::process;
+
+use cargo::util::{CargoError, CargoErrorKind, CargoResult};
+
+use super::report::{self, Report};
+
+/// A way of printing the reports of a run, one per `--format`
+///
+/// Tools embedding the analysis can implement it for formats of their
+/// own, and print what `analyze_workspace` returns with it.
+pub trait Printer {
+    /// Print `reports` to `out`, those of every member if `workspace_mode`
+    fn print(&self, out: &mut Write, reports: &[Report], workspace_mode: bool) -> CargoResult<()>;
+}
+
+/// The table of `--format list`, with its notes
+pub struct ListPrinter {
+    /// Whether to add the notes only shown with `--verbose`
+    pub verbose: bool,
+    /// Whether to color the `Latest` column by severity
+    pub color: bool,
+}
+
+impl Printer for ListPrinter {
+    fn print(&self, out: &mut Write, reports: &[Report], workspace_mode: bool) -> CargoResult<()> {
+        report::print_list(out, reports, workspace_mode, self.verbose, self.color)
+    }
+}
+
+/// The rows as tab-separated values, with `--no-table`
+pub struct TsvPrinter;
+
+impl Printer for TsvPrinter {
+    fn print(&self, out: &mut Write, reports: &[Report], workspace_mode: bool) -> CargoResult<()> {
+        report::print_tsv(out, reports, workspace_mode)
+    }
+}
+
+/// The numbers of dependencies up to date and behind, with `--summary`
+pub struct CountsPrinter;
+
+impl Printer for CountsPrinter {
+    fn print(&self, out: &mut Write, reports: &[Report], _: bool) -> CargoResult<()> {
+        report::print_counts(out, reports)
+    }
+}
+
+/// A JSON array of the reports, `--format json`
+pub struct JsonPrinter;
+
+impl Printer for JsonPrinter {
+    fn print(&self, out: &mut Write, reports: &[Report], _: bool) -> CargoResult<()> {
+        report::print_json(out, reports)
+    }
+}
+
+/// A JSON object per row, `--format jsonl`
+pub struct JsonlPrinter;
+
+impl Printer for JsonlPrinter {
+    fn print(&self, out: &mut Write, reports: &[Report], _: bool) -> CargoResult<()> {
+        for report in reports {
+            report.print_jsonl(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// The stable format for scripts, `--format porcelain`
+pub struct PorcelainPrinter;
+
+impl Printer for PorcelainPrinter {
+    fn print(&self, out: &mut Write, reports: &[Report], _: bool) -> CargoResult<()> {
+        report::print_porcelain(out, reports)
+    }
+}
+
+/// Markdown tables, `--format markdown`
+pub struct MarkdownPrinter;
+
+impl Printer for MarkdownPrinter {
+    fn print(&self, out: &mut Write, reports: &[Report], workspace_mode: bool) -> CargoResult<()> {
+        report::print_markdown(out, reports, workspace_mode)
+    }
+}
+
+/// An HTML fragment, `--format html`
+pub struct HtmlPrinter;
+
+impl Printer for HtmlPrinter {
+    fn print(&self, out: &mut Write, reports: &[Report], workspace_mode: bool) -> CargoResult<()> {
+        report::print_html(out, reports, workspace_mode)
+    }
+}
+
+/// A shields.io endpoint badge, `--format badge`
+pub struct BadgePrinter;
+
+impl Printer for BadgePrinter {
+    fn print(&self, out: &mut Write, reports: &[Report], _: bool) -> CargoResult<()> {
+        report::print_badge(out, reports)
+    }
+}
+
+/// Write what `print` prints to `path`, by way of a temporary file next to
+/// it which replaces it once complete, so that readers like the upload of
+/// a CI artifact never see a truncated file
+pub fn write_atomically(
+    path: &Path,
+    print: &mut FnMut(&mut Write) -> CargoResult<()>,
+) -> CargoResult<()> {
+    let file_name = match path.file_name() {
+        Some(file_name) => file_name.to_string_lossy().into_owned(),
+        None => {
+            return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+                "{} is not a file to write the report to",
+                path.display()
+            ))))
+        }
+    };
+    let temp = path.with_file_name(format!(".{}.{}.tmp", file_name, process::id()));
+    let written = File::create(&temp)
+        .map_err(CargoError::from)
+        .and_then(|file| {
+            let mut out = BufWriter::new(file);
+            print(&mut out)?;
+            out.flush()?;
+            out.get_ref().sync_all()?;
+            Ok(())
+        })
+        .and_then(|_| Ok(fs::rename(&temp, path)?));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+
+    use super::{PorcelainPrinter, Printer, Report};
+
+    /// Reports as `--format json` prints them, and the porcelain of them
+    const REPORTS: &str = include_str!("../../tests/fixtures/reports/reports.json");
+    const PORCELAIN: &str = include_str!("../../tests/fixtures/reports/porcelain.txt");
+
+    fn porcelain(reports: &[Report], workspace_mode: bool) -> String {
+        let mut out = vec![];
+        PorcelainPrinter
+            .print(&mut out, reports, workspace_mode)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn porcelain_matches_the_golden_output() {
+        let reports: Vec<Report> = serde_json::from_str(REPORTS).unwrap();
+        // checkouts may have turned the line endings into CRLF
+        let expected = PORCELAIN.replace("\r\n", "\n");
+        assert_eq!(porcelain(&reports, false), expected);
+        assert_eq!(porcelain(&reports, true), expected);
+    }
+
+    #[test]
+    fn porcelain_of_nothing_is_the_header() {
+        assert_eq!(porcelain(&[], false), "# cargo-outdated porcelain v1\n");
+    }
+
+    #[test]
+    fn porcelain_has_as_many_fields_on_every_line() {
+        let reports: Vec<Report> = serde_json::from_str(REPORTS).unwrap();
+        for line in porcelain(&reports, false).lines().skip(1) {
+            assert_eq!(line.split('\t').count(), 9, "{}", line);
+            assert!(line.split('\t').all(|field| !field.is_empty()), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use cargo::util::{CargoError, CargoErrorKind, CargoResult};
+use toml::Value;
+
+use super::policy::Policy;
+
+/// Name of the configuration file looked up in the workspace root
+pub const CONFIG_FILE: &str = ".cargo-outdated.toml";
+
+/// Settings of a single dependency
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct DependencyConfig {
+    /// Why the dependency is held back, shown along with its row
+    pub reason: Option<String>,
+}
+
+/// Defaults for command line options, which take precedence over them
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Defaults {
+    /// Added to those given with `--ignore`
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    pub depth: Option<i32>,
+    pub root_deps_only: Option<bool>,
+    pub exit_code: Option<i32>,
+}
+
+/// Configuration committed to a project, e.g.
+///
+/// ```toml
+/// [dependencies.openssl]
+/// reason = "Pinned until the TLS migration lands"
+///
+/// [policy]
+/// serde = "minor-only"
+///
+/// [defaults]
+/// exit-code = 1
+/// ```
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct ProjectConfig {
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, DependencyConfig>,
+    /// Updates allowed per crate, the others are reported as blocked
+    #[serde(default)]
+    pub policy: BTreeMap<String, Policy>,
+    #[serde(default)]
+    pub defaults: Defaults,
+}
+
+impl ProjectConfig {
+    /// Load the configuration file of the workspace rooted at `dir`, the
+    /// `[package.metadata.outdated]` or `[workspace.metadata.outdated]`
+    /// table of its manifest if there is none, and the default
+    /// configuration if there is neither
+    pub fn load(dir: &Path) -> CargoResult<ProjectConfig> {
+        let path = dir.join(CONFIG_FILE);
+        let mut contents = String::new();
+        match File::open(&path) {
+            Ok(mut file) => file.read_to_string(&mut contents)?,
+            Err(_) => return ProjectConfig::load_metadata(&dir.join("Cargo.toml")),
+        };
+        ::toml::from_str(&contents).map_err(|e| parse_error(&path, e))
+    }
+
+    fn load_metadata(manifest_path: &Path) -> CargoResult<ProjectConfig> {
+        let mut contents = String::new();
+        match File::open(manifest_path) {
+            Ok(mut file) => file.read_to_string(&mut contents)?,
+            Err(_) => return Ok(ProjectConfig::default()),
+        };
+        let manifest: Value = ::toml::from_str(&contents)?;
+        let metadata = ["package", "workspace"]
+            .iter()
+            .filter_map(|section| manifest.get(section))
+            .filter_map(|section| section.get("metadata"))
+            .filter_map(|metadata| metadata.get("outdated"))
+            .next();
+        match metadata {
+            Some(metadata) => metadata
+                .clone()
+                .try_into()
+                .map_err(|e| parse_error(manifest_path, e)),
+            None => Ok(ProjectConfig::default()),
+        }
+    }
+
+    /// The reason given for holding back a dependency
+    pub fn reason(&self, name: &str) -> Option<&str> {
+        self.dependencies
+            .get(name)
+            .and_then(|dep| dep.reason.as_ref())
+            .map(|reason| reason.as_str())
+    }
+}
+
+fn parse_error(path: &Path, e: ::toml::de::Error) -> CargoError {
+    CargoError::from_kind(CargoErrorKind::Msg(
+        format!("Failed to parse {}: {}", path.display(), e),
+    ))
+}
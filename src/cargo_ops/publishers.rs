@@ -0,0 +1,44 @@
+use cargo::util::CargoResult;
+use semver::Version;
+
+use super::crates_io::CratesIo;
+use super::report::Line;
+
+/// Mark rows whose latest version was published by an account which did
+/// not publish the version in use or any earlier one, which is a cheap
+/// hint of a crate changing hands
+pub fn annotate_publishers(crates_io: &mut CratesIo, lines: &mut [Line]) -> CargoResult<()> {
+    for line in lines.iter_mut() {
+        let current = Version::parse(&line.project);
+        let (current, latest) = match (current, Version::parse(&line.latest)) {
+            (Ok(current), Ok(latest)) => (current, latest),
+            _ => continue,
+        };
+        let name = line.crate_name().to_owned();
+        let metadata = match crates_io.crate_metadata(&name)? {
+            Some(metadata) => metadata,
+            None => continue,
+        };
+        let publisher = metadata
+            .version(&latest)
+            .and_then(|version| version.published_by.as_ref());
+        let previous = metadata.publishers_until(&current);
+        // nothing to compare with for crates older than the publisher records
+        line.new_publisher = match publisher {
+            Some(user) if !previous.is_empty() && !previous.contains(user.login.as_str()) => {
+                Some(user.login.clone())
+            }
+            _ => None,
+        };
+    }
+    Ok(())
+}
+
+/// Fill in the crates.io owners of every row
+pub fn annotate_owners(crates_io: &mut CratesIo, lines: &mut [Line]) -> CargoResult<()> {
+    for line in lines.iter_mut() {
+        let name = line.crate_name().to_owned();
+        line.owners = Some(crates_io.owners(&name)?.to_vec());
+    }
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::fs;
+use std::time::{Duration, SystemTime};
+
+use cargo::core::{Dependency, Registry, Source, SourceId, Summary};
+use cargo::util::{CargoError, CargoErrorKind, CargoResult, Config, ToUrl};
+use semver::Version;
+
+use super::pkg_status::is_candidate;
+use super::report::Line;
+
+/// A lazily updated view of a registry index
+///
+/// Queried versions are cached so that looking up the same crate
+/// from several manifests only hits the index once
+pub struct RegistryIndex<'cfg> {
+    source: Box<Source + 'cfg>,
+    source_id: SourceId,
+    /// Whether the index was updated, or may not be as the network is off
+    updated: bool,
+    /// Whether the copy on disk is recent, so that the index is only
+    /// updated for crates it doesn't know
+    recent: bool,
+    summaries: HashMap<String, Vec<Summary>>,
+    versions: HashMap<String, Vec<Version>>,
+    yanked: HashMap<String, Vec<Version>>,
+}
+
+impl<'cfg> RegistryIndex<'cfg> {
+    /// Open the crates.io index
+    pub fn crates_io(config: &'cfg Config) -> CargoResult<RegistryIndex<'cfg>> {
+        let source_id = SourceId::crates_io(config)?;
+        Ok(RegistryIndex {
+            source: source_id.load(config),
+            source_id: source_id,
+            updated: !config.network_allowed(),
+            recent: false,
+            summaries: HashMap::new(),
+            versions: HashMap::new(),
+            yanked: HashMap::new(),
+        })
+    }
+
+    /// Open the crates.io index which the resolution of the temporary
+    /// projects updated shortly before, without updating it again unless a
+    /// crate is missing from it
+    pub fn crates_io_updated(config: &'cfg Config) -> CargoResult<RegistryIndex<'cfg>> {
+        let mut index = RegistryIndex::crates_io(config)?;
+        index.recent = true;
+        Ok(index)
+    }
+
+    /// Open the index of an alternative registry, configured as
+    /// `registries.<name>.index`
+    pub fn alternative(config: &'cfg Config, name: &str) -> CargoResult<RegistryIndex<'cfg>> {
+        let url = match config.get_string(&format!("registries.{}.index", name))? {
+            Some(url) => url.val.to_url()?,
+            None => {
+                return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+                    "registry `{}` is not configured, `registries.{}.index` is missing",
+                    name, name
+                ))))
+            }
+        };
+        let source_id = SourceId::for_registry(&url);
+        Ok(RegistryIndex {
+            source: source_id.load(config),
+            source_id: source_id,
+            updated: !config.network_allowed(),
+            recent: false,
+            summaries: HashMap::new(),
+            versions: HashMap::new(),
+            yanked: HashMap::new(),
+        })
+    }
+
+    /// Published versions of a crate in ascending order, leaving out the
+    /// yanked ones, empty if the crate is not in the index
+    pub fn versions(&mut self, name: &str) -> CargoResult<&[Version]> {
+        if !self.versions.contains_key(name) {
+            let versions = self.summaries(name)?
+                .iter()
+                .map(|summary| summary.version().clone())
+                .collect();
+            self.versions.insert(name.to_owned(), versions);
+        }
+        Ok(&self.versions[name])
+    }
+
+    /// Index entries of the published versions of a crate, including their
+    /// dependencies, in ascending order of version
+    pub fn summaries(&mut self, name: &str) -> CargoResult<&[Summary]> {
+        if !self.summaries.contains_key(name) {
+            if !self.updated && !self.recent {
+                self.update()?;
+            }
+            let mut summaries = self.query(name)?;
+            // published since the copy on disk was updated, or never
+            if summaries.is_empty() && !self.updated {
+                self.update()?;
+                summaries = self.query(name)?;
+            }
+            summaries.sort_by(|a, b| a.version().cmp(b.version()));
+            self.summaries.insert(name.to_owned(), summaries);
+        }
+        Ok(&self.summaries[name])
+    }
+
+    fn update(&mut self) -> CargoResult<()> {
+        self.source.update()?;
+        self.updated = true;
+        Ok(())
+    }
+
+    /// Index entries of a crate as the copy on disk has them
+    fn query(&mut self, name: &str) -> CargoResult<Vec<Summary>> {
+        let dep = Dependency::parse_no_deprecated(name, None, &self.source_id)?;
+        let mut summaries = vec![];
+        self.source.query(&dep, &mut |summary| summaries.push(summary))?;
+        Ok(summaries)
+    }
+
+    /// Yanked versions of a crate in ascending order
+    ///
+    /// Sources leave yanked versions out of queries unless the dependency
+    /// is locked, so the index is queried once more as if it was.
+    pub fn yanked(&mut self, name: &str) -> CargoResult<&[Version]> {
+        if !self.yanked.contains_key(name) {
+            let published = self.versions(name)?.to_vec();
+            let locked = self.source_id.with_precise(Some("locked".to_owned()));
+            let dep = Dependency::parse_no_deprecated(name, None, &locked)?;
+            let mut yanked = vec![];
+            self.source.query(&dep, &mut |summary| {
+                if !published.contains(summary.version()) {
+                    yanked.push(summary.version().clone());
+                }
+            })?;
+            yanked.sort();
+            self.yanked.insert(name.to_owned(), yanked);
+        }
+        Ok(&self.yanked[name])
+    }
+
+    /// Whether a crate with this name has been published
+    pub fn contains(&mut self, name: &str) -> CargoResult<bool> {
+        Ok(!self.versions(name)?.is_empty())
+    }
+}
+
+/// Fill in the newest release within the major series of the version in
+/// use of every row, treating `0.x` as a series of its own when
+/// `zero_major_as_breaking` and considering pre-releases if `pre_release`
+pub fn annotate_series(
+    index: &mut RegistryIndex,
+    zero_major_as_breaking: bool,
+    pre_release: bool,
+    lines: &mut [Line],
+) -> CargoResult<()> {
+    for line in lines.iter_mut() {
+        let current = match Version::parse(&line.project) {
+            Ok(current) => current,
+            Err(_) => continue,
+        };
+        let newest = index
+            .versions(line.crate_name())?
+            .iter()
+            .filter(|v| is_candidate(v, &current, pre_release))
+            .filter(|v| {
+                v.major == current.major
+                    && (!zero_major_as_breaking || current.major > 0 || v.minor == current.minor)
+            })
+            .last()
+            .cloned();
+        line.latest_in_series = Some(match newest {
+            Some(ref newest) if *newest > current => newest.to_string(),
+            _ => "---".to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Time since the local copy of a registry index was last fetched,
+/// the most recently fetched one if there are several
+pub fn index_age(config: &Config) -> Option<Duration> {
+    let indexes = config.home().as_path_unlocked().join("registry").join("index");
+    let mut newest = None;
+    for index in fs::read_dir(indexes).ok()? {
+        let index = match index {
+            Ok(index) => index.path(),
+            Err(_) => continue,
+        };
+        // newer cargo versions leave a marker, older ones only the git fetch
+        for marker in &[".last-updated", ".git/FETCH_HEAD"] {
+            if let Ok(modified) = fs::metadata(index.join(marker)).and_then(|m| m.modified()) {
+                if newest.map(|newest| modified > newest).unwrap_or(true) {
+                    newest = Some(modified);
+                }
+            }
+        }
+    }
+    SystemTime::now().duration_since(newest?).ok()
+}
+
+/// Seconds since the registry index was fetched, if the run may not
+/// access the network and thus works with whatever copy there is
+pub fn stale_index_secs(config: &Config) -> Option<u64> {
+    if config.network_allowed() {
+        None
+    } else {
+        index_age(config).map(|age| age.as_secs())
+    }
+}
+
+/// Round an age to a human readable number of days, hours or minutes
+pub fn describe_age(age: &Duration) -> String {
+    let secs = age.as_secs();
+    let (count, unit) = if secs >= 86_400 {
+        (secs / 86_400, "day")
+    } else if secs >= 3_600 {
+        (secs / 3_600, "hour")
+    } else {
+        (secs / 60, "minute")
+    };
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
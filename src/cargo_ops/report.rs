@@ -0,0 +1,1071 @@
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use atty;
+use cargo::core::shell::ColorChoice;
+use cargo::util::{CargoResult, Config};
+use serde_json;
+use tabwriter::TabWriter;
+
+use super::registry::describe_age;
+
+/// A dependency row of the report
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Line {
+    /// Name of the dependency, prefixed with `parent->` for
+    /// dependencies of a non-workspace root
+    pub name: String,
+    pub project: String,
+    pub compat: String,
+    pub latest: String,
+    pub kind: Option<String>,
+    pub platform: Option<String>,
+    pub severity: Option<String>,
+    /// Recommended replacement if the dependency is deprecated
+    pub successor: Option<String>,
+    /// `rust-version` of the compatible version, if newer than the project's
+    pub compat_rust_version: Option<String>,
+    /// `rust-version` of the latest version, if newer than the project's
+    pub latest_rust_version: Option<String>,
+    /// Newest release, if newer than the latest version and held back by
+    /// `--rust-version` as it needs a newer Rust than the project
+    pub newest_release: Option<String>,
+    /// `rust-version` of the newest release, when held back
+    pub newest_rust_version: Option<String>,
+    /// Why the dependency is `Removed` from the compat or latest resolution
+    pub removed_reason: Option<String>,
+    /// Why the dependency is held back, from the project configuration
+    pub reason: Option<String>,
+    /// License of the latest version, if denied by `--deny-license` or
+    /// not allowed by `--allow-license`
+    pub denied_license: Option<String>,
+    /// Account which published the latest version without having published
+    /// the version in use or any earlier one, with `--check-publishers`
+    pub new_publisher: Option<String>,
+    /// Users and teams owning the crate on crates.io, with `--owners`
+    pub owners: Option<Vec<String>>,
+    /// Security advisories affecting the version in use which upgrading fixes
+    pub fixed_advisories: Option<Vec<String>>,
+    /// Public items of the API removed or changed in kind by a major upgrade
+    /// of a direct dependency, with `--api-diff`
+    pub breaking_changes: Option<usize>,
+    /// Rough estimate of the work the upgrade takes, with `--effort`
+    pub effort: Option<u32>,
+    /// Artifacts of an artifact dependency, like `bin` or `cdylib`
+    pub artifact: Option<String>,
+    /// Policy of the project configuration the latest version goes beyond
+    pub blocked_by: Option<String>,
+    /// Newest version the policy allows upgrading to, when blocked
+    pub allowed: Option<String>,
+    /// Newest release of the major series in use, with `--series`,
+    /// `---` if there is none newer
+    pub latest_in_series: Option<String>,
+    /// Whether the dependency is only reachable through dev-dependencies,
+    /// and thus never shipped
+    #[serde(default)]
+    pub dev_only: bool,
+    /// Whether the requirement is inherited from the workspace root's
+    /// `[workspace.dependencies]`, and thus declared there
+    #[serde(default)]
+    pub inherited: bool,
+    /// Whether the crate is overridden in the root's `[patch]` or `[replace]`
+    #[serde(default)]
+    pub patched: bool,
+    /// Whether the version in use was yanked from the registry
+    #[serde(default)]
+    pub yanked: bool,
+    /// Edition of the latest version, if newer than any in the graph,
+    /// with `--edition-gap`
+    pub latest_edition: Option<String>,
+    /// Days since the version in use was released, with `--show-dates`
+    pub project_age_days: Option<u64>,
+    /// Date the latest version was released on, with `--show-dates`
+    pub latest_released: Option<String>,
+    /// Security advisories affecting the version in use, whether upgrading
+    /// fixes them or not, with `--vulnerabilities`
+    pub advisories: Option<Vec<String>>,
+    /// Chain of packages from the root to the dependency, with `--why`
+    pub path: Option<Vec<String>>,
+    /// Number of versions of the crate in the dependency graph, if several
+    pub duplicates: Option<usize>,
+    /// Workspace members pulling in this version, if there are several
+    pub pulled_in_by: Option<Vec<String>>,
+    /// Repository, or else homepage, of the crate, with `--show-links`
+    pub link: Option<String>,
+    /// URL comparing the version in use and the latest one on GitHub, with
+    /// `--show-links`
+    pub compare_url: Option<String>,
+}
+
+impl Line {
+    /// A row with the versions of the dependency and no annotations
+    pub fn new(name: String, project: String, compat: String, latest: String) -> Line {
+        Line {
+            name: name,
+            project: project,
+            compat: compat,
+            latest: latest,
+            ..Default::default()
+        }
+    }
+
+    /// Name of the dependency without the `parent->` prefix
+    pub fn crate_name(&self) -> &str { self.name.rsplit("->").next().unwrap_or(&self.name) }
+
+    /// Whether the severity of the update is one of `severities`, given in
+    /// lower case, which is always the case if there are none, `yanked`
+    /// standing for the version in use being yanked
+    pub fn has_severity(&self, severities: &[String]) -> bool {
+        severities.is_empty()
+            || (self.yanked && severities.iter().any(|s| s == "yanked"))
+            || self.severity
+                .as_ref()
+                .map(|s| severities.contains(&s.to_lowercase()))
+                .unwrap_or(false)
+    }
+
+    /// Whether the compatible or the latest version is an upgrade, unlike in
+    /// rows listed for a yanked or duplicated version in use only
+    ///
+    /// The compatible version of a git dependency, `git (<rev>)`, is the
+    /// locked revision rather than an upgrade.
+    pub fn has_upgrade(&self) -> bool {
+        [&self.compat, &self.latest]
+            .iter()
+            .any(|version| match version.as_str() {
+                "---" | "Removed" | "Unknown" => false,
+                version if version.starts_with("git (") => false,
+                version => version != self.project,
+            })
+    }
+
+    /// Whether every upgrade goes beyond the policy of the project
+    /// configuration
+    pub fn is_blocked(&self) -> bool { self.blocked_by.is_some() && self.allowed.is_none() }
+
+    /// Whether there is an upgrade the project is willing to take
+    pub fn is_actionable(&self) -> bool { self.has_upgrade() && !self.is_blocked() }
+}
+
+/// Crates an upgrade of a direct dependency adds to or removes from the graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphChange {
+    pub name: String,
+    /// The version upgraded to
+    pub version: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Outdated dependencies of a single root package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub crate_name: String,
+    /// Path of the project relative to the current one, for reports of
+    /// projects besides the current workspace, e.g. in submodules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    pub dependencies: Vec<Line>,
+    /// Filled with `--graph-changes`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub graph_changes: Vec<GraphChange>,
+    /// Seconds since the registry index was last fetched, when the report
+    /// is based on a local copy which was not updated during the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_age_secs: Option<u64>,
+    /// Whether the latest versions could not be looked up, with
+    /// `--allow-network-failure`
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub latest_unavailable: bool,
+    /// Number of dependencies checked which are up to date, and thus have
+    /// no row
+    #[serde(default)]
+    pub up_to_date: usize,
+    /// Problems which left a member or dependencies out of the report,
+    /// rather than failing the run without `--strict`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+fn is_false(value: &bool) -> bool { !*value }
+
+impl Report {
+    /// Load reports previously written with `--format json`
+    pub fn load<P: AsRef<Path>>(path: P) -> CargoResult<Vec<Report>> {
+        Ok(serde_json::from_reader(File::open(path)?)?)
+    }
+
+    /// Save reports in the format of `--format json`, e.g. as the baseline
+    /// of `--compare-baseline`
+    pub fn save<P: AsRef<Path>>(reports: &[Report], path: P) -> CargoResult<()> {
+        let mut file = File::create(path)?;
+        serde_json::to_writer_pretty(&mut file, reports)?;
+        writeln!(file)?;
+        Ok(())
+    }
+
+    /// Name of the root package, along with the project it belongs to
+    pub fn title(&self) -> String {
+        match self.project {
+            Some(ref project) => format!("{} ({})", self.crate_name, project),
+            None => self.crate_name.clone(),
+        }
+    }
+
+    /// Number of outdated dependencies whose upgrade fixes an advisory
+    pub fn security_fixes(reports: &[Report]) -> i32 {
+        reports
+            .iter()
+            .flat_map(|report| report.dependencies.iter())
+            .filter(|line| line.fixed_advisories.is_some())
+            .count() as i32
+    }
+
+    /// Number of upgrades pulling in a disallowed license
+    pub fn denied_licenses(reports: &[Report]) -> usize {
+        reports
+            .iter()
+            .flat_map(|report| report.dependencies.iter())
+            .filter(|line| line.denied_license.is_some())
+            .count()
+    }
+
+    /// The age of the oldest index data any of the reports is based on
+    pub fn index_age(reports: &[Report]) -> Option<Duration> {
+        reports
+            .iter()
+            .filter_map(|report| report.index_age_secs)
+            .max()
+            .map(Duration::from_secs)
+    }
+
+    /// Number of outdated dependencies in all reports, those with an upgrade
+    /// which is not blocked by policy
+    ///
+    /// Rows listed for a yanked version in use without anything newer, or
+    /// only removed from the latest resolution, don't count, so that this is
+    /// what the summary adds up per severity.
+    pub fn count(reports: &[Report]) -> i32 {
+        let (_, counts) = severity_counts(reports);
+        counts.iter().sum::<usize>() as i32
+    }
+
+    /// Number of rows in all reports, whatever they are listed for
+    pub fn rows(reports: &[Report]) -> i32 {
+        reports.iter().map(|report| report.dependencies.len()).sum::<usize>() as i32
+    }
+}
+
+/// A dependency row along with the report it belongs to, a line of
+/// `--format jsonl`
+#[derive(Serialize)]
+struct JsonlLine<'a> {
+    crate_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project: Option<&'a str>,
+    dependency: &'a Line,
+}
+
+impl Report {
+    /// Print every row as a JSON object of its own line, flushing after
+    /// each one for consumers reading along
+    pub fn print_jsonl(&self, out: &mut Write) -> CargoResult<()> {
+        for line in &self.dependencies {
+            let line = JsonlLine {
+                crate_name: &self.crate_name,
+                project: self.project.as_ref().map(|p| p.as_str()),
+                dependency: line,
+            };
+            serde_json::to_writer(&mut *out, &line)?;
+            writeln!(out)?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Resets the color set by `severity_color`
+const RESET: &str = "\x1b[0m";
+
+/// The escape sequence coloring a cell by the severity of its row, red for
+/// major updates, yellow for minor and green for patch ones
+///
+/// All are as long, and other cells get one changing nothing, as
+/// `TabWriter` counts them as part of the cells when aligning the columns.
+fn severity_color(severity: Option<&String>) -> &'static str {
+    match severity.map(|s| s.as_str()) {
+        Some("Major") => "\x1b[31m",
+        Some("Minor") => "\x1b[33m",
+        Some("Patch") => "\x1b[32m",
+        _ => "\x1b[39m",
+    }
+}
+
+/// Whether the table gets colors, as `--color` says, and otherwise if
+/// stdout is a terminal and `NO_COLOR` is not set
+pub fn colors_stdout(config: &Config) -> bool {
+    match config.shell().color_choice() {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::CargoAuto => {
+            env::var_os("NO_COLOR").map(|v| v.is_empty()).unwrap_or(true)
+                && atty::is(atty::Stream::Stdout)
+        }
+    }
+}
+
+fn or_dashes(value: &Option<String>) -> &str {
+    value.as_ref().map(|v| v.as_str()).unwrap_or("---")
+}
+
+/// The name of a dependency, along with the number of its versions in the
+/// graph if there are several
+fn name_cell(line: &Line) -> String {
+    match line.duplicates {
+        Some(count) => format!("{} ({} versions)", line.name, count),
+        None => line.name.clone(),
+    }
+}
+
+/// The version in use, marked if the crate is overridden or the version
+/// was yanked
+fn project_cell(line: &Line) -> String {
+    let mut project = line.project.clone();
+    if line.patched {
+        project.push_str(" (patched)");
+    }
+    if line.yanked {
+        project.push_str(" (yanked)");
+    }
+    project
+}
+
+/// The kind of a dependency, along with its artifacts if it is an
+/// artifact dependency, marked if it is only needed by dev-dependencies or
+/// its requirement is inherited from the workspace
+fn kind_cell(line: &Line) -> String {
+    let mut kind = match line.artifact {
+        Some(ref artifact) => format!("{} (artifact {})", or_dashes(&line.kind), artifact),
+        None => or_dashes(&line.kind).to_owned(),
+    };
+    // direct dev-dependencies are obviously dev-only already
+    if line.dev_only && line.kind.as_ref().map(|k| k.as_str()) != Some("Development") {
+        kind.push_str(" (dev-only)");
+    }
+    if line.inherited {
+        kind.push_str(" (workspace)");
+    }
+    kind
+}
+
+/// A candidate version, marked if it needs a newer Rust than the project
+fn with_rust_version(version: &str, rust_version: &Option<String>) -> String {
+    match *rust_version {
+        Some(ref rust_version) => format!("{} (needs Rust {})", version, rust_version),
+        None => version.to_owned(),
+    }
+}
+
+/// The latest version, marked if it needs a newer Rust than the project
+/// or an edition newer than any in the graph, or if a newer release was
+/// held back by `--rust-version`
+fn latest_cell(line: &Line) -> String {
+    let mut latest = with_rust_version(&line.latest, &line.latest_rust_version);
+    if let (&Some(ref newest), &Some(ref rust_version)) =
+        (&line.newest_release, &line.newest_rust_version)
+    {
+        latest = format!("{} ({} needs Rust {})", latest, newest, rust_version);
+    }
+    match line.latest_edition {
+        Some(ref edition) => format!("{} (edition {})", latest, edition),
+        None => latest,
+    }
+}
+
+/// Print reports as tables, with a heading per report in workspace mode
+/// and for reports of other projects
+///
+/// When `verbose`, the configured reasons for holding dependencies back
+/// are printed as well
+pub fn print_list(
+    out: &mut Write,
+    reports: &[Report],
+    workspace_mode: bool,
+    verbose: bool,
+    color: bool,
+) -> CargoResult<()> {
+    let mut preceding_line = false;
+    for report in reports {
+        if report.dependencies.is_empty() && report.errors.is_empty() {
+            continue;
+        }
+        if preceding_line {
+            writeln!(out)?;
+        }
+        if workspace_mode || report.project.is_some() {
+            writeln!(out, "{}\n================", report.title())?;
+        }
+        if !report.dependencies.is_empty() {
+            print_table(out, &report.dependencies, color)?;
+        }
+        for error in &report.errors {
+            writeln!(out, "Warning: {}", error)?;
+        }
+        print_notes(out, &report.dependencies, verbose)?;
+        print_graph_changes(out, &report.graph_changes)?;
+        preceding_line = true;
+    }
+    if !preceding_line {
+        writeln!(out, "All dependencies are up to date, yay!")?;
+    }
+    Ok(())
+}
+
+/// Print dependency rows to `TabWriter`, with `Series`, `Effort`, `Owners`,
+/// `Age` and `Released`, and `Path` columns if they were computed, and the
+/// `Latest` column colored by severity if `color`
+pub fn print_table(out: &mut Write, lines: &[Line], color: bool) -> CargoResult<()> {
+    let with_series = lines.iter().any(|line| line.latest_in_series.is_some());
+    let with_dates = lines
+        .iter()
+        .any(|line| line.project_age_days.is_some() || line.latest_released.is_some());
+    let with_effort = lines.iter().any(|line| line.effort.is_some());
+    let with_owners = lines.iter().any(|line| line.owners.is_some());
+    let with_path = lines.iter().any(|line| line.path.is_some());
+    let with_pulled_in_by = lines.iter().any(|line| line.pulled_in_by.is_some());
+    let with_links = lines
+        .iter()
+        .any(|line| line.link.is_some() || line.compare_url.is_some());
+    let mut tw = TabWriter::new(vec![]);
+    let paint = |cell: &str, severity: Option<&String>| if color {
+        format!("{}{}{}", severity_color(severity), cell, RESET)
+    } else {
+        cell.to_owned()
+    };
+    write!(
+        &mut tw,
+        "Name\tProject\tCompat\t{}\tKind\tPlatform\tSeverity",
+        paint("Latest", None)
+    )?;
+    write!(&mut tw, "{}", if with_series { "\tSeries" } else { "" })?;
+    write!(&mut tw, "{}", if with_effort { "\tEffort" } else { "" })?;
+    write!(&mut tw, "{}", if with_owners { "\tOwners" } else { "" })?;
+    write!(&mut tw, "{}", if with_dates { "\tAge\tReleased" } else { "" })?;
+    write!(&mut tw, "{}", if with_path { "\tPath" } else { "" })?;
+    write!(&mut tw, "{}", if with_pulled_in_by { "\tPulled in by" } else { "" })?;
+    write!(&mut tw, "{}\n", if with_links { "\tLink" } else { "" })?;
+    write!(
+        &mut tw,
+        "----\t-------\t------\t{}\t----\t--------\t--------",
+        paint("------", None)
+    )?;
+    write!(&mut tw, "{}", if with_series { "\t------" } else { "" })?;
+    write!(&mut tw, "{}", if with_effort { "\t------" } else { "" })?;
+    write!(&mut tw, "{}", if with_owners { "\t------" } else { "" })?;
+    write!(&mut tw, "{}", if with_dates { "\t---\t--------" } else { "" })?;
+    write!(&mut tw, "{}", if with_path { "\t----" } else { "" })?;
+    write!(&mut tw, "{}", if with_pulled_in_by { "\t------------" } else { "" })?;
+    write!(&mut tw, "{}\n", if with_links { "\t----" } else { "" })?;
+    for line in lines {
+        // name version compatible latest kind platform severity
+        write!(
+            &mut tw,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            name_cell(line),
+            project_cell(line),
+            with_rust_version(&line.compat, &line.compat_rust_version),
+            paint(&latest_cell(line), line.severity.as_ref()),
+            kind_cell(line),
+            or_dashes(&line.platform),
+            or_dashes(&line.severity)
+        )?;
+        if with_series {
+            write!(&mut tw, "\t{}", or_dashes(&line.latest_in_series))?;
+        }
+        if with_effort {
+            match line.effort {
+                Some(effort) => write!(&mut tw, "\t{}", effort)?,
+                None => write!(&mut tw, "\t---")?,
+            }
+        }
+        if with_owners {
+            let owners = line.owners.as_ref().map(|o| o.join(", ")).unwrap_or_default();
+            write!(&mut tw, "\t{}", if owners.is_empty() { "---" } else { owners.as_str() })?;
+        }
+        if with_dates {
+            let age = line.project_age_days
+                .map(|days| describe_age(&Duration::from_secs(days * 86_400)));
+            write!(&mut tw, "\t{}\t{}", or_dashes(&age), or_dashes(&line.latest_released))?;
+        }
+        if with_path {
+            let path = line.path.as_ref().map(|p| p.join(" -> "));
+            write!(&mut tw, "\t{}", or_dashes(&path))?;
+        }
+        if with_pulled_in_by {
+            let members = line.pulled_in_by.as_ref().map(|m| m.join(", "));
+            write!(&mut tw, "\t{}", or_dashes(&members))?;
+        }
+        if with_links {
+            // the changes are what there is to review
+            let link = line.compare_url.as_ref().or(line.link.as_ref()).cloned();
+            write!(&mut tw, "\t{}", or_dashes(&link))?;
+        }
+        write!(&mut tw, "\n")?;
+    }
+    tw.flush()?;
+    write!(
+        out,
+        "{}",
+        String::from_utf8(tw.into_inner().unwrap()).unwrap()
+    )?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Print notes explaining `Removed` rows, warning of yanked versions in
+/// use, listing fixed advisories and breaking API changes, flagging new
+/// publishers, updates blocked by policy and disallowed licenses, and
+/// suggesting replacements of deprecated dependencies
+fn print_notes(out: &mut Write, lines: &[Line], verbose: bool) -> CargoResult<()> {
+    for line in lines {
+        if let Some(ref reason) = line.removed_reason {
+            writeln!(out, "Note: {} is Removed because {}", line.name, reason)?;
+        }
+    }
+    for line in lines {
+        if line.yanked {
+            writeln!(
+                out,
+                "Warning: {} {} was yanked from the registry, consider updating",
+                line.name, line.project
+            )?;
+        }
+    }
+    for line in lines {
+        if let Some(ref advisories) = line.fixed_advisories {
+            writeln!(
+                out,
+                "Note: upgrading {} fixes {}",
+                line.name,
+                advisories.join(", ")
+            )?;
+        }
+    }
+    for line in lines {
+        if let Some(ref advisories) = line.advisories {
+            let fixed = line.fixed_advisories.as_ref();
+            let unfixed: Vec<_> = advisories
+                .iter()
+                .filter(|id| !fixed.map(|fixed| fixed.contains(*id)).unwrap_or(false))
+                .map(|id| id.as_str())
+                .collect();
+            if !unfixed.is_empty() {
+                writeln!(
+                    out,
+                    "Note: {} {} is affected by {}, which no newer version fixes",
+                    line.name,
+                    line.project,
+                    unfixed.join(", ")
+                )?;
+            }
+        }
+    }
+    for line in lines {
+        if let Some(count) = line.breaking_changes {
+            writeln!(
+                out,
+                "Note: upgrading {} to {} removes or changes {} public item(s) of its API",
+                line.name, line.latest, count
+            )?;
+        }
+    }
+    for line in lines {
+        if let Some(ref publisher) = line.new_publisher {
+            writeln!(
+                out,
+                "Note: {} {} was published by {}, who published none of the earlier versions",
+                line.crate_name(),
+                line.latest,
+                publisher
+            )?;
+        }
+    }
+    for line in lines {
+        if let Some(ref policy) = line.blocked_by {
+            match line.allowed {
+                Some(ref allowed) => writeln!(
+                    out,
+                    "Note: {} {} is blocked by its {} policy, {} is allowed",
+                    line.name, line.latest, policy, allowed
+                )?,
+                None => writeln!(
+                    out,
+                    "Note: {} {} is blocked by its {} policy, no update is allowed",
+                    line.name, line.latest, policy
+                )?,
+            }
+        }
+    }
+    for line in lines {
+        if let Some(ref license) = line.denied_license {
+            writeln!(
+                out,
+                "Note: upgrading {} to {} would pull in the disallowed license {}",
+                line.name, line.latest, license
+            )?;
+        }
+    }
+    if verbose {
+        let mut seen = HashSet::new();
+        for line in lines {
+            if let Some(ref reason) = line.reason {
+                if seen.insert(line.crate_name()) {
+                    writeln!(out, "Note: {} is held back: {}", line.crate_name(), reason)?;
+                }
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    for line in lines {
+        if let Some(ref successor) = line.successor {
+            let name = line.crate_name();
+            if seen.insert(name) {
+                writeln!(out, "Note: {} is deprecated, consider {} instead", name, successor)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print what upgrading direct dependencies adds to or removes from the graph
+fn print_graph_changes(out: &mut Write, changes: &[GraphChange]) -> CargoResult<()> {
+    for change in changes {
+        let mut effects = vec![];
+        if !change.added.is_empty() {
+            effects.push(format!("adds {}", change.added.join(", ")));
+        }
+        if !change.removed.is_empty() {
+            effects.push(format!("removes {}", change.removed.join(", ")));
+        }
+        writeln!(
+            out,
+            "Upgrading {} to {} {}",
+            change.name,
+            change.version,
+            effects.join(" and ")
+        )?;
+    }
+    Ok(())
+}
+
+/// Print a stable one-line summary for scripts, e.g.
+/// `outdated: 12 (3 major, 7 minor, 2 patch)`
+pub fn print_summary(out: &mut Write, reports: &[Report]) -> CargoResult<()> {
+    let (_, counts) = severity_counts(reports);
+    let mut blocked = 0;
+    let mut vulnerable = 0;
+    let mut yanked = 0;
+    let mut editions = BTreeMap::new();
+    for line in reports.iter().flat_map(|r| r.dependencies.iter()) {
+        if line.advisories.is_some() {
+            vulnerable += 1;
+        }
+        if line.yanked {
+            yanked += 1;
+        }
+        if let Some(ref edition) = line.latest_edition {
+            *editions.entry(edition.as_str()).or_insert(0) += 1;
+        }
+        if line.is_blocked() {
+            blocked += 1;
+        }
+    }
+    write!(
+        out,
+        "outdated: {} ({} major, {} minor, {} patch",
+        Report::count(reports),
+        counts[0],
+        counts[1],
+        counts[2]
+    )?;
+    if counts[3] > 0 {
+        write!(out, ", {} other", counts[3])?;
+    }
+    write!(out, ")")?;
+    if blocked > 0 {
+        write!(out, ", {} blocked by policy", blocked)?;
+    }
+    if vulnerable > 0 {
+        write!(out, ", {} vulnerable", vulnerable)?;
+    }
+    if yanked > 0 {
+        write!(out, ", {} yanked", yanked)?;
+    }
+    for (edition, count) in editions {
+        write!(out, ", {} need edition {}", count, edition)?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Numbers of dependencies up to date and of those with an upgrade which
+/// is not blocked by policy, by severity: major, minor, patch and other,
+/// e.g. for git dependencies behind their branch
+fn severity_counts(reports: &[Report]) -> (usize, [usize; 4]) {
+    let mut counts = [0; 4];
+    for line in reports.iter().flat_map(|r| r.dependencies.iter()) {
+        if !line.is_actionable() {
+            continue;
+        }
+        match line.severity.as_ref().map(|s| s.as_str()) {
+            Some("Major") => counts[0] += 1,
+            Some("Minor") => counts[1] += 1,
+            Some("Patch") => counts[2] += 1,
+            _ => counts[3] += 1,
+        }
+    }
+    (reports.iter().map(|r| r.up_to_date).sum(), counts)
+}
+
+/// Print the aggregate counts only, with `--summary`, e.g.
+/// `40 up to date, 2 patch behind, 7 minor behind, 3 major behind`
+pub fn print_counts(out: &mut Write, reports: &[Report]) -> CargoResult<()> {
+    let (up_to_date, counts) = severity_counts(reports);
+    writeln!(
+        out,
+        "{} up to date, {} patch behind, {} minor behind, {} major behind",
+        up_to_date, counts[2], counts[1], counts[0]
+    )?;
+    Ok(())
+}
+
+/// The payload of a shields.io endpoint badge
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Badge {
+    schema_version: u32,
+    label: &'static str,
+    message: String,
+    color: &'static str,
+}
+
+/// Print a shields.io endpoint badge, red if any dependency is a major
+/// version behind, yellow if one is behind at all and green otherwise
+pub fn print_badge(out: &mut Write, reports: &[Report]) -> CargoResult<()> {
+    let (_, counts) = severity_counts(reports);
+    let outdated = Report::count(reports);
+    let badge = Badge {
+        schema_version: 1,
+        label: "dependencies",
+        message: if outdated == 0 {
+            "up to date".to_owned()
+        } else {
+            format!("{} outdated", outdated)
+        },
+        color: if counts[0] > 0 {
+            "red"
+        } else if outdated > 0 {
+            "yellow"
+        } else {
+            "brightgreen"
+        },
+    };
+    writeln!(out, "{}", serde_json::to_string(&badge)?)?;
+    Ok(())
+}
+
+/// Print reports as GitHub flavored markdown tables, with a heading per
+/// report in workspace mode and for reports of other projects
+pub fn print_markdown(
+    out: &mut Write,
+    reports: &[Report],
+    workspace_mode: bool,
+) -> CargoResult<()> {
+    let mut preceding_table = false;
+    for report in reports
+        .iter()
+        .filter(|r| !r.dependencies.is_empty() || !r.errors.is_empty())
+    {
+        if preceding_table {
+            writeln!(out)?;
+        }
+        if workspace_mode || report.project.is_some() {
+            writeln!(out, "### {}\n", markdown_escape(&report.title()))?;
+        }
+        if !report.dependencies.is_empty() {
+            writeln!(
+                out,
+                "| Name | Project | Compat | Latest | Kind | Platform | Severity |"
+            )?;
+            writeln!(out, "|---|---|---|---|---|---|---|")?;
+            for line in &report.dependencies {
+                let cells: Vec<_> = cells(line).iter().map(|c| markdown_escape(c)).collect();
+                writeln!(out, "| {} |", cells.join(" | "))?;
+            }
+        }
+        for error in &report.errors {
+            writeln!(out, "\n> **Warning:** {}", markdown_escape(error))?;
+        }
+        preceding_table = true;
+    }
+    if !preceding_table {
+        writeln!(out, "All dependencies are up to date, yay!")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Print reports as an HTML fragment, a table per report
+///
+/// Rows have a `severity-major`, `severity-minor` or `severity-patch`
+/// class, and cells one named after their column, for styling. Errors of
+/// a report follow its table as paragraphs of the `warning` class.
+pub fn print_html(out: &mut Write, reports: &[Report], workspace_mode: bool) -> CargoResult<()> {
+    const COLUMNS: &[&str] = &[
+        "name", "project", "compat", "latest", "kind", "platform", "severity"
+    ];
+    writeln!(out, "<div class=\"cargo-outdated\">")?;
+    let mut any = false;
+    for report in reports
+        .iter()
+        .filter(|r| !r.dependencies.is_empty() || !r.errors.is_empty())
+    {
+        if workspace_mode || report.project.is_some() {
+            writeln!(out, "<h3>{}</h3>", html_escape(&report.title()))?;
+        }
+        if !report.dependencies.is_empty() {
+            writeln!(out, "<table>")?;
+            writeln!(
+                out,
+                "<thead><tr><th>Name</th><th>Project</th><th>Compat</th><th>Latest</th>\
+                 <th>Kind</th><th>Platform</th><th>Severity</th></tr></thead>"
+            )?;
+            writeln!(out, "<tbody>")?;
+            for line in &report.dependencies {
+                match line.severity {
+                    Some(ref severity) => write!(
+                        out,
+                        "<tr class=\"severity-{}\">",
+                        severity.to_lowercase()
+                    )?,
+                    None => write!(out, "<tr>")?,
+                }
+                for (column, cell) in COLUMNS.iter().zip(cells(line).iter()) {
+                    write!(out, "<td class=\"{}\">{}</td>", column, html_escape(cell))?;
+                }
+                writeln!(out, "</tr>")?;
+            }
+            writeln!(out, "</tbody>")?;
+            writeln!(out, "</table>")?;
+        }
+        for error in &report.errors {
+            writeln!(out, "<p class=\"warning\">Warning: {}</p>", html_escape(error))?;
+        }
+        any = true;
+    }
+    if !any {
+        writeln!(out, "<p>All dependencies are up to date, yay!</p>")?;
+    }
+    writeln!(out, "</div>")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// The cells of the columns all outputs share, as in the table
+fn cells(line: &Line) -> Vec<String> {
+    vec![
+        name_cell(line),
+        project_cell(line),
+        with_rust_version(&line.compat, &line.compat_rust_version),
+        latest_cell(line),
+        kind_cell(line),
+        or_dashes(&line.platform).to_owned(),
+        or_dashes(&line.severity).to_owned(),
+    ]
+}
+
+fn markdown_escape(text: &str) -> String { text.replace('|', "\\|") }
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Version of the `--porcelain` format, bumped whenever its fields change
+pub const PORCELAIN_VERSION: u32 = 1;
+
+/// Print reports for scripts, in a format kept stable across releases: a
+/// `# cargo-outdated porcelain v1` header, then per outdated dependency the
+/// tab separated fields root, project, name, current, compat, latest, kind,
+/// platform and severity, `-` standing for a missing value
+///
+/// Fields are only ever added after the existing ones, along with a bump
+/// of `PORCELAIN_VERSION`.
+pub fn print_porcelain(out: &mut Write, reports: &[Report]) -> CargoResult<()> {
+    writeln!(out, "# cargo-outdated porcelain v{}", PORCELAIN_VERSION)?;
+    let or_dash = |value: Option<&String>| value.map(|v| v.as_str()).unwrap_or("-").to_owned();
+    for report in reports {
+        for line in &report.dependencies {
+            let fields = [
+                report.crate_name.clone(),
+                or_dash(report.project.as_ref()),
+                line.name.clone(),
+                line.project.clone(),
+                line.compat.clone(),
+                line.latest.clone(),
+                or_dash(line.kind.as_ref()),
+                or_dash(line.platform.as_ref()),
+                or_dash(line.severity.as_ref()),
+            ];
+            writeln!(out, "{}", fields.join("\t"))?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Print the shared columns of the rows as tab-separated values with a
+/// header, for `awk` or `cut`, led by the member in workspace mode
+pub fn print_tsv(out: &mut Write, reports: &[Report], workspace_mode: bool) -> CargoResult<()> {
+    let with_member = workspace_mode || reports.iter().any(|r| r.project.is_some());
+    let header = "Name\tProject\tCompat\tLatest\tKind\tPlatform\tSeverity";
+    if with_member {
+        writeln!(out, "Member\t{}", header)?;
+    } else {
+        writeln!(out, "{}", header)?;
+    }
+    for report in reports {
+        for line in &report.dependencies {
+            let cells = cells(line).join("\t");
+            if with_member {
+                writeln!(out, "{}\t{}", report.title(), cells)?;
+            } else {
+                writeln!(out, "{}", cells)?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Print reports as a JSON array
+pub fn print_json(out: &mut Write, reports: &[Report]) -> CargoResult<()> {
+    serde_json::to_writer_pretty(&mut *out, reports)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+
+    use super::{print_html, print_markdown, print_summary, Line, Report};
+
+    fn line(json: &str) -> Line { serde_json::from_str(json).unwrap() }
+
+    fn report(dependencies: Vec<Line>) -> Vec<Report> {
+        vec![
+            Report {
+                crate_name: "app".to_owned(),
+                project: None,
+                dependencies: dependencies,
+                graph_changes: vec![],
+                index_age_secs: None,
+                latest_unavailable: false,
+                up_to_date: 0,
+                errors: vec![],
+            },
+        ]
+    }
+
+    fn summary(reports: &[Report]) -> String {
+        let mut out = vec![];
+        print_summary(&mut out, reports).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn only_upgrades_are_counted() {
+        let reports = report(vec![
+            line(r#"{"name": "a", "project": "1.0.0", "compat": "1.0.1", "latest": "2.0.0",
+                     "severity": "Major"}"#),
+            line(r#"{"name": "b", "project": "1.0.0", "compat": "1.1.0", "latest": "1.1.0",
+                     "severity": "Minor"}"#),
+            // yanked, with nothing newer
+            line(r#"{"name": "c", "project": "1.0.0", "compat": "---", "latest": "---",
+                     "yanked": true}"#),
+            // only removed from the latest resolution
+            line(r#"{"name": "d", "project": "1.0.0", "compat": "---", "latest": "Removed"}"#),
+            // a git dependency behind its branch
+            line(r#"{"name": "e", "project": "main", "compat": "---",
+                     "latest": "abc1234 (main)"}"#),
+            // locked git dependencies, released in the version in use or never
+            line(r#"{"name": "f", "project": "0.3.0", "compat": "git (abc12345)",
+                     "latest": "0.3.0"}"#),
+            line(r#"{"name": "g", "project": "0.1.0", "compat": "git (abc12345)",
+                     "latest": "Unknown"}"#),
+        ]);
+        assert_eq!(Report::count(&reports), 3);
+        assert_eq!(Report::rows(&reports), 7);
+        assert_eq!(
+            summary(&reports),
+            "outdated: 3 (1 major, 1 minor, 0 patch, 1 other), 1 yanked\n"
+        );
+    }
+
+    #[test]
+    fn blocked_upgrades_are_not_counted() {
+        let reports = report(vec![
+            line(r#"{"name": "a", "project": "1.0.0", "compat": "1.0.1", "latest": "2.0.0",
+                     "severity": "Major", "blocked_by": "minor-only"}"#),
+            line(r#"{"name": "b", "project": "1.0.0", "compat": "1.0.1", "latest": "2.0.0",
+                     "severity": "Major", "blocked_by": "minor-only", "allowed": "1.0.1"}"#),
+        ]);
+        assert_eq!(Report::count(&reports), 1);
+        assert_eq!(
+            summary(&reports),
+            "outdated: 1 (1 major, 0 minor, 0 patch), 1 blocked by policy\n"
+        );
+    }
+
+    #[test]
+    fn nothing_outdated() {
+        let reports = report(vec![]);
+        assert_eq!(Report::count(&reports), 0);
+        assert_eq!(summary(&reports), "outdated: 0 (0 major, 0 minor, 0 patch)\n");
+    }
+
+    fn failed_member() -> Vec<Report> {
+        let mut reports = report(vec![]);
+        reports[0].errors.push("failed to analyze app: <no index>".to_owned());
+        reports
+    }
+
+    #[test]
+    fn markdown_lists_errors() {
+        let mut out = vec![];
+        print_markdown(&mut out, &failed_member(), true).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "### app\n\n\n> **Warning:** failed to analyze app: <no index>\n"
+        );
+    }
+
+    #[test]
+    fn html_lists_errors() {
+        let mut out = vec![];
+        print_html(&mut out, &failed_member(), true).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "<div class=\"cargo-outdated\">\n<h3>app</h3>\n\
+             <p class=\"warning\">Warning: failed to analyze app: &lt;no index&gt;</p>\n\
+             </div>\n"
+        );
+    }
+}
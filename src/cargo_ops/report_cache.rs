@@ -0,0 +1,119 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use cargo::core::Workspace;
+use cargo::util::{short_hash, CargoResult, Config};
+use serde_json;
+
+use super::project_config::CONFIG_FILE;
+use super::report::Report;
+
+/// What a cache entry holds, the reports of a workspace
+#[derive(Serialize, Deserialize)]
+pub struct CachedReports {
+    pub reports: Vec<Report>,
+    pub workspace_mode: bool,
+}
+
+/// The cache entry of a workspace analyzed with some options, under
+/// `reports` in the cache directory
+///
+/// Entries are keyed by a hash of the options, the manifests of the members,
+/// the lock file, the project configuration, the cargo configuration of the
+/// workspace root and the state of the local copies of the registry indexes,
+/// so that any change of them makes for another entry. Dependencies outside
+/// the workspace, e.g. on git branches, are not taken into account.
+///
+/// A hit skips the resolution, which is what updates the indexes, so that
+/// entries expire after a while for new releases to show up.
+pub struct ReportCache {
+    path: PathBuf,
+}
+
+impl ReportCache {
+    pub fn new(
+        cache_dir: &Path,
+        config: &Config,
+        workspace: &Workspace,
+        options: &str,
+    ) -> CargoResult<ReportCache> {
+        let root = workspace.root();
+        let mut inputs = vec![env!("CARGO_PKG_VERSION").to_owned(), options.to_owned()];
+        inputs.push(read_or_empty(&root.join("Cargo.toml"))?);
+        for member in workspace.members() {
+            inputs.push(member.manifest_path().to_string_lossy().into_owned());
+            inputs.push(read_or_empty(member.manifest_path())?);
+        }
+        inputs.push(read_or_empty(&root.join("Cargo.lock"))?);
+        inputs.push(read_or_empty(&root.join(CONFIG_FILE))?);
+        // source replacement changes what the versions resolve to
+        inputs.push(read_or_empty(&root.join(".cargo").join("config"))?);
+        inputs.push(read_or_empty(&root.join(".cargo").join("config.toml"))?);
+        inputs.extend(index_states(config)?);
+        Ok(ReportCache {
+            path: cache_dir
+                .join("reports")
+                .join(format!("{}.json", short_hash(&inputs))),
+        })
+    }
+
+    /// The reports of the entry, `None` if there is none, it is older than
+    /// `max_age` or it can't be read
+    pub fn load(&self, max_age: Duration) -> Option<CachedReports> {
+        let age = fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        if age > max_age {
+            return None;
+        }
+        File::open(&self.path)
+            .ok()
+            .and_then(|file| serde_json::from_reader(file).ok())
+    }
+
+    pub fn store(&self, reports: &[Report], workspace_mode: bool) -> CargoResult<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let cached = CachedReports {
+            reports: reports.to_vec(),
+            workspace_mode: workspace_mode,
+        };
+        serde_json::to_writer(File::create(&self.path)?, &cached)?;
+        Ok(())
+    }
+}
+
+fn read_or_empty(path: &Path) -> CargoResult<String> {
+    let mut contents = String::new();
+    if path.is_file() {
+        File::open(path)?.read_to_string(&mut contents)?;
+    }
+    Ok(contents)
+}
+
+/// What the local copies of the registry indexes were last fetched at, the
+/// commit git fetched or else the time of the marker newer cargo versions
+/// leave
+fn index_states(config: &Config) -> CargoResult<Vec<String>> {
+    let indexes = config.home().as_path_unlocked().join("registry").join("index");
+    let mut states = vec![];
+    if let Ok(entries) = fs::read_dir(indexes) {
+        for index in entries {
+            let index = index?.path();
+            let fetch_head = read_or_empty(&index.join(".git").join("FETCH_HEAD"))?;
+            let marker = fs::metadata(index.join(".last-updated"))
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|since| since.as_secs().to_string())
+                .unwrap_or_default();
+            states.push(format!("{}:{}{}", index.display(), fetch_head, marker));
+        }
+    }
+    states.sort();
+    Ok(states)
+}
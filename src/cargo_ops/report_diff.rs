@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use cargo::util::CargoResult;
+use serde_json;
+use tabwriter::TabWriter;
+
+use super::report::{Line, Report};
+
+/// A dependency whose row differs between two reports
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    pub crate_name: String,
+    pub before: Option<Line>,
+    pub after: Option<Line>,
+}
+
+/// Differences between a previous and the current report
+#[derive(Debug, Default, Serialize)]
+pub struct ReportDiff {
+    /// Dependencies which were up to date before
+    pub newly_outdated: Vec<Change>,
+    /// Dependencies which are up to date now
+    pub fixed: Vec<Change>,
+    /// Dependencies which are still outdated, but by a different severity
+    pub severity_changed: Vec<Change>,
+}
+
+type Key = (String, String, Option<String>);
+
+fn index(reports: &[Report]) -> BTreeMap<Key, &Line> {
+    let mut lines = BTreeMap::new();
+    for report in reports {
+        for line in &report.dependencies {
+            let key = (report.title(), line.name.clone(), line.kind.clone());
+            lines.insert(key, line);
+        }
+    }
+    lines
+}
+
+impl ReportDiff {
+    pub fn between(previous: &[Report], current: &[Report]) -> ReportDiff {
+        let before = index(previous);
+        let after = index(current);
+        let mut diff = ReportDiff::default();
+        for (key, line) in &after {
+            let previous = before.get(key).cloned();
+            let change = Change {
+                crate_name: key.0.clone(),
+                before: previous.cloned(),
+                after: Some((*line).clone()),
+            };
+            match previous {
+                None => diff.newly_outdated.push(change),
+                Some(l) if l.severity != line.severity => diff.severity_changed.push(change),
+                Some(_) => {}
+            }
+        }
+        for (key, line) in &before {
+            if !after.contains_key(key) {
+                diff.fixed.push(Change {
+                    crate_name: key.0.clone(),
+                    before: Some((*line).clone()),
+                    after: None,
+                });
+            }
+        }
+        diff
+    }
+
+    /// Number of newly outdated dependencies, only counting those whose
+    /// upgrade fixes an advisory if `security_only`
+    pub fn count_newly_outdated(&self, security_only: bool) -> i32 {
+        self.newly_outdated
+            .iter()
+            .filter_map(|change| change.after.as_ref())
+            .filter(|line| !security_only || line.fixed_advisories.is_some())
+            .count() as i32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.newly_outdated.is_empty() && self.fixed.is_empty() && self.severity_changed.is_empty()
+    }
+
+    /// Print changes grouped by category
+    pub fn print_list(&self, out: &mut Write) -> CargoResult<()> {
+        if self.is_empty() {
+            writeln!(out, "No changes since the previous report")?;
+            return Ok(());
+        }
+        let sections = [
+            ("Newly outdated", &self.newly_outdated),
+            ("Fixed", &self.fixed),
+            ("Severity changed", &self.severity_changed),
+        ];
+        let mut tw = TabWriter::new(vec![]);
+        let mut preceding_line = false;
+        for &(title, changes) in &sections {
+            if changes.is_empty() {
+                continue;
+            }
+            if preceding_line {
+                write!(&mut tw, "\n")?;
+            }
+            write!(&mut tw, "{}\n", title)?;
+            write!(&mut tw, "Member\tName\tProject\tLatest\tSeverity\n")?;
+            write!(&mut tw, "------\t----\t-------\t------\t--------\n")?;
+            for change in changes.iter() {
+                let line = change.after.as_ref().or(change.before.as_ref()).unwrap();
+                let severity = match (&change.before, &change.after) {
+                    (&Some(ref before), &Some(ref after)) => format!(
+                        "{} -> {}",
+                        before.severity.as_ref().map(|s| s.as_str()).unwrap_or("---"),
+                        after.severity.as_ref().map(|s| s.as_str()).unwrap_or("---")
+                    ),
+                    _ => line.severity.clone().unwrap_or_else(|| "---".to_owned()),
+                };
+                write!(
+                    &mut tw,
+                    "{}\t{}\t{}\t{}\t{}\n",
+                    change.crate_name,
+                    line.name,
+                    line.project,
+                    line.latest,
+                    severity
+                )?;
+            }
+            preceding_line = true;
+        }
+        tw.flush()?;
+        write!(
+            out,
+            "{}",
+            String::from_utf8(tw.into_inner().unwrap()).unwrap()
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+
+    pub fn print_json(&self, out: &mut Write) -> CargoResult<()> {
+        serde_json::to_writer_pretty(&mut *out, self)?;
+        writeln!(out)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use cargo::util::CargoResult;
+use semver::Version;
+use serde_json;
+use toml::Value;
+
+use super::crates_io::CratesIo;
+use super::pkg_status::is_candidate;
+use super::report::Line;
+
+/// The `rust-version` releases declare, kept as `rust-versions.json` in the
+/// cache directory
+///
+/// Published releases never change, so that entries never expire and
+/// crates.io is only asked about releases not looked up before.
+pub struct RustVersions {
+    path: PathBuf,
+    known: BTreeMap<String, Option<String>>,
+    changed: bool,
+}
+
+impl RustVersions {
+    /// Load the entries kept under `cache_dir`, none if they can't be read
+    pub fn load(cache_dir: &Path) -> RustVersions {
+        let path = cache_dir.join("rust-versions.json");
+        let known = File::open(&path)
+            .ok()
+            .and_then(|file| serde_json::from_reader(file).ok())
+            .unwrap_or_default();
+        RustVersions {
+            path: path,
+            known: known,
+            changed: false,
+        }
+    }
+
+    /// The `rust-version` of a release given as shown in the report, `None`
+    /// if it declares none or crates.io doesn't know it
+    fn get(
+        &mut self,
+        crates_io: &mut CratesIo,
+        name: &str,
+        version: &str,
+    ) -> CargoResult<Option<String>> {
+        let key = format!("{} {}", name, version);
+        if let Some(rust_version) = self.known.get(&key) {
+            return Ok(rust_version.clone());
+        }
+        // unknown releases may just not be fetched, e.g. with the network off
+        let rust_version = match crates_io.version_info(name, version)? {
+            Some(info) => info.rust_version.clone(),
+            None => return Ok(None),
+        };
+        self.known.insert(key, rust_version.clone());
+        self.changed = true;
+        Ok(rust_version)
+    }
+
+    /// Keep the entries looked up in this run for the next ones
+    pub fn store(&self) -> CargoResult<()> {
+        if !self.changed {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        serde_json::to_writer(File::create(&self.path)?, &self.known)?;
+        Ok(())
+    }
+}
+
+/// Parse a `rust-version` like `1.56` into a full version
+pub fn parse_rust_version(rust_version: &str) -> Option<Version> {
+    let mut parts: Vec<_> = rust_version.trim().split('.').collect();
+    while parts.len() < 3 {
+        parts.push("0");
+    }
+    Version::parse(&parts.join(".")).ok()
+}
+
+/// The `package.rust-version` declared in a manifest
+pub fn declared_rust_version(manifest_path: &Path) -> CargoResult<Option<Version>> {
+    let mut contents = String::new();
+    File::open(manifest_path)?.read_to_string(&mut contents)?;
+    let manifest: Value = ::toml::from_str(&contents)?;
+    Ok(manifest
+        .get("package")
+        .and_then(|p| p.get("rust-version"))
+        .and_then(|v| v.as_str())
+        .and_then(parse_rust_version))
+}
+
+/// Set the `needs Rust` markers of rows for a project declaring
+/// `rust-version` in `manifest_path`
+pub fn annotate_rust_versions(
+    crates_io: &mut CratesIo,
+    rust_versions: &mut RustVersions,
+    manifest_path: &Path,
+    lines: &mut [Line],
+) -> CargoResult<()> {
+    let msrv = match declared_rust_version(manifest_path)? {
+        Some(msrv) => msrv,
+        None => return Ok(()),
+    };
+    for line in lines.iter_mut() {
+        let name = line.crate_name().to_owned();
+        line.compat_rust_version =
+            required_rust_version(crates_io, rust_versions, &name, &line.compat, &msrv)?;
+        line.latest_rust_version =
+            required_rust_version(crates_io, rust_versions, &name, &line.latest, &msrv)?;
+    }
+    Ok(())
+}
+
+/// Newest release of a crate on crates.io whose `rust-version` is at most
+/// `msrv`, releases declaring none counting as compatible
+pub fn newest_compatible(
+    crates_io: &mut CratesIo,
+    name: &str,
+    msrv: &Version,
+    pre_release: bool,
+) -> CargoResult<Option<Version>> {
+    let metadata = match crates_io.crate_metadata(name)? {
+        Some(metadata) => metadata,
+        None => return Ok(None),
+    };
+    Ok(metadata
+        .versions
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter(|v| match v.rust_version.as_ref().and_then(|r| parse_rust_version(r)) {
+            Some(ref required) => required <= msrv,
+            None => true,
+        })
+        .filter_map(|v| Version::parse(&v.num).ok())
+        .filter(|v| !v.is_prerelease() || pre_release)
+        .max())
+}
+
+/// Set the newest release of rows whose latest version was held back by
+/// `--rust-version`, as the newest needs a newer Rust than the project
+/// declaring `rust-version` in `manifest_path`
+pub fn annotate_newest_releases(
+    crates_io: &mut CratesIo,
+    rust_versions: &mut RustVersions,
+    manifest_path: &Path,
+    pre_release: bool,
+    lines: &mut [Line],
+) -> CargoResult<()> {
+    let msrv = match declared_rust_version(manifest_path)? {
+        Some(msrv) => msrv,
+        None => return Ok(()),
+    };
+    for line in lines.iter_mut() {
+        let latest = match Version::parse(&line.latest) {
+            Ok(latest) => latest,
+            Err(_) => continue,
+        };
+        let name = line.crate_name().to_owned();
+        let newest = match crates_io.crate_metadata(&name)? {
+            Some(metadata) => metadata
+                .versions
+                .iter()
+                .filter(|v| !v.yanked)
+                .filter_map(|v| Version::parse(&v.num).ok())
+                .filter(|v| is_candidate(v, &latest, pre_release))
+                .max(),
+            None => None,
+        };
+        let newest = match newest {
+            Some(ref newest) if *newest > latest => newest.to_string(),
+            _ => continue,
+        };
+        let required = required_rust_version(crates_io, rust_versions, &name, &newest, &msrv)?;
+        if let Some(rust_version) = required {
+            line.newest_release = Some(newest);
+            line.newest_rust_version = Some(rust_version);
+        }
+    }
+    Ok(())
+}
+
+/// The `rust-version` of a candidate, if it exceeds `msrv`
+fn required_rust_version(
+    crates_io: &mut CratesIo,
+    rust_versions: &mut RustVersions,
+    name: &str,
+    candidate: &str,
+    msrv: &Version,
+) -> CargoResult<Option<String>> {
+    let rust_version = rust_versions.get(crates_io, name, candidate)?;
+    Ok(rust_version.and_then(|rust_version| match parse_rust_version(&rust_version) {
+        Some(ref required) if required > msrv => Some(rust_version.clone()),
+        _ => None,
+    }))
+}
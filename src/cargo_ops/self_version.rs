@@ -0,0 +1,119 @@
+use std::io::{self, Write};
+
+use cargo::core::Workspace;
+use cargo::util::CargoResult;
+use semver::Version;
+use serde_json;
+use tabwriter::TabWriter;
+
+use super::registry::RegistryIndex;
+
+/// A publishable workspace member whose version doesn't follow the newest
+/// one published under its name
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberVersion {
+    pub name: String,
+    pub local: String,
+    pub published: String,
+    /// `behind` if the local version is older than the published one,
+    /// `ahead` if it skips over versions a next release would be numbered
+    pub status: String,
+}
+
+/// Local versions of the publishable members of a workspace compared with
+/// their releases on crates.io
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfVersionReport {
+    /// Number of members compared, i.e. publishable ones already on crates.io
+    pub checked: usize,
+    pub members: Vec<MemberVersion>,
+}
+
+impl SelfVersionReport {
+    pub fn check(workspace: &Workspace) -> CargoResult<SelfVersionReport> {
+        let mut index = RegistryIndex::crates_io(workspace.config())?;
+        let mut checked = 0;
+        let mut members = vec![];
+        for member in workspace.members() {
+            if !member.publish() {
+                continue;
+            }
+            let local = member.version();
+            let published = match index
+                .versions(member.name())?
+                .iter()
+                .filter(|v| !v.is_prerelease() || local.is_prerelease())
+                .last()
+            {
+                Some(published) => published.clone(),
+                // not released yet
+                None => continue,
+            };
+            checked += 1;
+            let status = if *local < published {
+                "behind"
+            } else if *local > published && !is_next_release(&published, local) {
+                "ahead"
+            } else {
+                continue;
+            };
+            members.push(MemberVersion {
+                name: member.name().to_owned(),
+                local: local.to_string(),
+                published: published.to_string(),
+                status: status.to_owned(),
+            });
+        }
+        members.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(SelfVersionReport {
+            checked: checked,
+            members: members,
+        })
+    }
+
+    pub fn print_list(&self) -> CargoResult<()> {
+        if self.members.is_empty() {
+            println!(
+                "All {} published members follow their latest releases, yay!",
+                self.checked
+            );
+            return Ok(());
+        }
+        let mut tw = TabWriter::new(vec![]);
+        write!(&mut tw, "Name\tLocal\tPublished\tStatus\n")?;
+        write!(&mut tw, "----\t-----\t---------\t------\n")?;
+        for member in &self.members {
+            write!(
+                &mut tw,
+                "{}\t{}\t{}\t{}\n",
+                member.name, member.local, member.published, member.status
+            )?;
+        }
+        tw.flush()?;
+        write!(
+            io::stdout(),
+            "{}",
+            String::from_utf8(tw.into_inner().unwrap()).unwrap()
+        )?;
+        io::stdout().flush()?;
+        Ok(())
+    }
+
+    pub fn print_json(&self) -> CargoResult<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        serde_json::to_writer_pretty(&mut stdout, self)?;
+        writeln!(stdout)?;
+        Ok(())
+    }
+}
+
+/// Whether `local` is what the release after `published` would be numbered,
+/// i.e. a patch, minor or major bump of it or a pre-release of one, or a
+/// later pre-release of a published pre-release
+fn is_next_release(published: &Version, local: &Version) -> bool {
+    let (major, minor, patch) = (published.major, published.minor, published.patch);
+    let next = (local.major, local.minor, local.patch);
+    next == (major, minor, patch) || next == (major, minor, patch + 1)
+        || next == (major, minor + 1, 0) || next == (major + 1, 0, 0)
+}
@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+
+use cargo::core::PackageId;
+use cargo::util::{CargoResult, Config};
+use semver::Version;
+use tabwriter::TabWriter;
+
+use super::crates_io::CratesIo;
+use super::registry::RegistryIndex;
+
+/// A dependency taken into account by the statistics
+struct Entry {
+    pkg_id: PackageId,
+    latest: Option<Version>,
+}
+
+/// Aggregated staleness statistics, broken down by dependency kind
+#[derive(Default)]
+pub struct Stats {
+    seen: HashSet<(String, PackageId)>,
+    entries: BTreeMap<String, Vec<Entry>>,
+}
+
+/// Statistics of a set of dependencies
+struct Summary {
+    count: usize,
+    up_to_date: usize,
+    behind: Vec<usize>,
+    oldest: Option<u64>,
+}
+
+impl Stats {
+    /// Record a registry dependency of the given kind, once per kind
+    pub fn add(&mut self, kind: String, pkg_id: &PackageId, latest: Option<&Version>) {
+        if !pkg_id.source_id().is_registry()
+            || !self.seen.insert((kind.clone(), pkg_id.clone()))
+        {
+            return;
+        }
+        self.entries.entry(kind).or_insert_with(Vec::new).push(Entry {
+            pkg_id: pkg_id.clone(),
+            latest: latest.cloned(),
+        });
+    }
+
+    /// Print the statistics, looking up published versions and release dates
+    pub fn print(&self, out: &mut Write, config: &Config) -> CargoResult<()> {
+        let mut index = RegistryIndex::crates_io(config)?;
+        let mut crates_io = CratesIo::new(config);
+        let mut summaries = vec![];
+        let mut all = vec![];
+        for (kind, entries) in &self.entries {
+            let summary = Self::summarize(entries.iter(), &mut index, &mut crates_io)?;
+            summaries.push((kind.clone(), summary));
+            all.extend(entries);
+        }
+        summaries.push((
+            "Total".to_owned(),
+            Self::summarize(all.into_iter(), &mut index, &mut crates_io)?,
+        ));
+
+        let mut tw = TabWriter::new(vec![]);
+        write!(
+            &mut tw,
+            "Kind\tDependencies\tUp to date\tBehind (avg)\tBehind (median)\tOldest (days)\n"
+        )?;
+        write!(
+            &mut tw,
+            "----\t------------\t----------\t------------\t---------------\t-------------\n"
+        )?;
+        for (kind, summary) in summaries {
+            let (average, median) = average_and_median(&summary.behind);
+            write!(
+                &mut tw,
+                "{}\t{}\t{} ({:.0}%)\t{:.1}\t{}\t{}\n",
+                kind,
+                summary.count,
+                summary.up_to_date,
+                if summary.count == 0 {
+                    100.0
+                } else {
+                    100.0 * summary.up_to_date as f64 / summary.count as f64
+                },
+                average,
+                median,
+                summary
+                    .oldest
+                    .map(|days| days.to_string())
+                    .unwrap_or_else(|| "---".to_owned())
+            )?;
+        }
+        tw.flush()?;
+        write!(
+            out,
+            "\n{}",
+            String::from_utf8(tw.into_inner().unwrap()).unwrap()
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+
+    fn summarize<'a, I: Iterator<Item = &'a Entry>>(
+        entries: I,
+        index: &mut RegistryIndex,
+        crates_io: &mut CratesIo,
+    ) -> CargoResult<Summary> {
+        let mut summary = Summary {
+            count: 0,
+            up_to_date: 0,
+            behind: vec![],
+            oldest: None,
+        };
+        for entry in entries {
+            let current = entry.pkg_id.version();
+            let latest = entry.latest.as_ref().unwrap_or(current);
+            // pre-releases only count when already on one
+            let behind = index
+                .versions(entry.pkg_id.name())?
+                .iter()
+                .filter(|v| *v > current && *v <= latest)
+                .filter(|v| !v.is_prerelease() || current.is_prerelease())
+                .count();
+            summary.count += 1;
+            if behind == 0 {
+                summary.up_to_date += 1;
+            }
+            summary.behind.push(behind);
+            let age = crates_io
+                .crate_metadata(entry.pkg_id.name())?
+                .and_then(|metadata| metadata.version(current))
+                .and_then(|version| version.age_in_days());
+            if age > summary.oldest {
+                summary.oldest = age;
+            }
+        }
+        Ok(summary)
+    }
+}
+
+fn average_and_median(values: &[usize]) -> (f64, usize) {
+    if values.is_empty() {
+        return (0.0, 0);
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    let sum: usize = sorted.iter().sum();
+    (sum as f64 / sorted.len() as f64, sorted[sorted.len() / 2])
+}
@@ -0,0 +1,32 @@
+/// Deprecated or unmaintained crates along with their recommended successors,
+/// mostly taken from the informational advisories of the RustSec database
+/// and from the descriptions the authors published on crates.io
+const SUCCESSORS: &[(&str, &str)] = &[
+    ("ansi_term", "nu-ansi-term"),
+    ("atty", "std::io::IsTerminal"),
+    ("chan", "crossbeam-channel"),
+    ("difference", "similar"),
+    ("dotenv", "dotenvy"),
+    ("error-chain", "thiserror"),
+    ("failure", "anyhow"),
+    ("futures-cpupool", "futures"),
+    ("gcc", "cc"),
+    ("instant", "web-time"),
+    ("lazy_static", "std::sync::LazyLock"),
+    ("memmap", "memmap2"),
+    ("net2", "socket2"),
+    ("proc-macro-error", "proc-macro-error2"),
+    ("rustc-serialize", "serde"),
+    ("structopt", "clap"),
+    ("tempdir", "tempfile"),
+    ("term", "termcolor"),
+    ("tokio-core", "tokio"),
+];
+
+/// The recommended replacement of a deprecated crate
+pub fn successor(name: &str) -> Option<&'static str> {
+    SUCCESSORS
+        .iter()
+        .find(|&&(deprecated, _)| deprecated == name)
+        .map(|&(_, successor)| successor)
+}
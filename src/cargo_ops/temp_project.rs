@@ -1,99 +1,149 @@
 use std::path::{Path, PathBuf};
 use std::fs::{self, File};
-use std::io::{Read, Write};
-use std::collections::HashSet;
-use std::env;
+use std::io::{self, Read, Write};
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::cell::RefCell;
 
+use semver::{Version, VersionReq};
 use tempdir::TempDir;
 use toml::Value;
 use toml::value::Table;
 use cargo::util::errors::CargoResultExt;
-use cargo::core::{PackageId, Workspace};
-use cargo::util::{CargoError, CargoErrorKind, CargoResult, Config};
+use cargo::core::{Features, PackageId, Workspace};
+use cargo::util::{CargoError, CargoErrorKind, CargoResult, Config, ConfigValue};
 use cargo::ops::{update_lockfile, UpdateOptions};
 
-use Options;
+use OutdatedOptions;
 use super::{ElaborateWorkspace, Manifest};
+use super::artifact;
+use super::git_cli;
+use super::lockfile;
+use super::offline;
+use super::crates_io::CratesIo;
+use super::registry::RegistryIndex;
+use super::rust_version::{newest_compatible, parse_rust_version};
+
+/// Sections of the original manifests left out of the temporary ones
+const UNUSABLE_SECTIONS: &[&str] = &["example", "test", "bench"];
+
+/// Sections overriding dependencies, which only apply in the workspace root
+const OVERRIDE_SECTIONS: &[&str] = &["patch", "replace"];
 
 /// A temporary project
 pub struct TempProject<'tmp> {
     pub workspace: Rc<RefCell<Option<Workspace<'tmp>>>>,
-    pub temp_dir: TempDir,
+    temp_dir: TempDirGuard,
+    /// Direct dependencies which are not found in the registry index
+    /// and thus kept with their original requirements in the wildcard pass
+    pub unknown_deps: RefCell<HashSet<String>>,
+    /// Newest versions of the direct dependencies from alternative
+    /// registries, `None` for those missing from the index of theirs
+    pub alternative_latest: RefCell<HashMap<String, Option<Version>>>,
+    /// Path dependencies left out of the temporary manifests as their path
+    /// can't be resolved, e.g. a sibling above the workspace root which is
+    /// gone, with the reason
+    pub skipped_paths: HashMap<String, String>,
     manifest_paths: Vec<PathBuf>,
     config: Config,
-    relative_manifest: String,
+    /// Path of the manifest of the current package, relative to the root
+    relative_manifest: PathBuf,
+    /// Whether `cargo update -p` updates the dependencies of the packages
+    /// named as well, with `--aggressive`
+    aggressive: bool,
+    /// Whether the wildcard pass considers pre-releases, with `--pre-release`
+    pre_release: bool,
+    /// Whether the wildcard pass only considers releases supporting the
+    /// `rust-version` of the project, with `--rust-version`
+    rust_version: bool,
 }
 
 impl<'tmp> TempProject<'tmp> {
     /// Copy needed manifest and lock files from an existing workspace
     pub fn from_workspace(
         orig_workspace: &ElaborateWorkspace,
-        orig_manifest: &str,
-        options: &Options,
+        orig_manifest: &Path,
+        options: &OutdatedOptions,
     ) -> CargoResult<TempProject<'tmp>> {
         // e.g. /path/to/project
-        let workspace_root = orig_workspace.workspace.root().to_str().ok_or_else(|| {
-            CargoError::from_kind(CargoErrorKind::Msg(format!(
-                "Invalid character found in path {}",
-                orig_workspace.workspace.root().to_string_lossy()
-            )))
-        })?;
+        let workspace_root = orig_workspace.workspace.root();
 
-        let temp_dir = TempDir::new("cargo-outdated")?;
+        let dir = match options.flag_temp_dir {
+            Some(ref parent) => {
+                let parent = orig_workspace.workspace.config().cwd().join(parent);
+                fs::create_dir_all(&parent).chain_err(|| {
+                    format!("Failed to create temporary directory {}", parent.display())
+                })?;
+                TempDir::new_in(parent, "cargo-outdated")?
+            }
+            // `env::temp_dir()`, which honors `$TMPDIR`
+            None => TempDir::new("cargo-outdated")?,
+        };
+        let temp_dir = TempDirGuard {
+            dir: Some(dir),
+            keep: options.flag_keep_temp,
+        };
+        if options.flag_keep_temp {
+            orig_workspace.workspace.config().shell().status(
+                "Keeping",
+                format!("temporary project {}", temp_dir.path().display()),
+            )?;
+        }
         let manifest_paths = manifest_paths(orig_workspace)?;
         let mut tmp_manifest_paths = vec![];
         for from in &manifest_paths {
             // e.g. /path/to/project/src/sub
-            let mut from_dir = from.clone();
-            from_dir.pop();
-            let from_dir = from_dir.to_string_lossy();
+            let from_dir = from.parent().unwrap_or(workspace_root);
             // e.g. /tmp/cargo.xxx/src/sub
-            let mut dest = PathBuf::from(format!(
-                "{}/{}",
-                temp_dir.path().to_string_lossy(),
-                &from_dir[workspace_root.len()..]
-            ));
+            let mut dest = temp_dir.path().join(relative_path(from_dir, workspace_root)?);
             fs::create_dir_all(&dest)?;
             // e.g. /tmp/cargo.xxx/src/sub/Cargo.toml
             dest.push("Cargo.toml");
             tmp_manifest_paths.push(dest.clone());
             fs::copy(from, &dest)?;
-            let lockfile = PathBuf::from(format!("{}/Cargo.lock", from_dir));
+            let lockfile = from_dir.join("Cargo.lock");
             if lockfile.is_file() {
                 dest.pop();
                 dest.push("Cargo.lock");
-                fs::copy(lockfile, dest)?;
+                lockfile::copy_normalized(lockfile, dest)?;
             }
         }
-        Self::write_manifest_semver_with_paths(
+        let skipped_paths = Self::write_manifest_semver_with_paths(
             &tmp_manifest_paths,
             workspace_root,
-            &temp_dir.path().to_string_lossy(),
+            temp_dir.path(),
+            options,
+            orig_workspace.workspace.config(),
         )?;
 
         // virtual root
-        let mut virtual_root = PathBuf::from(format!("{}/Cargo.toml", workspace_root));
+        let mut virtual_root = workspace_root.join("Cargo.toml");
         if !manifest_paths.contains(&virtual_root) && virtual_root.is_file() {
-            fs::copy(
-                &virtual_root,
-                format!("{}/Cargo.toml", temp_dir.path().to_string_lossy()),
+            let tmp_virtual_root = temp_dir.path().join("Cargo.toml");
+            fs::copy(&virtual_root, &tmp_virtual_root)?;
+            let mut manifest = Self::read_manifest(&tmp_virtual_root)?;
+            manifest.retain_cargo_features(&Self::is_supported_feature);
+            Self::replace_override_paths(
+                &mut manifest,
+                workspace_root,
+                temp_dir.path(),
+                &tmp_virtual_root,
+                options,
+                orig_workspace.workspace.config(),
             )?;
+            Self::write_manifest(&manifest, &tmp_virtual_root)?;
             virtual_root.pop();
             virtual_root.push("Cargo.lock");
             if virtual_root.is_file() {
-                fs::copy(
-                    &virtual_root,
-                    format!("{}/Cargo.lock", temp_dir.path().to_string_lossy()),
-                )?;
+                lockfile::copy_normalized(&virtual_root, temp_dir.path().join("Cargo.lock"))?;
             }
         }
 
-        let relative_manifest =
-            String::from(&orig_manifest[orig_workspace.workspace.root().to_string_lossy().len()..]);
+        Self::write_cargo_config(orig_workspace.workspace.config(), temp_dir.path(), options)?;
+        let relative_manifest = relative_path(orig_manifest, workspace_root)?;
         let config = Self::generate_config(
-            &temp_dir.path().to_string_lossy(),
+            orig_workspace.workspace.config(),
+            temp_dir.path(),
             &relative_manifest,
             options,
         )?;
@@ -101,31 +151,140 @@ impl<'tmp> TempProject<'tmp> {
             // workspace: Workspace::new(Path::new(&root_manifest), config)?,
             workspace: Rc::new(RefCell::new(None)),
             temp_dir: temp_dir,
+            unknown_deps: RefCell::new(HashSet::new()),
+            alternative_latest: RefCell::new(HashMap::new()),
+            skipped_paths: skipped_paths,
             manifest_paths: tmp_manifest_paths,
             config: config,
             relative_manifest: relative_manifest,
+            aggressive: options.flag_aggressive,
+            pre_release: options.flag_pre_release,
+            rust_version: options.flag_rust_version,
         })
     }
 
+    /// Root directory of the temporary project
+    pub fn temp_root(&self) -> &Path { self.temp_dir.path() }
+
+    /// Manifest of the current package in the temporary project
+    fn root_manifest(&self) -> PathBuf { self.temp_root().join(&self.relative_manifest) }
+
+    /// Carry the `[net]`, `[http]` and `[source]` settings in effect for the
+    /// original workspace over to the temporary project, whose own directory
+    /// doesn't see those of the project's `.cargo/config`, and replace
+    /// crates.io by its local copy with `--offline`
+    fn write_cargo_config(
+        orig_config: &Config,
+        root: &Path,
+        options: &OutdatedOptions,
+    ) -> CargoResult<()> {
+        let mut net = Table::new();
+        if let Some(retry) = orig_config.get_i64("net.retry")? {
+            net.insert("retry".to_owned(), Value::Integer(retry.val));
+        }
+        for key in &["git-fetch-with-cli", "offline"] {
+            if let Some(value) = orig_config.get_bool(&format!("net.{}", key))? {
+                net.insert(key.to_string(), Value::Boolean(value.val));
+            }
+        }
+        let mut http = Table::new();
+        if let Some(proxy) = orig_config.get_string("http.proxy")? {
+            http.insert("proxy".to_owned(), Value::String(proxy.val));
+        }
+        if let Some(timeout) = orig_config.get_i64("http.timeout")? {
+            http.insert("timeout".to_owned(), Value::Integer(timeout.val));
+        }
+        if let Some(cainfo) = orig_config.get_path("http.cainfo")? {
+            let cainfo = cainfo.val.to_string_lossy().into_owned();
+            http.insert("cainfo".to_owned(), Value::String(cainfo));
+        }
+        let mut settings = Table::new();
+        if !net.is_empty() {
+            settings.insert("net".to_owned(), Value::Table(net));
+        }
+        if !http.is_empty() {
+            settings.insert("http".to_owned(), Value::Table(http));
+        }
+        // source replacement, e.g. by vendored sources or a mirror
+        let mut sources = match orig_config.get_table("source")? {
+            Some(sources) => Self::source_settings(&sources.val),
+            None => Table::new(),
+        };
+        if options.flag_offline {
+            sources.extend(offline::replacement_sources(orig_config, root)?);
+        }
+        if !sources.is_empty() {
+            settings.insert("source".to_owned(), Value::Table(sources));
+        }
+        if settings.is_empty() {
+            return Ok(());
+        }
+        let dir = root.join(".cargo");
+        fs::create_dir_all(&dir)?;
+        let mut file = File::create(dir.join("config"))?;
+        write!(file, "{}", ::toml::to_string(&settings)?)?;
+        Ok(())
+    }
+
+    /// The `[source]` tables of a configuration, with the paths of
+    /// directory and local registry sources made absolute, as they are
+    /// relative to the directory the `.cargo` directory defining them is in
+    fn source_settings(sources: &HashMap<String, ConfigValue>) -> Table {
+        let mut settings = Table::new();
+        for (name, source) in sources {
+            let entries = match *source {
+                ConfigValue::Table(ref entries, _) => entries,
+                _ => continue,
+            };
+            let mut table = Table::new();
+            for (key, value) in entries {
+                let value = match *value {
+                    ConfigValue::String(ref path, ref definition)
+                        if key == "directory" || key == "local-registry" =>
+                    {
+                        let base = definition.parent().and_then(|dir| dir.parent());
+                        let path = match base {
+                            Some(base) => base.join(path),
+                            None => PathBuf::from(path),
+                        };
+                        Value::String(path.to_string_lossy().into_owned())
+                    }
+                    ConfigValue::String(ref value, _) => Value::String(value.clone()),
+                    ConfigValue::Integer(value, _) => Value::Integer(value),
+                    ConfigValue::Boolean(value, _) => Value::Boolean(value),
+                    ConfigValue::List(ref values, _) => Value::Array(
+                        values
+                            .iter()
+                            .map(|&(ref value, _)| Value::String(value.clone()))
+                            .collect(),
+                    ),
+                    ConfigValue::Table(..) => continue,
+                };
+                table.insert(key.clone(), value);
+            }
+            settings.insert(name.clone(), Value::Table(table));
+        }
+        settings
+    }
+
+    /// The configuration of the temporary project, in the cargo home of the
+    /// original one
     fn generate_config(
-        root: &str,
-        relative_manifest: &str,
-        options: &Options,
+        orig_config: &Config,
+        root: &Path,
+        relative_manifest: &Path,
+        options: &OutdatedOptions,
     ) -> CargoResult<Config> {
         let shell = ::cargo::core::Shell::new();
-        let cwd = env::current_dir()
-            .chain_err(|| "Cargo couldn't get the current directory of the process")?;
-
-        let homedir = ::cargo::util::homedir(&cwd).ok_or_else(|| {
-            "Cargo couldn't find your home directory. \
-             This probably means that $HOME was not set."
-        })?;
-        let mut cwd = PathBuf::from(format!("{}/{}", root, relative_manifest));
+        let homedir = orig_config.home().as_path_unlocked().to_path_buf();
+        let mut cwd = root.join(relative_manifest);
         cwd.pop();
         let config = Config::new(shell, cwd, homedir);
+        // cargo reports updating the index and downloads along with the
+        // phases of the run
         config.configure(
             0,
-            if options.flag_verbose > 0 {
+            if options.shows_progress() {
                 None
             } else {
                 Some(true)
@@ -139,55 +298,85 @@ impl<'tmp> TempProject<'tmp> {
     }
 
     /// Run `cargo update` against the temporary project
-    pub fn cargo_update(&self) -> CargoResult<()> {
+    pub fn cargo_update(&self) -> CargoResult<()> { self.cargo_update_packages(&[]) }
+
+    /// Run `cargo update -p` for `packages` against the temporary project,
+    /// leaving every other package locked unless it has to change
+    pub fn cargo_update_packages(&self, packages: &[String]) -> CargoResult<()> {
+        self.update_lockfile(packages, None)
+    }
+
+    /// Run `cargo update -p <name> --precise <version>` against the
+    /// temporary project
+    pub fn cargo_update_precise(&self, name: &str, version: &str) -> CargoResult<()> {
+        self.update_lockfile(&[name.to_owned()], Some(version))
+    }
+
+    fn update_lockfile(&self, packages: &[String], precise: Option<&str>) -> CargoResult<()> {
         let update_opts = UpdateOptions {
-            aggressive: false,
-            precise: None,
-            to_update: &[],
+            aggressive: self.aggressive,
+            precise: precise,
+            to_update: packages,
             config: &self.config,
         };
         update_lockfile(self.workspace.borrow().as_ref().unwrap(), &update_opts)?;
         Ok(())
     }
 
+    fn read_manifest<P: AsRef<Path>>(path: P) -> CargoResult<Manifest> {
+        let mut buf = String::new();
+        let mut file = File::open(path)?;
+        file.read_to_string(&mut buf)?;
+        Manifest::parse(&buf)
+    }
+
+    /// The `[workspace]` table of the workspace root manifest, which members
+    /// inherit package fields and dependencies from
+    fn workspace_table(orig_root: &Path) -> CargoResult<Option<Table>> {
+        let root_manifest = orig_root.join("Cargo.toml");
+        if !root_manifest.is_file() {
+            return Ok(None);
+        }
+        let mut root = Self::read_manifest(root_manifest)?;
+        Ok(root.table_mut("workspace").cloned())
+    }
+
+    /// Where a path of an entry of `[workspace.dependencies]`, relative to
+    /// the workspace root, points to from the temporary manifests: the copy
+    /// in the temporary project if there is one, the original otherwise
+    fn resolve_workspace_path(
+        path: &str,
+        orig_root: &Path,
+        tmp_root: &Path,
+    ) -> Result<String, String> {
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return Ok(path.to_string_lossy().into_owned());
+        }
+        let copy = tmp_root.join(path);
+        if copy.join("Cargo.toml").exists() {
+            return Ok(copy.to_string_lossy().into_owned());
+        }
+        canonicalize(&orig_root.join(path))
+            .map(|absolute| absolute.to_string_lossy().into_owned())
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
     fn write_manifest<P: AsRef<Path>>(manifest: &Manifest, path: P) -> CargoResult<()> {
         let mut file = try!(File::create(path));
-        let serialized = ::toml::to_string(manifest).expect("Failed to serialized Cargo.toml");
-        try!(write!(file, "{}", serialized));
+        try!(write!(file, "{}", manifest.to_toml()?));
         Ok(())
     }
 
     fn manipulate_dependencies(manifest: &mut Manifest, f: &Fn(&mut Table)) {
-        manifest.dependencies.as_mut().map(f);
-        manifest.dev_dependencies.as_mut().map(f);
-        manifest.build_dependencies.as_mut().map(f);
-        manifest
-            .target
-            .as_mut()
-            .map(|ref mut t| for target in t.values_mut() {
-                if let Value::Table(ref mut target) = *target {
-                    for dependency_tables in
-                        &["dependencies", "dev-dependencies", "build-dependencies"]
-                    {
-                        target.get_mut(*dependency_tables).map(|dep_table| {
-                            if let Value::Table(ref mut dep_table) = *dep_table {
-                                f(dep_table);
-                            }
-                        });
-                    }
-                }
-            });
+        for dependencies in manifest.dependency_tables_mut() {
+            f(dependencies);
+        }
     }
 
     /// Write manifests with SemVer requirements
     pub fn write_manifest_semver(&'tmp self) -> CargoResult<()> {
-        let root_manifest = format!(
-            "{}/{}",
-            self.temp_dir.path().to_string_lossy(),
-            self.relative_manifest
-        );
-        *self.workspace.borrow_mut() =
-            Some(Workspace::new(Path::new(&root_manifest), &self.config)?);
+        *self.workspace.borrow_mut() = Some(Workspace::new(&self.root_manifest(), &self.config)?);
         Ok(())
     }
 
@@ -195,88 +384,365 @@ impl<'tmp> TempProject<'tmp> {
         manifest_paths: &[PathBuf],
         orig_root: P,
         tmp_root: P,
-    ) -> CargoResult<()> {
+        options: &OutdatedOptions,
+        config: &Config,
+    ) -> CargoResult<HashMap<String, String>> {
         let bin = {
             let mut bin = Table::new();
             bin.insert("name".to_owned(), Value::String("test".to_owned()));
             bin.insert("path".to_owned(), Value::String("test.rs".to_owned()));
             bin
         };
+        let workspace = Self::workspace_table(orig_root.as_ref())?;
+        let workspace_package = workspace
+            .as_ref()
+            .and_then(|w| w.get("package"))
+            .and_then(|package| package.as_table());
+        let workspace_dependencies = workspace
+            .as_ref()
+            .and_then(|w| w.get("dependencies"))
+            .and_then(|dependencies| dependencies.as_table());
+        let mut skipped = HashMap::new();
+        let fetch_with_cli = git_cli::fetches_with_cli(config)?;
+        let mut git_databases = HashMap::new();
+        let resolve_path = |path: &str| {
+            Self::resolve_workspace_path(path, orig_root.as_ref(), tmp_root.as_ref())
+        };
         for manifest_path in manifest_paths {
-            let mut manifest: Manifest = {
-                let mut buf = String::new();
-                let mut file = File::open(manifest_path)?;
-                file.read_to_string(&mut buf)?;
-                ::toml::from_str(&buf)?
-            };
-            manifest.bin = Some(vec![bin.clone()]);
+            let mut manifest = Self::read_manifest(manifest_path)?;
+            // features of newer cargo versions
+            manifest.inherit_package_fields(workspace_package);
+            let inherited_broken =
+                manifest.inherit_dependencies(workspace_dependencies, &resolve_path)?;
+            manifest.normalize_features();
+            manifest.set_bin(bin.clone());
             // provide lib.path
-            manifest.lib.as_mut().map(|lib| {
+            manifest.lib_mut().map(|lib| {
                 lib.insert("path".to_owned(), Value::String("test_lib.rs".to_owned()));
             });
+            // nothing in the temporary project is ever built, with --no-exec
+            // build scripts are removed altogether so that it can't be
+            if options.flag_no_exec {
+                if let Some(package) = manifest.package_mut() {
+                    package.remove("build");
+                }
+            }
+            // targets whose sources are not copied
+            for section in UNUSABLE_SECTIONS {
+                manifest.remove(section);
+            }
+            // the cargo version linked in refuses the whole manifest over a
+            // single feature it doesn't know or can't enable on its channel
+            for feature in manifest.retain_cargo_features(&Self::is_supported_feature) {
+                verbose!(
+                    config,
+                    "Skipping",
+                    format!("cargo feature {} in {}", feature, manifest_path.display())
+                );
+            }
+            if *manifest_path == tmp_root.as_ref().join("Cargo.toml") {
+                Self::replace_override_paths(
+                    &mut manifest,
+                    orig_root.as_ref(),
+                    tmp_root.as_ref(),
+                    manifest_path,
+                    options,
+                    config,
+                )?;
+            } else {
+                for section in OVERRIDE_SECTIONS {
+                    manifest.remove(section);
+                }
+            }
+            let unresolvable = RefCell::new(inherited_broken);
             Self::manipulate_dependencies(&mut manifest, &|deps| {
-                Self::replace_path_with_absolute(
+                let broken = Self::replace_path_with_absolute(
                     deps,
                     orig_root.as_ref(),
                     tmp_root.as_ref(),
                     manifest_path,
-                )
+                );
+                // skipped with a warning below, unless the run is to fail
+                for &(ref name, _) in &broken {
+                    deps.remove(name);
+                }
+                unresolvable.borrow_mut().extend(broken);
+                artifact::strip_artifact_keys(deps);
             });
+            for (name, reason) in unresolvable.into_inner() {
+                if options.flag_broken_paths == "fail" {
+                    return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+                        "path dependency {} can't be resolved: {}",
+                        name, reason
+                    ))));
+                }
+                config.shell().warn(format!(
+                    "skipping path dependency {} which can't be resolved: {}",
+                    name, reason
+                ))?;
+                manifest.remove_feature_references(&name);
+                skipped.insert(name, reason);
+            }
+            // cargo fetches from the local copies with the built-in client,
+            // which never goes over the network then
+            if fetch_with_cli {
+                for deps in manifest.dependency_tables_mut() {
+                    git_cli::use_local_databases(config, deps, &mut git_databases)?;
+                }
+                for overrides in manifest.override_tables_mut() {
+                    git_cli::use_local_databases(config, overrides, &mut git_databases)?;
+                }
+            }
             Self::write_manifest(&manifest, manifest_path)?;
         }
 
+        Ok(skipped)
+    }
+
+    /// Whether the cargo version linked in accepts a feature listed in
+    /// `cargo-features`
+    fn is_supported_feature(feature: &str) -> bool {
+        Features::new(&[feature.to_owned()], &mut vec![]).is_ok()
+    }
+
+    /// Make the relative paths of the `[patch]` and `[replace]` entries of
+    /// the root manifest absolute, as for dependencies, so that the overrides
+    /// apply in the temporary project too
+    ///
+    /// Entries whose path can't be resolved are skipped with a warning,
+    /// unless the run is to fail.
+    fn replace_override_paths(
+        manifest: &mut Manifest,
+        orig_root: &Path,
+        tmp_root: &Path,
+        tmp_manifest: &Path,
+        options: &OutdatedOptions,
+        config: &Config,
+    ) -> CargoResult<()> {
+        let mut unresolvable = vec![];
+        for overrides in manifest.override_tables_mut() {
+            let broken =
+                Self::replace_path_with_absolute(overrides, orig_root, tmp_root, tmp_manifest);
+            for &(ref name, _) in &broken {
+                overrides.remove(name);
+            }
+            unresolvable.extend(broken);
+        }
+        for (name, reason) in unresolvable {
+            if options.flag_broken_paths == "fail" {
+                return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+                    "override of {} can't be resolved: {}",
+                    name, reason
+                ))));
+            }
+            config.shell().warn(format!(
+                "skipping override of {} which can't be resolved: {}",
+                name, reason
+            ))?;
+        }
         Ok(())
     }
 
-    /// Write manifests with wildcard requirements
-    pub fn write_manifest_latest(&'tmp self) -> CargoResult<()> {
+    /// Write manifests with wildcard requirements, except for the crates
+    /// in `within`, which get the requirements given there, with
+    /// `--pre-release`, those whose newest version is a pre-release, and,
+    /// with `--rust-version`, those of crates.io whose newest release needs
+    /// a newer Rust than the `rust-version` of the package
+    pub fn write_manifest_latest(&'tmp self, within: &HashMap<String, String>) -> CargoResult<()> {
         let bin = {
             let mut bin = Table::new();
             bin.insert("name".to_owned(), Value::String("test".to_owned()));
             bin.insert("path".to_owned(), Value::String("test.rs".to_owned()));
             bin
         };
+        let mut index = RegistryIndex::crates_io(&self.config)?;
+        let mut alternatives = HashMap::new();
+        let mut alternative_latest = HashMap::new();
+        let mut unknown_deps = HashSet::new();
+        // `(registry, name)` of the dependencies keeping their requirement
+        let mut kept_deps = HashSet::new();
+        let mut pre_releases = HashMap::new();
+        let mut crates_io = CratesIo::new(&self.config);
         for manifest_path in &self.manifest_paths {
-            let mut manifest: Manifest = {
-                let mut buf = String::new();
-                let mut file = File::open(manifest_path)?;
-                file.read_to_string(&mut buf)?;
-                ::toml::from_str(&buf)?
+            let mut manifest = Self::read_manifest(manifest_path)?;
+            let msrv = if self.rust_version {
+                manifest
+                    .raw
+                    .get("package")
+                    .and_then(|p| p.get("rust-version"))
+                    .and_then(|v| v.as_str())
+                    .and_then(parse_rust_version)
+            } else {
+                None
             };
-            manifest.bin = Some(vec![bin.clone()]);
+            let mut compatible = HashMap::new();
+            manifest.set_bin(bin.clone());
             // provide lib.path
-            manifest.lib.as_mut().map(|lib| {
+            manifest.lib_mut().map(|lib| {
                 lib.insert("path".to_owned(), Value::String("test_lib.rs".to_owned()));
             });
-            Self::manipulate_dependencies(&mut manifest, &Self::replace_version_with_wildcard);
+            // a single crate missing from the index would otherwise
+            // fail the resolution of the whole wildcard workspace
+            let registry_deps = RefCell::new(vec![]);
+            Self::manipulate_dependencies(&mut manifest, &|deps| {
+                let names = Self::registry_dependency_names(deps);
+                registry_deps.borrow_mut().extend(names.into_iter().map(|name| {
+                    let registry = Self::alternative_registry(deps, &name);
+                    (name, registry)
+                }))
+            });
+            for (name, registry) in registry_deps.into_inner() {
+                // the cargo version linked in drops the `registry` key and
+                // would resolve the crate against crates.io, so its latest
+                // version is the newest one of its own index
+                if let Some(registry) = registry {
+                    if !alternatives.contains_key(&registry) {
+                        let alternative = RegistryIndex::alternative(&self.config, &registry)?;
+                        alternatives.insert(registry.clone(), alternative);
+                    }
+                    let alternative = alternatives.get_mut(&registry).unwrap();
+                    let within = within.get(&name).and_then(|req| VersionReq::parse(req).ok());
+                    let newest = alternative
+                        .versions(&name)?
+                        .iter()
+                        .filter(|v| self.pre_release || !v.is_prerelease())
+                        .filter(|v| within.as_ref().map(|req| req.matches(v)).unwrap_or(true))
+                        .max()
+                        .cloned();
+                    alternative_latest.insert(name.clone(), newest);
+                    kept_deps.insert((Some(registry), name));
+                    continue;
+                }
+                if !index.contains(&name)? {
+                    unknown_deps.insert(name.clone());
+                    kept_deps.insert((None, name));
+                    continue;
+                }
+                // a wildcard never matches pre-releases, so a newer one than
+                // any stable release is asked for explicitly
+                if self.pre_release {
+                    if let Some(newest) = index.versions(&name)?.iter().max() {
+                        if newest.is_prerelease() {
+                            pre_releases.insert(name.clone(), format!(">={}", newest));
+                        }
+                    }
+                }
+                // the API knows the `rust-version` of every release, unlike
+                // the index format of the cargo version linked in
+                if let Some(ref msrv) = msrv {
+                    if let Some(newest) =
+                        newest_compatible(&mut crates_io, &name, msrv, self.pre_release)?
+                    {
+                        compatible.insert(name, format!("<={}", newest));
+                    }
+                }
+            }
+            Self::manipulate_dependencies(&mut manifest, &|deps| {
+                Self::replace_version_with_wildcard(deps, &kept_deps);
+                Self::replace_requirements(deps, &pre_releases);
+                Self::replace_requirements(deps, &compatible);
+                Self::replace_requirements(deps, within);
+            });
             Self::write_manifest(&manifest, manifest_path)?;
         }
+        *self.unknown_deps.borrow_mut() = unknown_deps;
+        *self.alternative_latest.borrow_mut() = alternative_latest;
 
-        let root_manifest = format!(
-            "{}/{}",
-            self.temp_dir.path().to_string_lossy(),
-            self.relative_manifest
-        );
-        *self.workspace.borrow_mut() =
-            Some(Workspace::new(Path::new(&root_manifest), &self.config)?);
+        *self.workspace.borrow_mut() = Some(Workspace::new(&self.root_manifest(), &self.config)?);
         Ok(())
     }
 
-    fn replace_version_with_wildcard(dependencies: &mut Table) {
+    /// Write manifests in which the direct dependencies named in
+    /// `requirements` get the given version requirements, leaving all
+    /// other requirements as they are
+    pub fn write_manifest_requirements(
+        &'tmp self,
+        requirements: &HashMap<String, String>,
+    ) -> CargoResult<()> {
+        for manifest_path in &self.manifest_paths {
+            let mut manifest = Self::read_manifest(manifest_path)?;
+            Self::manipulate_dependencies(&mut manifest, &|deps| {
+                Self::replace_requirements(deps, requirements)
+            });
+            Self::write_manifest(&manifest, manifest_path)?;
+        }
+
+        *self.workspace.borrow_mut() = Some(Workspace::new(&self.root_manifest(), &self.config)?);
+        Ok(())
+    }
+
+    fn replace_requirements(dependencies: &mut Table, requirements: &HashMap<String, String>) {
+        for name in Self::registry_dependency_names(dependencies) {
+            let requirement = match requirements.get(&name) {
+                Some(requirement) => Value::String(requirement.clone()),
+                None => continue,
+            };
+            let replaced = match dependencies[&name] {
+                Value::Table(ref t) => {
+                    let mut replaced = t.clone();
+                    replaced.insert("version".to_owned(), requirement);
+                    Value::Table(replaced)
+                }
+                _ => requirement,
+            };
+            dependencies.insert(name, replaced);
+        }
+    }
+
+    /// Names of dependencies which are pulled from a registry, crates.io
+    /// or an alternative one
+    fn registry_dependency_names(dependencies: &Table) -> Vec<String> {
+        dependencies
+            .iter()
+            .filter(|&(_, spec)| match *spec {
+                Value::String(_) => true,
+                Value::Table(ref t) => !t.contains_key("path") && !t.contains_key("git"),
+                _ => false,
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// The alternative registry a dependency is pulled from, given with
+    /// `registry = "name"`, `None` for crates.io
+    fn alternative_registry(dependencies: &Table, name: &str) -> Option<String> {
+        dependencies
+            .get(name)
+            .and_then(|spec| spec.get("registry"))
+            .and_then(|registry| registry.as_str())
+            .map(String::from)
+    }
+
+    /// Replace the requirements with wildcards, except for those of the
+    /// dependencies in `kept_deps`, by registry and name
+    fn replace_version_with_wildcard(
+        dependencies: &mut Table,
+        kept_deps: &HashSet<(Option<String>, String)>,
+    ) {
         let dep_names: Vec<_> = dependencies.keys().cloned().collect();
         for name in dep_names {
+            let key = (Self::alternative_registry(dependencies, &name), name.clone());
+            if kept_deps.contains(&key) {
+                continue;
+            }
             let original = dependencies.get(&name).cloned().unwrap();
             match original {
-                Value::String(_) => {
-                    dependencies.insert(name, Value::String("*".to_owned()));
+                Value::String(ref requirement) => {
+                    dependencies.insert(
+                        name,
+                        Value::String(Self::latest_requirement(requirement)),
+                    );
                 }
                 Value::Table(ref t) => {
                     if t.contains_key("path") {
                         continue;
                     }
                     let mut replaced = t.clone();
-                    if replaced.contains_key("version") {
-                        replaced.insert("version".to_owned(), Value::String("*".to_owned()));
+                    if let Some(Value::String(requirement)) = t.get("version").cloned() {
+                        replaced.insert(
+                            "version".to_owned(),
+                            Value::String(Self::latest_requirement(&requirement)),
+                        );
                     }
                     dependencies.insert(name, Value::Table(replaced));
                 }
@@ -285,12 +751,29 @@ impl<'tmp> TempProject<'tmp> {
         }
     }
 
+    /// The requirement used in place of `requirement` in the wildcard pass
+    ///
+    /// A wildcard never matches pre-releases, so a pre-release requirement
+    /// becomes `>=` the pre-release instead, which accepts both newer
+    /// pre-releases of the same version and any later stable release
+    fn latest_requirement(requirement: &str) -> String {
+        let version = requirement.trim().trim_left_matches(|c: char| "^~=<> ".contains(c));
+        match Version::parse(version) {
+            Ok(ref v) if v.is_prerelease() => format!(">={}", v),
+            _ => "*".to_owned(),
+        }
+    }
+
+    /// Make relative paths of dependencies outside the temporary project
+    /// absolute, returning the dependencies whose path can't be resolved
+    /// along with the reason
     fn replace_path_with_absolute(
         dependencies: &mut Table,
         orig_root: &Path,
         tmp_root: &Path,
         tmp_manifest: &Path,
-    ) {
+    ) -> Vec<(String, String)> {
+        let mut unresolvable = vec![];
         let dep_names: Vec<_> = dependencies.keys().cloned().collect();
         for name in dep_names {
             let original = dependencies.get(&name).cloned().unwrap();
@@ -299,25 +782,27 @@ impl<'tmp> TempProject<'tmp> {
                     if let Value::String(ref orig_path) = t["path"] {
                         let orig_path = Path::new(orig_path);
                         if orig_path.is_relative() {
-                            let relative = {
-                                let delimiter: &[_] = &['/', '\\'];
-                                let relative = &tmp_manifest.to_string_lossy()
-                                    [tmp_root.to_string_lossy().len()..];
-                                let mut relative =
-                                    PathBuf::from(relative.trim_left_matches(delimiter));
-                                relative.pop();
-                                relative.join(orig_path)
-                            };
+                            // the directory of the manifest, relative to the root
+                            let manifest_dir = tmp_manifest
+                                .parent()
+                                .and_then(|dir| dir.strip_prefix(tmp_root).ok())
+                                .unwrap_or_else(|| Path::new(""));
+                            let relative = manifest_dir.join(orig_path);
                             if !tmp_root.join(&relative).join("Cargo.toml").exists() {
+                                let absolute = match canonicalize(&orig_root.join(&relative)) {
+                                    Ok(absolute) => absolute,
+                                    Err(e) => {
+                                        unresolvable.push((
+                                            name.clone(),
+                                            format!("{}: {}", orig_path.display(), e),
+                                        ));
+                                        continue;
+                                    }
+                                };
                                 let mut replaced = t.clone();
                                 replaced.insert(
                                     "path".to_owned(),
-                                    Value::String(
-                                        fs::canonicalize(orig_root.join(relative))
-                                            .unwrap()
-                                            .to_string_lossy()
-                                            .to_string(),
-                                    ),
+                                    Value::String(absolute.to_string_lossy().to_string()),
                                 );
                                 dependencies.insert(name, Value::Table(replaced));
                             }
@@ -327,6 +812,34 @@ impl<'tmp> TempProject<'tmp> {
                 _ => {}
             }
         }
+        unresolvable
+    }
+}
+
+/// A temporary directory removed when dropped, even if the run fails or
+/// panics, unless it is to be kept for debugging with `--keep-temp`
+struct TempDirGuard {
+    dir: Option<TempDir>,
+    keep: bool,
+}
+
+impl TempDirGuard {
+    fn path(&self) -> &Path {
+        self.dir
+            .as_ref()
+            .expect("Temporary directory already removed")
+            .path()
+    }
+}
+
+impl Drop for TempDirGuard {
+    fn drop(&mut self) {
+        if !self.keep {
+            return;
+        }
+        if let Some(dir) = self.dir.take() {
+            dir.into_path();
+        }
     }
 }
 
@@ -338,7 +851,7 @@ fn manifest_paths(elab: &ElaborateWorkspace) -> CargoResult<Vec<PathBuf>> {
     fn manifest_paths_recursive(
         pkg_id: &PackageId,
         elab: &ElaborateWorkspace,
-        workspace_path: &str,
+        workspace_path: &Path,
         visited: &mut HashSet<PackageId>,
         manifest_paths: &mut Vec<PathBuf>,
     ) -> CargoResult<()> {
@@ -347,8 +860,8 @@ fn manifest_paths(elab: &ElaborateWorkspace) -> CargoResult<Vec<PathBuf>> {
         }
         visited.insert(pkg_id.clone());
         let pkg = &elab.pkgs[pkg_id];
-        let pkg_path = pkg.root().to_string_lossy();
-        if pkg_path.starts_with(workspace_path) {
+        // by components, so that /project-fork isn't taken to be in /project
+        if pkg.root().starts_with(workspace_path) {
             manifest_paths.push(pkg.manifest_path().to_owned());
         }
 
@@ -360,14 +873,14 @@ fn manifest_paths(elab: &ElaborateWorkspace) -> CargoResult<Vec<PathBuf>> {
     };
 
     // executed against a virtual manifest
-    let workspace_path = elab.workspace.root().to_string_lossy();
+    let workspace_path = elab.workspace.root();
     // if cargo workspace is not explicitly used, the pacakge itself would be a member
     for member in elab.workspace.members() {
         let root_pkg_id = member.package_id();
         manifest_paths_recursive(
             root_pkg_id,
             elab,
-            &workspace_path,
+            workspace_path,
             &mut visited,
             &mut manifest_paths,
         )?;
@@ -375,3 +888,244 @@ fn manifest_paths(elab: &ElaborateWorkspace) -> CargoResult<Vec<PathBuf>> {
 
     Ok(manifest_paths)
 }
+
+/// `path` relative to `base`, which it is inside of
+///
+/// Both are compared as they are first and canonicalized otherwise, as the
+/// same directory may be given with a `\\?\` prefix or a drive letter in
+/// another case on Windows.
+fn relative_path(path: &Path, base: &Path) -> CargoResult<PathBuf> {
+    if let Ok(relative) = path.strip_prefix(base) {
+        return Ok(relative.to_path_buf());
+    }
+    let path_canonical = canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let base_canonical = canonicalize(base).unwrap_or_else(|_| base.to_path_buf());
+    path_canonical
+        .strip_prefix(&base_canonical)
+        .map(|relative| relative.to_path_buf())
+        .map_err(|_| {
+            CargoError::from_kind(CargoErrorKind::Msg(format!(
+                "{} is not inside the workspace root {}",
+                path.display(),
+                base.display()
+            )))
+        })
+}
+
+/// `fs::canonicalize` without the `\\?\` prefix it puts in front of paths
+/// on a drive on Windows, which the paths of the original workspace lack
+fn canonicalize(path: &Path) -> io::Result<PathBuf> { Ok(strip_verbatim(fs::canonicalize(path)?)) }
+
+/// Drop the `\\?\` prefix of a path on a drive, keeping it on UNC paths
+/// like `\\?\UNC\server\share` which can't do without
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    let stripped = match path.to_str() {
+        Some(s) if s.starts_with(r"\\?\") && s[4..].chars().nth(1) == Some(':') => {
+            Some(PathBuf::from(&s[4..]))
+        }
+        _ => None,
+    };
+    stripped.unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use cargo::core::{EitherManifest, SourceId};
+    use cargo::util::Config;
+    use cargo::util::toml::read_manifest;
+    use tempdir::TempDir;
+    use toml::Value;
+    use toml::value::Table;
+
+    use OutdatedOptions;
+    use super::{canonicalize, relative_path, strip_verbatim, Manifest, TempProject};
+
+    /// Rewrite the manifests of the packages in `dirs` of a fixture under
+    /// `tests/fixtures/manifests` the way the temporary projects get them,
+    /// checking that the cargo version linked in accepts every one
+    fn rewrite_fixture(fixture: &str, dirs: &[&str]) -> Vec<Manifest> {
+        let orig = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("manifests")
+            .join(fixture);
+        let tmp = TempDir::new("cargo-outdated-test").unwrap();
+        let mut manifest_paths = vec![];
+        for dir in dirs {
+            fs::create_dir_all(tmp.path().join(dir)).unwrap();
+            let manifest_path = tmp.path().join(dir).join("Cargo.toml");
+            fs::copy(orig.join(dir).join("Cargo.toml"), &manifest_path).unwrap();
+            manifest_paths.push(manifest_path);
+        }
+        let options = OutdatedOptions::from_args(Vec::<String>::new()).unwrap();
+        let config = Config::default().unwrap();
+        TempProject::write_manifest_semver_with_paths(
+            &manifest_paths,
+            orig.as_path(),
+            tmp.path(),
+            &options,
+            &config,
+        ).unwrap();
+        manifest_paths
+            .iter()
+            .map(|path| {
+                let source_id = SourceId::for_path(path.parent().unwrap()).unwrap();
+                match read_manifest(path, &source_id, &config) {
+                    Ok((EitherManifest::Real(_), _)) => {}
+                    Ok(_) => panic!("{} became a virtual manifest", path.display()),
+                    Err(e) => panic!("{} is rejected: {}", path.display(), e),
+                }
+                TempProject::read_manifest(path).unwrap()
+            })
+            .collect()
+    }
+
+    fn features(manifest: &mut Manifest) -> Value {
+        Value::Table(manifest.table_mut("features").cloned().unwrap_or_default())
+    }
+
+    #[test]
+    fn every_edition_is_accepted() {
+        for fixture in &["edition-2015", "edition-2018", "edition-2021", "edition-2024"] {
+            rewrite_fixture(fixture, &["", "dep"]);
+        }
+    }
+
+    #[test]
+    fn workspace_fields_and_dependencies_are_inherited() {
+        let mut manifests = rewrite_fixture("workspace-inheritance", &["app", "dep"]);
+        let app = &mut manifests[0];
+        let package = app.package_mut().cloned().unwrap();
+        assert_eq!(package["version"].as_str(), Some("0.3.0"));
+        assert_eq!(package["license"].as_str(), Some("MIT"));
+        let dep = app.table_mut("dependencies").unwrap()["dep"].clone();
+        assert_eq!(dep.get("optional").and_then(|o| o.as_bool()), Some(true));
+        assert_eq!(
+            dep.get("features"),
+            Some(&Value::Array(vec![Value::String("extra".to_owned())]))
+        );
+        assert!(dep.get("workspace").is_none());
+    }
+
+    #[test]
+    fn namespaced_and_weak_features_are_rewritten() {
+        let mut manifests = rewrite_fixture("features", &["", "dep", "extras"]);
+        // `dep` is folded into `full`, which enables it
+        let expected: Value = ::toml::from_str(
+            r#"
+            default = ["full"]
+            full = ["dep", "extras/serde", "extras"]
+            std = ["extras/std"]
+            serde = ["extras", "extras/serde"]
+            "#,
+        ).unwrap();
+        assert_eq!(features(&mut manifests[0]), expected);
+    }
+
+    #[test]
+    fn verbatim_prefix_is_stripped_from_drive_paths_only() {
+        assert_eq!(strip_verbatim(PathBuf::from(r"\\?\C:\ws")), PathBuf::from(r"C:\ws"));
+        assert_eq!(strip_verbatim(PathBuf::from(r"\\?\d:\ws")), PathBuf::from(r"d:\ws"));
+        assert_eq!(
+            strip_verbatim(PathBuf::from(r"\\?\UNC\server\share\ws")),
+            PathBuf::from(r"\\?\UNC\server\share\ws")
+        );
+        assert_eq!(
+            strip_verbatim(PathBuf::from(r"\\server\share\ws")),
+            PathBuf::from(r"\\server\share\ws")
+        );
+        assert_eq!(strip_verbatim(PathBuf::from("/ws")), PathBuf::from("/ws"));
+    }
+
+    #[test]
+    fn canonical_paths_have_no_verbatim_prefix() {
+        let dir = TempDir::new("cargo-outdated-test").unwrap();
+        let canonical = canonicalize(dir.path()).unwrap();
+        assert!(canonical.is_absolute());
+        assert!(!canonical.to_string_lossy().starts_with(r"\\?\"));
+        assert_eq!(canonicalize(&canonical).unwrap(), canonical);
+    }
+
+    #[test]
+    fn paths_inside_the_base_are_made_relative() {
+        let dir = TempDir::new("cargo-outdated-test").unwrap();
+        let member = dir.path().join("member");
+        fs::create_dir(&member).unwrap();
+        assert_eq!(relative_path(&member, dir.path()).unwrap(), Path::new("member"));
+        // only one of them canonical
+        let canonical = canonicalize(&member).unwrap();
+        assert_eq!(relative_path(&canonical, dir.path()).unwrap(), Path::new("member"));
+        let dotted = member.join("..");
+        assert_eq!(relative_path(&member, &dotted).unwrap(), Path::new("member"));
+    }
+
+    #[test]
+    fn paths_outside_the_base_are_an_error() {
+        let dir = TempDir::new("cargo-outdated-test").unwrap();
+        let base = dir.path().join("ws");
+        let outside = dir.path().join("outside");
+        fs::create_dir(&base).unwrap();
+        fs::create_dir(&outside).unwrap();
+        assert!(relative_path(&outside, &base).is_err());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn verbatim_and_plain_paths_match() {
+        let dir = TempDir::new("cargo-outdated-test").unwrap();
+        let member = dir.path().join("member");
+        fs::create_dir(&member).unwrap();
+        let verbatim = fs::canonicalize(&member).unwrap();
+        assert!(verbatim.to_string_lossy().starts_with(r"\\?\"));
+        assert_eq!(relative_path(&verbatim, dir.path()).unwrap(), Path::new("member"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn drive_letters_in_another_case_match() {
+        let dir = TempDir::new("cargo-outdated-test").unwrap();
+        let member = dir.path().join("member");
+        fs::create_dir(&member).unwrap();
+        let base = canonicalize(dir.path()).unwrap().to_string_lossy().into_owned();
+        let swapped = match base.chars().next() {
+            Some(c) if c.is_ascii_uppercase() => c.to_ascii_lowercase(),
+            Some(c) => c.to_ascii_uppercase(),
+            None => unreachable!(),
+        };
+        let other_case = PathBuf::from(format!("{}{}", swapped, &base[1..]));
+        assert_eq!(relative_path(&member, &other_case).unwrap(), Path::new("member"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn unc_paths_are_compared_as_they_are() {
+        let base = Path::new(r"\\server\share\ws");
+        let manifest = Path::new(r"\\server\share\ws\member\Cargo.toml");
+        assert_eq!(
+            relative_path(manifest, base).unwrap(),
+            Path::new(r"member\Cargo.toml")
+        );
+        assert!(relative_path(Path::new(r"\\server\other\ws"), base).is_err());
+    }
+
+    #[test]
+    fn kept_requirements_are_keyed_by_registry() {
+        let mut dependencies: Table = ::toml::from_str(
+            r#"
+            foo = "1.0"
+            bar = { version = "0.2", registry = "internal" }
+            "#,
+        ).unwrap();
+        let mut kept = HashSet::new();
+        kept.insert((Some("internal".to_owned()), "foo".to_owned()));
+        kept.insert((Some("internal".to_owned()), "bar".to_owned()));
+        TempProject::replace_version_with_wildcard(&mut dependencies, &kept);
+        // the crates.io `foo` is not the one missing from the internal index
+        assert_eq!(dependencies["foo"].as_str(), Some("*"));
+        assert_eq!(dependencies["bar"]["version"].as_str(), Some("0.2"));
+    }
+}
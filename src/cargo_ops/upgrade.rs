@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use cargo::util::{CargoResult, Config};
+use semver::Version;
+
+use super::inheritance::inherited_dependencies;
+use super::report::Line;
+
+/// Names of the tables listing dependencies
+const DEPENDENCY_TABLES: &[&str] = &[
+    "dependencies",
+    "dev-dependencies",
+    "dev_dependencies",
+    "build-dependencies",
+    "build_dependencies",
+];
+
+/// What the lines of a manifest belong to
+enum Section {
+    /// A table of dependencies, e.g. `[dependencies]`
+    Dependencies,
+    /// The table of a single dependency, e.g. `[dependencies.foo]`
+    Dependency(String),
+    Other,
+}
+
+/// Rewrite the requirements on the direct dependencies of the manifest at
+/// `manifest_path` to the versions `select` picks for their rows, e.g. the
+/// compat or latest ones with `--write`
+///
+/// Requirements inherited from the workspace are rewritten in the root
+/// manifest under `workspace_root`. Only the requirements themselves are
+/// touched, so that comments and formatting are kept. Pinned and complex
+/// requirements, renamed dependencies and rows a policy blocks are left
+/// alone.
+pub fn write_requirements(
+    config: &Config,
+    manifest_path: &Path,
+    workspace_root: &Path,
+    lines: &[Line],
+    select: &mut FnMut(&Line) -> CargoResult<Option<String>>,
+) -> CargoResult<()> {
+    let inherited = inherited_dependencies(manifest_path)?;
+    let mut member = HashMap::new();
+    let mut shared = HashMap::new();
+    for line in lines {
+        if line.name.contains("->") || !line.is_actionable() || upgrades_of(line).is_empty() {
+            continue;
+        }
+        let version = match select(line)? {
+            Some(ref version) if *version == line.project || Version::parse(version).is_err() => {
+                continue
+            }
+            Some(version) => version,
+            None => continue,
+        };
+        if inherited.contains(&line.name) {
+            shared.insert(line.name.clone(), version);
+        } else {
+            member.insert(line.name.clone(), version);
+        }
+    }
+    rewrite_manifest(config, manifest_path, &member)?;
+    rewrite_manifest(config, &workspace_root.join("Cargo.toml"), &shared)
+}
+
+/// Ask on the terminal which version the requirement on the dependency of
+/// `line` should be rewritten to, with `--interactive`
+///
+/// Anything but one of the choices offered skips the dependency.
+pub fn prompt_upgrade(line: &Line) -> CargoResult<Option<String>> {
+    let upgrades = upgrades_of(line);
+    let choices: Vec<_> = upgrades
+        .iter()
+        .map(|&(choice, version)| format!("[{}] {} {}", &choice[..1], choice, version))
+        .collect();
+    let stderr = io::stderr();
+    let mut stderr = stderr.lock();
+    write!(
+        stderr,
+        "{} {}: {}, [s]kip? ",
+        line.name,
+        line.project,
+        choices.join(", ")
+    )?;
+    stderr.flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+    Ok(upgrades
+        .into_iter()
+        .find(|&(choice, _)| !answer.is_empty() && choice.starts_with(&answer))
+        .map(|(_, version)| version.to_owned()))
+}
+
+/// The compat and latest versions of a row which differ from the one in use
+fn upgrades_of(line: &Line) -> Vec<(&'static str, &str)> {
+    let mut upgrades: Vec<(&'static str, &str)> = vec![];
+    for &(choice, version) in &[("compat", &line.compat), ("latest", &line.latest)] {
+        let known = upgrades.iter().any(|&(_, v)| v == version.as_str());
+        if *version != line.project && !known && Version::parse(version).is_ok() {
+            upgrades.push((choice, version));
+        }
+    }
+    upgrades
+}
+
+fn read(path: &Path) -> CargoResult<String> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/// Replace the requirements on `upgrades` in the manifest at `path` line by
+/// line, writing it only if anything changed
+fn rewrite_manifest(
+    config: &Config,
+    path: &Path,
+    upgrades: &HashMap<String, String>,
+) -> CargoResult<()> {
+    if upgrades.is_empty() || !path.is_file() {
+        return Ok(());
+    }
+    let contents = read(path)?;
+    let mut section = Section::Other;
+    let mut changed = false;
+    let mut lines = vec![];
+    for line in contents.split('\n') {
+        let trimmed = line.trim_left();
+        if trimmed.starts_with('[') {
+            section = section_of(&header_keys(trimmed));
+            lines.push(line.to_owned());
+            continue;
+        }
+        let upgraded = split_key(line).and_then(|(key, value)| {
+            let (name, value) = match section {
+                Section::Dependencies => (key, value),
+                Section::Dependency(ref name) if key == "version" => (name.clone(), value),
+                _ => return None,
+            };
+            upgrades
+                .get(&name)
+                .and_then(|version| upgrade_value(value, version))
+                .map(|(upgraded, len)| format!("{}{}", &line[..line.len() - len], upgraded))
+        });
+        match upgraded {
+            Some(upgraded) => {
+                changed = true;
+                lines.push(upgraded);
+            }
+            None => lines.push(line.to_owned()),
+        }
+    }
+    if changed {
+        config.shell().status("Updating", path.display())?;
+        let mut file = File::create(path)?;
+        write!(file, "{}", lines.join("\n"))?;
+    }
+    Ok(())
+}
+
+/// The keys of a table header like `[target.'cfg(unix)'.dependencies]`
+fn header_keys(header: &str) -> Vec<String> {
+    let mut keys = vec![];
+    let mut key = String::new();
+    let mut quote = None;
+    for c in header.trim_left_matches('[').chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => key.push(c),
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '.' => {
+                    keys.push(key.trim().to_owned());
+                    key.clear();
+                }
+                ']' => break,
+                _ => key.push(c),
+            },
+        }
+    }
+    keys.push(key.trim().to_owned());
+    keys
+}
+
+fn section_of(keys: &[String]) -> Section {
+    // `[dependencies]`, `[workspace.dependencies]` and
+    // `[target.<cfg>.dependencies]`, possibly followed by a dependency
+    let prefix = match keys.first().map(|k| k.as_str()) {
+        Some("workspace") => 1,
+        Some("target") => 2,
+        _ => 0,
+    };
+    if keys.len() <= prefix {
+        return Section::Other;
+    }
+    let is_table = |key: &String| DEPENDENCY_TABLES.contains(&key.as_str());
+    match keys.len() - prefix {
+        1 if is_table(&keys[prefix]) => Section::Dependencies,
+        2 if is_table(&keys[prefix]) => Section::Dependency(keys[prefix + 1].clone()),
+        _ => Section::Other,
+    }
+}
+
+/// The key of a line like `foo = "1.0"` along with the rest of the line
+/// after the `=`
+fn split_key(line: &str) -> Option<(String, &str)> {
+    let trimmed = line.trim_left();
+    let (key, rest) = match trimmed.chars().next() {
+        Some(quote) if quote == '"' || quote == '\'' => match trimmed[1..].find(quote) {
+            Some(end) => (&trimmed[1..end + 1], &trimmed[end + 2..]),
+            None => return None,
+        },
+        Some(_) => {
+            let end = trimmed
+                .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+                .unwrap_or_else(|| trimmed.len());
+            (&trimmed[..end], &trimmed[end..])
+        }
+        None => return None,
+    };
+    let rest = rest.trim_left();
+    if key.is_empty() || !rest.starts_with('=') {
+        return None;
+    }
+    Some((key.to_owned(), &rest[1..]))
+}
+
+/// The value of a dependency spec, a requirement or an inline table, with
+/// the requirement upgraded to `version`, along with the length of the
+/// original value
+fn upgrade_value(value: &str, version: &str) -> Option<(String, usize)> {
+    let requirement = if value.trim_left().starts_with('{') {
+        let elsewhere = ["package", "git", "path", "workspace"]
+            .iter()
+            .any(|key| inline_value(value, key).is_some());
+        if elsewhere {
+            return None;
+        }
+        match inline_value(value, "version") {
+            Some(requirement) => requirement,
+            None => return None,
+        }
+    } else {
+        value
+    };
+    let (start, end) = match string_range(requirement) {
+        Some(range) => range,
+        None => return None,
+    };
+    let offset = value.len() - requirement.len();
+    upgrade_requirement(&requirement[start..end], version).map(|upgraded| {
+        let upgraded = format!(
+            "{}{}{}",
+            &value[..offset + start],
+            upgraded,
+            &value[offset + end..]
+        );
+        (upgraded, value.len())
+    })
+}
+
+/// The rest of an inline table after `key =`, if it has that key
+fn inline_value<'a>(table: &'a str, key: &str) -> Option<&'a str> {
+    table
+        .match_indices(key)
+        .filter_map(|(i, _)| {
+            let is_key = table[..i]
+                .chars()
+                .next_back()
+                .map(|c| c == '{' || c == ',' || c.is_whitespace())
+                .unwrap_or(false);
+            let rest = table[i + key.len()..].trim_left();
+            if is_key && rest.starts_with('=') {
+                Some(&rest[1..])
+            } else {
+                None
+            }
+        })
+        .next()
+}
+
+/// Byte range of the contents of the string `s` starts with
+fn string_range(s: &str) -> Option<(usize, usize)> {
+    let start = s.len() - s.trim_left().len();
+    match s[start..].chars().next() {
+        Some(quote) if quote == '"' || quote == '\'' => s[start + 1..]
+            .find(quote)
+            .map(|end| (start + 1, start + 1 + end)),
+        _ => None,
+    }
+}
+
+/// A requirement like `^1.2` on `version`, keeping the operator and as many
+/// components as it had, unless it is pinned, complex or already there
+fn upgrade_requirement(requirement: &str, version: &str) -> Option<String> {
+    let trimmed = requirement.trim();
+    let operator = match trimmed.chars().next() {
+        Some(c) if c == '^' || c == '~' => &trimmed[..1],
+        Some(c) if c.is_digit(10) => "",
+        _ => return None,
+    };
+    let current = trimmed[operator.len()..].trim();
+    if current.contains(|c: char| c == ',' || c == '*' || c == ' ') {
+        return None;
+    }
+    let components = current.split('.').count();
+    let upgraded = if version.contains('-') || components >= 3 {
+        version.to_owned()
+    } else {
+        version.splitn(3, '.').take(components).collect::<Vec<_>>().join(".")
+    };
+    if upgraded == current {
+        None
+    } else {
+        Some(format!("{}{}", operator, upgraded))
+    }
+}
@@ -0,0 +1,819 @@
+use std::path::Path;
+
+use clap::{App, AppSettings, Arg, SubCommand};
+use semver::{Version, VersionReq};
+
+/// The command line interface of `cargo outdated`
+pub fn app() -> App<'static, 'static> {
+    App::new("cargo-outdated")
+        .author("Kevin K. <kbknapp@gmail.com>")
+        .about(ABOUT)
+        .version(concat!("v", crate_version!()))
+        .bin_name("cargo")
+        .settings(&[
+            AppSettings::GlobalVersion,
+            AppSettings::SubcommandRequired,
+        ])
+        .subcommand(outdated_command())
+}
+
+/// What `cargo outdated` does, in a sentence
+pub const ABOUT: &str = "Displays information about project dependency versions";
+
+/// The `outdated` subcommand of [`app`](fn.app.html), with its own
+/// subcommands
+pub fn outdated_command() -> App<'static, 'static> {
+    let args = outdated_args();
+    SubCommand::with_name("outdated")
+        .about(ABOUT)
+        .args(&args)
+        .subcommand(
+            SubCommand::with_name("audit-crate")
+                .about("Checks how current the requirements of a published crate are")
+                .args(&args)
+                .arg(
+                    Arg::with_name("crate")
+                        .help("Name of the crate on crates.io")
+                        .value_name("CRATE")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("crate-version")
+                        .long("version")
+                        .help("Version to check (Defaults to the newest release)")
+                        .value_name("VERSION")
+                        .validator(is_version),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("batch")
+                .about("Analyzes many projects and prints a combined JSON report")
+                .args(&args)
+                .arg(
+                    Arg::with_name("projects")
+                        .help(
+                            "File listing one project per line, or a directory \
+                             to discover projects under",
+                        )
+                        .value_name("PROJECTS")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("completions")
+                .about("Prints a completion script for a shell")
+                .arg(
+                    Arg::with_name("shell")
+                        .help("Shell to complete cargo outdated in")
+                        .value_name("SHELL")
+                        .required(true)
+                        .possible_values(&["bash", "fish", "zsh", "powershell"]),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("consolidate")
+                .about("Consolidates diverging requirements into [workspace.dependencies]")
+                .args(&args)
+                .arg(
+                    Arg::with_name("apply")
+                        .long("apply")
+                        .help("Write the entries and make the members inherit them"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff-crate")
+                .about("Compares the dependencies of two published versions of a crate")
+                .args(&args)
+                .arg(
+                    Arg::with_name("crate")
+                        .help("Name of the crate on crates.io")
+                        .value_name("CRATE")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .help("Version to compare from")
+                        .value_name("FROM")
+                        .required(true)
+                        .validator(is_version),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .help("Version to compare to")
+                        .value_name("TO")
+                        .required(true)
+                        .validator(is_version),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("explain")
+                .about("Explains the status of a single dependency")
+                .args(&args)
+                .arg(
+                    Arg::with_name("crate")
+                        .help("Name of the dependency to explain")
+                        .value_name("CRATE")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("self-version")
+                .about("Compares the versions of the members with their releases")
+                .args(&args),
+        )
+        .subcommand(
+            SubCommand::with_name("tree")
+                .about("Displays the dependency tree with outdated packages annotated")
+                .args(&args),
+        ),
+}
+
+/// Arguments shared by `cargo outdated` and its subcommands
+fn outdated_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("color")
+            .long("color")
+            .help("Coloring: auto, always, never")
+            .takes_value(true)
+            .number_of_values(1)
+            .possible_values(&["auto", "always", "never"])
+            .default_value("auto"),
+        Arg::with_name("features")
+            .long("features")
+            .help("Space-separated list of features")
+            .takes_value(true)
+            .value_name("FEATURE")
+            .value_delimiter(" ")
+            .empty_values(true)
+            .conflicts_with("all-features"),
+        Arg::with_name("all-features")
+            .long("all-features")
+            .help("Activate all features (Default unless features are chosen)"),
+        Arg::with_name("no-default-features")
+            .long("no-default-features")
+            .help("Do not activate the default features")
+            .conflicts_with("all-features"),
+        Arg::with_name("packages")
+            .long("packages")
+            .short("p")
+            .help("Only report on these dependencies, direct or transitive")
+            .takes_value(true)
+            .value_name("PKG")
+            .value_delimiter(" ")
+            .multiple(true),
+        Arg::with_name("root")
+            .long("root")
+            .short("r")
+            .help("Package to treat as the root package")
+            .takes_value(true)
+            .value_name("ROOT")
+            .number_of_values(1),
+        Arg::with_name("verbose")
+            .long("verbose")
+            .short("v")
+            .help("Use verbose output")
+            .multiple(true),
+        Arg::with_name("quiet")
+            .long("quiet")
+            .short("q")
+            .help("No output printed to stderr but errors")
+            .conflicts_with("verbose"),
+        Arg::with_name("depth")
+            .long("depth")
+            .short("d")
+            .long_help(
+                "How deep in the dependency chain to search \
+                 (Defaults to the root dependencies, see --transitive)",
+            )
+            .takes_value(true)
+            .value_name("NUM")
+            .number_of_values(1),
+        Arg::with_name("exit-code")
+            .long("exit-code")
+            .help("The exit code to return on new versions found")
+            .takes_value(true)
+            .value_name("NUM")
+            .number_of_values(1)
+            .default_value("0"),
+        Arg::with_name("broken-paths")
+            .long("broken-paths")
+            .help(
+                "What to do with path dependencies which can't be resolved, \
+                 skip them with a warning or fail",
+            )
+            .takes_value(true)
+            .value_name("MODE")
+            .possible_values(&["skip", "fail"])
+            .default_value("skip"),
+        Arg::with_name("aggressive")
+            .long("aggressive")
+            .help("Also update the dependencies of the crate cargo update is run for"),
+        Arg::with_name("precise")
+            .long("precise")
+            .help("Resolve a crate to this version in the compat workspace, e.g. 'foo:1.2.3'")
+            .takes_value(true)
+            .value_name("CRATE:VERSION")
+            .number_of_values(1)
+            .validator(is_crate_version),
+        Arg::with_name("keep-temp")
+            .long("keep-temp")
+            .help(
+                "Keep the temporary projects the resolution runs in, printing their \
+                 paths, for debugging",
+            ),
+        Arg::with_name("no-cache")
+            .long("no-cache")
+            .help("Resolve again even if the workspace is unchanged since a previous run"),
+        Arg::with_name("cache-ttl")
+            .long("cache-ttl")
+            .help("How long cached reports are used for, e.g. 1h, 0 not to use them")
+            .takes_value(true)
+            .value_name("DURATION")
+            .default_value("10m")
+            .number_of_values(1)
+            .validator(is_duration),
+        Arg::with_name("offline")
+            .long("offline")
+            .help("Resolve against the local copy of the crates.io index only"),
+        Arg::with_name("locked")
+            .long("locked")
+            .help("Require Cargo.lock to be up to date with the manifests"),
+        Arg::with_name("frozen")
+            .long("frozen")
+            .help("Require Cargo.lock to be up to date, and resolve offline (Implies --locked)"),
+        Arg::with_name("check-lockfile")
+            .long("check-lockfile")
+            .long_help(
+                "Only report the dependencies Cargo.lock holds back, i.e. those \
+                 cargo update would upgrade within the requirements",
+            )
+            .conflicts_with("installed"),
+        Arg::with_name("no-table")
+            .long("no-table")
+            .help("Print the rows as plain tab-separated values rather than a table"),
+        Arg::with_name("summary")
+            .long("summary")
+            .long_help(
+                "Print the numbers of dependencies up to date and behind by a patch, \
+                 minor or major version rather than the rows",
+            ),
+        Arg::with_name("no-exec")
+            .long("no-exec")
+            .help(
+                "Never run third-party code (build scripts are removed \
+                 from the temporary manifests)",
+            ),
+        Arg::with_name("patched-as-current")
+            .long("patched-as-current")
+            .long_help(
+                "Regard crates overridden in the [patch] section, e.g. by a git \
+                 fork carrying a fix, as up to date",
+            ),
+        Arg::with_name("pre-release")
+            .long("pre-release")
+            .help("Also consider pre-releases as latest versions, e.g. 2.0.0-beta.1"),
+        Arg::with_name("root-deps-only")
+            .long("root-deps-only")
+            .short("R")
+            .help("Only check root dependencies, the default (Equivalent to --depth=1)")
+            .conflicts_with("depth"),
+        Arg::with_name("transitive")
+            .long("transitive")
+            .help("Check the transitive dependencies too (Equivalent to leaving out --depth)")
+            .conflicts_with_all(&["depth", "root-deps-only"]),
+        Arg::with_name("rust-version")
+            .long("rust-version")
+            .long_help(
+                "Report the newest versions supporting the rust-version of the \
+                 package as latest, marking newer ones needing a newer Rust",
+            ),
+        Arg::with_name("manifest-path")
+            .long("manifest-path")
+            .short("m")
+            .long_help(
+                "An absolute path to the Cargo.toml file to use \
+                 (Defaults to Cargo.toml in project root)",
+            )
+            .takes_value(true)
+            .value_name("PATH")
+            .number_of_values(1)
+            .validator(is_file),
+        Arg::with_name("why")
+            .long("why")
+            .help("Add a column with the chain of packages pulling in each dependency"),
+        Arg::with_name("duplicates-only")
+            .long("duplicates-only")
+            .long_help(
+                "Only list the crates present in several versions in the dependency \
+                 graph, every version of them, up to date or not",
+            ),
+        Arg::with_name("workspace")
+            .long("workspace")
+            .short("w")
+            .long_help(
+                "Check updates for all workspace members \
+                 rather than only the root package",
+            ),
+        Arg::with_name("include-submodules")
+            .long("include-submodules")
+            .help("Also check the workspaces inside git submodules"),
+        Arg::with_name("all-targets-platforms")
+            .long("all-targets-platforms")
+            .long_help(
+                "Include the dependencies of every [target] table rather than \
+                 only those which apply to the host platform",
+            ),
+        Arg::with_name("target")
+            .long("target")
+            .help("Only include the dependencies which apply to this platform rather than the host")
+            .takes_value(true)
+            .value_name("TRIPLE")
+            .number_of_values(1)
+            .conflicts_with("all-targets-platforms"),
+        Arg::with_name("installed")
+            .long("installed")
+            .help("Check the packages installed with cargo install instead"),
+        Arg::with_name("include-standalone")
+            .long("include-standalone")
+            .long_help(
+                "Also check the packages under the workspace root which are \
+                 not members, e.g. examples, tools or fuzz targets",
+            ),
+        Arg::with_name("effort")
+            .long("effort")
+            .help("Add a column with a rough estimate of the work each upgrade takes"),
+        Arg::with_name("edition-gap")
+            .long("edition-gap")
+            .help(
+                "Mark latest versions written in an edition newer than any in the \
+                 dependency graph",
+            ),
+        Arg::with_name("series")
+            .long("series")
+            .help("Add a column with the newest release of the major series in use"),
+        Arg::with_name("show-dates")
+            .long("show-dates")
+            .help("Add columns with the age of the version in use and the latest's release date"),
+        Arg::with_name("show-links")
+            .long("show-links")
+            .long_help(
+                "Add a column with the repository of each crate, or a URL comparing \
+                 the version in use with the latest on GitHub",
+            ),
+        Arg::with_name("sort")
+            .long("sort")
+            .help("Order of the rows, effort puts the hardest upgrades first")
+            .takes_value(true)
+            .value_name("KEY")
+            .possible_values(&["name", "effort"])
+            .default_value("name"),
+        Arg::with_name("no-sort")
+            .long("no-sort")
+            .help(
+                "Keep the rows in the order the dependency graph is walked in rather \
+                 than sorting them",
+            ),
+        Arg::with_name("include-members")
+            .long("include-members")
+            .long_help(
+                "List workspace members other members depend on, compared against \
+                 their releases on crates.io",
+            )
+            .conflicts_with("exclude-members-as-deps"),
+        Arg::with_name("exclude-members-as-deps")
+            .long("exclude-members-as-deps")
+            .help("Never list workspace members as dependencies of other members"),
+        Arg::with_name("unpublished-members")
+            .long("unpublished-members")
+            .long_help(
+                "How --include-members treats members without a release: hide them, \
+                 show them with ---, or compare them like published ones",
+            )
+            .takes_value(true)
+            .value_name("MODE")
+            .possible_values(&["hide", "show", "compare"])
+            .default_value("compare"),
+        Arg::with_name("also")
+            .long("also")
+            .long_help(
+                "Also check the project at this path, or the projects under it, \
+                 e.g. independent crates with their own lock files in examples/",
+            )
+            .takes_value(true)
+            .value_name("PATH")
+            .number_of_values(1)
+            .multiple(true),
+        Arg::with_name("ignore")
+            .long("ignore")
+            .help("Dependencies to leave out of the report and the exit code")
+            .takes_value(true)
+            .value_name("CRATE")
+            .value_delimiter(",")
+            .number_of_values(1)
+            .multiple(true),
+        Arg::with_name("member")
+            .long("member")
+            .help("Workspace members to check, implying --workspace")
+            .takes_value(true)
+            .value_name("NAME")
+            .value_delimiter(",")
+            .number_of_values(1)
+            .multiple(true),
+        Arg::with_name("only")
+            .long("only")
+            .help(
+                "Only report updates of these severities, or yanked for the \
+                 dependencies whose version in use was yanked",
+            )
+            .takes_value(true)
+            .value_name("SEVERITY")
+            .possible_values(&["major", "minor", "patch", "yanked"])
+            .value_delimiter(",")
+            .number_of_values(1)
+            .multiple(true),
+        Arg::with_name("kind")
+            .long("kind")
+            .long_help(
+                "Only report the dependencies of these kinds, along with \
+                 what they pull in",
+            )
+            .takes_value(true)
+            .value_name("KIND")
+            .possible_values(&["normal", "dev", "build"])
+            .value_delimiter(",")
+            .number_of_values(1)
+            .multiple(true),
+        Arg::with_name("porcelain")
+            .long("porcelain")
+            .help("Print the stable format for scripts (Equivalent to --format=porcelain)"),
+        Arg::with_name("format")
+            .long("format")
+            .help("Output format")
+            .takes_value(true)
+            .value_name("FORMAT")
+            .possible_values(&[
+                "list",
+                "json",
+                "jsonl",
+                "porcelain",
+                "markdown",
+                "html",
+                "dot",
+                "badge",
+            ])
+            .default_value("list"),
+        Arg::with_name("exec")
+            .long("exec")
+            .long_help(
+                "Run a command once per outdated dependency, substituting {name}, \
+                 {current}, {compat}, {latest}, {kind}, {severity} and {root}",
+            )
+            .takes_value(true)
+            .value_name("CMD")
+            .number_of_values(1),
+        Arg::with_name("impact")
+            .long("impact")
+            .long_help(
+                "Show which other packages change if only this one is \
+                 upgraded to its latest version",
+            )
+            .takes_value(true)
+            .value_name("CRATE"),
+        Arg::with_name("what-if")
+            .long("what-if")
+            .long_help(
+                "Resolve as if the requirement on a direct dependency were REQ, \
+                 showing what changes or conflicts, e.g. 'foo=2.1'",
+            )
+            .takes_value(true)
+            .value_name("CRATE=REQ")
+            .validator(is_crate_requirement)
+            .conflicts_with("impact"),
+        Arg::with_name("within")
+            .long("within")
+            .long_help(
+                "Report the newest version of a crate satisfying a requirement \
+                 as its latest one, e.g. 'foo:^1' to stay on the 1.x series",
+            )
+            .takes_value(true)
+            .value_name("CRATE:REQ")
+            .validator(is_crate_constraint)
+            .number_of_values(1)
+            .multiple(true),
+        Arg::with_name("output")
+            .long("output")
+            .long_help(
+                "Write the report to this file rather than stdout, replacing the file \
+                 only once the report is complete",
+            )
+            .takes_value(true)
+            .value_name("FILE")
+            .number_of_values(1)
+            .validator(is_file),
+        Arg::with_name("since")
+            .long("since")
+            .long_help(
+                "Only print the changes since a report previously saved \
+                 with --format json",
+            )
+            .takes_value(true)
+            .value_name("REPORT")
+            .number_of_values(1),
+        Arg::with_name("save-baseline")
+            .long("save-baseline")
+            .long_help(
+                "Save the report as a baseline for --compare-baseline, in the \
+                 format of --format json",
+            )
+            .takes_value(true)
+            .value_name("FILE")
+            .number_of_values(1),
+        Arg::with_name("compare-baseline")
+            .long("compare-baseline")
+            .long_help(
+                "Only count the dependencies outdated since a baseline saved \
+                 with --save-baseline towards the exit code",
+            )
+            .takes_value(true)
+            .value_name("FILE")
+            .number_of_values(1),
+        Arg::with_name("write")
+            .long("write")
+            .long_help(
+                "Rewrite the requirements on the direct dependencies in the manifests \
+                 to their compat or latest versions",
+            )
+            .takes_value(true)
+            .value_name("VERSIONS")
+            .possible_values(&["compat", "latest"])
+            .conflicts_with("installed"),
+        Arg::with_name("interactive")
+            .long("interactive")
+            .short("i")
+            .help("Choose per dependency whether to rewrite its requirement to compat or latest")
+            .conflicts_with_all(&["write", "installed"]),
+        Arg::with_name("git")
+            .long("git")
+            .help("Check git dependencies for newer commits and tags, contacting their remotes"),
+        Arg::with_name("graph-changes")
+            .long("graph-changes")
+            .long_help(
+                "List the crates upgrading each direct dependency to its latest \
+                 version would add to or remove from the dependency graph",
+            ),
+        Arg::with_name("allow-license")
+            .long("allow-license")
+            .long_help(
+                "Only allow upgrades to versions under these licenses, \
+                 e.g. 'MIT' or 'Apache-2.0*'",
+            )
+            .takes_value(true)
+            .value_name("LICENSE")
+            .number_of_values(1)
+            .multiple(true),
+        Arg::with_name("deny-license")
+            .long("deny-license")
+            .long_help(
+                "Fail if upgrading to the latest version of a dependency \
+                 pulls in one of these licenses, e.g. 'GPL-3.0*'",
+            )
+            .takes_value(true)
+            .value_name("LICENSE")
+            .number_of_values(1)
+            .multiple(true),
+        Arg::with_name("api-diff")
+            .long("api-diff")
+            .long_help(
+                "Count the public items major upgrades of direct dependencies \
+                 remove or change, from rustdoc JSON",
+            ),
+        Arg::with_name("check-publishers")
+            .long("check-publishers")
+            .long_help(
+                "Flag latest versions published by someone who published \
+                 none of the earlier versions",
+            ),
+        Arg::with_name("owners")
+            .long("owners")
+            .help("Add a column with the owners of the crates on crates.io"),
+        Arg::with_name("security-only")
+            .long("security-only")
+            .long_help(
+                "Only count outdated dependencies whose upgrade fixes a known \
+                 security advisory towards the exit code",
+            ),
+        Arg::with_name("vulnerabilities")
+            .long("vulnerabilities")
+            .help("Flag dependencies with known security advisories against the version in use"),
+        Arg::with_name("vulnerable-only")
+            .long("vulnerable-only")
+            .long_help(
+                "Only list the dependencies with known security advisories \
+                 (Implies --vulnerabilities)",
+            ),
+        Arg::with_name("stats")
+            .long("stats")
+            .help("Print staleness statistics, broken down by dependency kind"),
+        Arg::with_name("timeout")
+            .long("timeout")
+            .help("Timeout of network requests, e.g. 30s or 2m")
+            .takes_value(true)
+            .value_name("DURATION")
+            .number_of_values(1)
+            .validator(is_duration),
+        Arg::with_name("watch")
+            .long("watch")
+            .long_help(
+                "Run again every INTERVAL, e.g. 5m, and whenever a manifest or the \
+                 lock file changes, until interrupted (--exit-code has no effect)",
+            )
+            .takes_value(true)
+            .value_name("INTERVAL")
+            .number_of_values(1)
+            .validator(is_interval)
+            .conflicts_with_all(&["write", "interactive"]),
+        Arg::with_name("strict")
+            .long("strict")
+            .long_help(
+                "Fail as soon as a member, dependency or project can't be analyzed \
+                 rather than reporting it and carrying on with the others",
+            ),
+        Arg::with_name("allow-network-failure")
+            .long("allow-network-failure")
+            .long_help(
+                "If the latest versions can't be looked up, report the lock file with \
+                 the latest versions unknown and exit with code 2",
+            ),
+        Arg::with_name("cargo-home")
+            .long("cargo-home")
+            .help("Cargo home to use instead of $CARGO_HOME, including the registry index")
+            .takes_value(true)
+            .value_name("DIR")
+            .number_of_values(1),
+        Arg::with_name("cache-dir")
+            .long("cache-dir")
+            .help(
+                "Directory to keep downloads like rustdoc JSON in [default: \
+                 $CARGO_OUTDATED_CACHE_DIR or ~/.cache/cargo-outdated]",
+            )
+            .takes_value(true)
+            .value_name("DIR")
+            .number_of_values(1),
+        Arg::with_name("temp-dir")
+            .long("temp-dir")
+            .help("Directory to create the temporary projects in [default: $TMPDIR]")
+            .takes_value(true)
+            .value_name("DIR")
+            .number_of_values(1),
+        Arg::with_name("log-level")
+            .long("log-level")
+            .help("Log messages up to this level to stderr, RUST_LOG refines it per module")
+            .takes_value(true)
+            .value_name("LEVEL")
+            .possible_values(&["error", "warn", "info", "debug", "trace"]),
+        Arg::with_name("retry")
+            .long("retry")
+            .help("Number of times a failed network request is retried")
+            .takes_value(true)
+            .value_name("NUM")
+            .number_of_values(1),
+        Arg::with_name("zero-major-as-breaking")
+            .long("zero-major-as-breaking")
+            .long_help(
+                "Classify 0.x -> 0.y updates as major like cargo does \
+                 when checking for compatibility",
+            )
+            .takes_value(true)
+            .value_name("BOOL")
+            .possible_values(&["true", "false"])
+            .default_value("true"),
+        // for packagers, who'd rather not keep a copy of the page up to date
+        Arg::with_name("generate-man")
+            .long("generate-man")
+            .help("Print a man page generated from this help")
+            .hidden(true),
+    ]
+}
+
+#[allow(unknown_lints)]
+#[allow(needless_pass_by_value)]
+fn is_file(s: String) -> Result<(), String> {
+    let p = Path::new(&*s);
+    if p.file_name().is_none() {
+        return Err(format!("'{}' doesn't appear to be a valid file name", &*s));
+    }
+    Ok(())
+}
+
+#[allow(unknown_lints)]
+#[allow(needless_pass_by_value)]
+fn is_crate_requirement(s: String) -> Result<(), String> {
+    parse_crate_requirement(&s, '=').map(|_| ())
+}
+
+#[allow(unknown_lints)]
+#[allow(needless_pass_by_value)]
+fn is_crate_constraint(s: String) -> Result<(), String> {
+    parse_crate_requirement(&s, ':').map(|_| ())
+}
+
+#[allow(unknown_lints)]
+#[allow(needless_pass_by_value)]
+fn is_crate_version(s: String) -> Result<(), String> { parse_crate_version(&s).map(|_| ()) }
+
+#[allow(unknown_lints)]
+#[allow(needless_pass_by_value)]
+fn is_version(s: String) -> Result<(), String> {
+    Version::parse(&s)
+        .map(|_| ())
+        .map_err(|e| format!("'{}' is not a valid version: {}", s, e))
+}
+
+/// Parse a crate name along with a version requirement, like `foo=2.1`
+/// or `foo:^1`, depending on the `separator`
+pub fn parse_crate_requirement(s: &str, separator: char) -> Result<(String, String), String> {
+    let mut parts = s.splitn(2, separator);
+    let (name, req) = match (parts.next(), parts.next()) {
+        (Some(name), Some(req)) if !name.trim().is_empty() => (name.trim(), req.trim()),
+        _ => {
+            return Err(format!(
+                "'{}' doesn't look like CRATE{}REQ, e.g. 'foo{}2.1'",
+                s, separator, separator
+            ))
+        }
+    };
+    VersionReq::parse(req)
+        .map(|_| (name.to_owned(), req.to_owned()))
+        .map_err(|e| format!("'{}' is not a valid requirement: {}", req, e))
+}
+
+/// Parse a crate name along with an exact version, like `foo:1.2.3`
+pub fn parse_crate_version(s: &str) -> Result<(String, String), String> {
+    let mut parts = s.splitn(2, ':');
+    match (parts.next(), parts.next()) {
+        (Some(name), Some(version)) if !name.trim().is_empty() => Version::parse(version.trim())
+            .map(|_| (name.trim().to_owned(), version.trim().to_owned()))
+            .map_err(|e| format!("'{}' is not a valid version: {}", version, e)),
+        _ => Err(format!(
+            "'{}' doesn't look like CRATE:VERSION, e.g. 'foo:1.2.3'",
+            s
+        )),
+    }
+}
+
+#[allow(unknown_lints)]
+#[allow(needless_pass_by_value)]
+fn is_duration(s: String) -> Result<(), String> { parse_duration(&s).map(|_| ()) }
+
+/// A duration of at least a second, for intervals
+#[allow(unknown_lints)]
+#[allow(needless_pass_by_value)]
+fn is_interval(s: String) -> Result<(), String> {
+    match parse_duration(&s)? {
+        0 => Err(format!("'{}' is too short an interval, use at least 1s", s)),
+        _ => Ok(()),
+    }
+}
+
+/// Parse durations like `30`, `30s` or `2m` into seconds
+pub fn parse_duration(s: &str) -> Result<u64, String> {
+    let (number, unit) = match s.find(|c: char| !c.is_digit(10)) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => return Err(format!("'{}' has an unknown unit, use s, m or h", s)),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("'{}' doesn't appear to be a valid duration", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_interval, parse_duration};
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("30"), Ok(30));
+        assert_eq!(parse_duration("30s"), Ok(30));
+        assert_eq!(parse_duration("2m"), Ok(120));
+        assert_eq!(parse_duration("1h"), Ok(3600));
+        assert!(parse_duration("1d").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("18446744073709551615h").is_err());
+    }
+
+    #[test]
+    fn intervals_are_not_zero() {
+        assert!(is_interval("0".to_owned()).is_err());
+        assert!(is_interval("0s".to_owned()).is_err());
+        assert!(is_interval("x".to_owned()).is_err());
+        assert!(is_interval("1s".to_owned()).is_ok());
+    }
+}
@@ -0,0 +1,1542 @@
+//! The analysis behind `cargo outdated`, for tools which would rather embed
+//! it than parse its output
+//!
+//! ```no_run
+//! extern crate cargo_outdated;
+//!
+//! use cargo_outdated::{analyze_workspace, OutdatedOptions};
+//!
+//! fn main() {
+//!     let options = OutdatedOptions::from_args(vec!["--workspace", "--depth", "1"]).unwrap();
+//!     let config = options.config().unwrap();
+//!     let outdated = analyze_workspace(&options, &config).unwrap();
+//!     for report in &outdated.reports {
+//!         for line in &report.dependencies {
+//!             println!("{}: {} -> {}", line.name, line.project, line.latest);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The reports print in the formats of `--format` with the implementations
+//! of [`Printer`](trait.Printer.html), which tools can add their own to.
+//!
+//! The binary is a thin command line interface over [`app`](fn.app.html)
+//! and [`execute`](fn.execute.html).
+
+extern crate atty;
+extern crate cargo;
+#[macro_use]
+extern crate clap;
+extern crate flate2;
+extern crate git2;
+#[macro_use]
+extern crate log;
+extern crate semver;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate tabwriter;
+extern crate tempdir;
+#[cfg(feature = "debug")]
+extern crate termcolor;
+extern crate toml;
+
+#[macro_use]
+mod macros;
+mod cargo_ops;
+mod cli;
+mod man;
+use cargo_ops::{annotate_breaking_changes, annotate_dates, annotate_editions, annotate_effort,
+                annotate_licenses, annotate_links, annotate_newest_releases, annotate_owners,
+                annotate_publishers, annotate_rust_versions, annotate_series, cache_dir,
+                describe_age, discovery, exec_per_dependency, fetch_locked_sources, git_lines,
+                installed_report, newest_edition, prompt_upgrade, report, stale_index_secs,
+                write_atomically, write_requirements, AdvisoryDatabase, BatchReport,
+                ConsolidationReport, CrateAudit, CrateDiff, CratesIo, Defaults, ElaborateWorkspace,
+                Impact, LicensePolicy, LocalAnnotations, ProjectConfig, RegistryIndex, ReportCache,
+                ReportDiff, RustVersions, RustdocJson, SelfVersionReport, Stats, TempProject};
+use cli::{outdated_command, parse_crate_requirement, parse_crate_version, parse_duration, ABOUT};
+pub use cargo_ops::Report;
+pub use cli::app;
+pub use cargo_ops::report::{GraphChange, Line};
+pub use cargo_ops::printer::{BadgePrinter, CountsPrinter, HtmlPrinter, JsonPrinter, JsonlPrinter,
+                             ListPrinter, MarkdownPrinter, PorcelainPrinter, Printer, TsvPrinter};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use cargo::core::Workspace;
+use cargo::util::important_paths::find_root_manifest_for_wd;
+use cargo::util::{homedir, CargoError, CargoErrorKind, CargoResult, Config, ConfigValue};
+use cargo::util::errors::CargoResultExt;
+use cargo::core::shell::Verbosity;
+use clap::{ArgMatches, Shell};
+
+/// Options of an analysis, as given on the command line
+#[derive(Deserialize, Debug, Clone)]
+pub struct OutdatedOptions {
+    cmd_audit_crate: bool,
+    cmd_batch: bool,
+    cmd_completions: bool,
+    cmd_consolidate: bool,
+    cmd_diff_crate: bool,
+    cmd_explain: bool,
+    cmd_self_version: bool,
+    cmd_tree: bool,
+    arg_crate: Option<String>,
+    arg_crate_version: Option<String>,
+    arg_from: Option<String>,
+    arg_to: Option<String>,
+    arg_projects: Option<String>,
+    arg_apply: bool,
+    arg_shell: Option<String>,
+    flag_color: Option<String>,
+    flag_features: Vec<String>,
+    flag_all_features: bool,
+    flag_no_default_features: bool,
+    flag_manifest_path: Option<String>,
+    flag_quiet: Option<bool>,
+    flag_verbose: u32,
+    flag_frozen: bool,
+    flag_locked: bool,
+    flag_check_lockfile: bool,
+    flag_no_exec: bool,
+    flag_keep_temp: bool,
+    flag_no_cache: bool,
+    flag_cache_ttl: u64,
+    flag_aggressive: bool,
+    flag_precise: Option<(String, String)>,
+    flag_offline: bool,
+    flag_exec: Option<String>,
+    flag_broken_paths: String,
+    flag_patched_as_current: bool,
+    flag_exit_code: i32,
+    flag_packages: Vec<String>,
+    flag_root: Option<String>,
+    flag_depth: i32,
+    flag_workspace: bool,
+    flag_members: Vec<String>,
+    flag_all_targets_platforms: bool,
+    flag_target: Option<String>,
+    flag_installed: bool,
+    flag_include_submodules: bool,
+    flag_include_standalone: bool,
+    flag_include_members: bool,
+    flag_exclude_members_as_deps: bool,
+    flag_unpublished_members: String,
+    flag_also: Vec<String>,
+    flag_ignore: Vec<String>,
+    flag_only: Vec<String>,
+    flag_kind: Vec<String>,
+    flag_why: bool,
+    flag_duplicates_only: bool,
+    flag_no_table: bool,
+    flag_summary: bool,
+    flag_pre_release: bool,
+    flag_rust_version: bool,
+    flag_zero_major_as_breaking: bool,
+    flag_timeout: Option<u64>,
+    flag_watch: Option<u64>,
+    flag_retry: Option<u32>,
+    flag_cargo_home: Option<String>,
+    flag_cache_dir: Option<String>,
+    flag_temp_dir: Option<String>,
+    flag_log_level: Option<String>,
+    flag_stats: bool,
+    flag_api_diff: bool,
+    flag_check_publishers: bool,
+    flag_owners: bool,
+    flag_security_only: bool,
+    flag_vulnerabilities: bool,
+    flag_vulnerable_only: bool,
+    flag_allow_license: Vec<String>,
+    flag_deny_license: Vec<String>,
+    flag_graph_changes: bool,
+    flag_git: bool,
+    flag_effort: bool,
+    flag_series: bool,
+    flag_show_dates: bool,
+    flag_show_links: bool,
+    flag_edition_gap: bool,
+    flag_sort: String,
+    flag_no_sort: bool,
+    flag_impact: Option<String>,
+    flag_what_if: Option<(String, String)>,
+    flag_within: HashMap<String, String>,
+    flag_allow_network_failure: bool,
+    flag_strict: bool,
+    flag_format: String,
+    flag_output: Option<String>,
+    flag_since: Option<String>,
+    flag_save_baseline: Option<String>,
+    flag_compare_baseline: Option<String>,
+    flag_write: Option<String>,
+    flag_interactive: bool,
+    flag_generate_man: bool,
+    /// Configuration of the project, along with the root of its workspace,
+    /// as loaded by `apply_project_defaults`
+    #[serde(skip)]
+    project_config: Option<(PathBuf, ProjectConfig)>,
+}
+
+impl OutdatedOptions {
+    /// Options from the arguments of `cargo outdated`, e.g.
+    /// `vec!["--workspace", "--depth", "1"]`
+    ///
+    /// Unlike on the command line, the defaults of the project configuration
+    /// are not applied.
+    pub fn from_args<I, T>(args: I) -> Result<OutdatedOptions, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let args: Vec<OsString> = vec![OsString::from("cargo"), OsString::from("outdated")]
+            .into_iter()
+            .chain(args.into_iter().map(Into::into))
+            .collect();
+        let m = app().get_matches_from_safe(args)?;
+        let m = m.subcommand_matches("outdated")
+            .expect("Subcommand outdated not found");
+        Ok(OutdatedOptions::from_matches(m))
+    }
+
+    /// The cargo configuration to run with, in the cargo home the options
+    /// point to and with their timeout and retry count of network requests
+    ///
+    /// Nothing is put into the environment of the process, the temporary
+    /// projects take these settings over from the configuration.
+    pub fn config(&self) -> CargoResult<Config> {
+        let cwd = env::current_dir()
+            .chain_err(|| "couldn't get the current directory of the process")?;
+        let home = match self.flag_cargo_home {
+            Some(ref cargo_home) => cwd.join(cargo_home),
+            None => homedir(&cwd).ok_or_else(|| {
+                "Cargo couldn't find your home directory. \
+                 This probably means that $HOME was not set."
+            })?,
+        };
+        let config = Config::new(cargo::core::Shell::new(), cwd, home);
+        if self.flag_timeout.is_some() || self.flag_retry.is_some() {
+            let mut values = config.load_values()?;
+            if let Some(timeout) = self.flag_timeout {
+                set_config_integer(&mut values, "http", "timeout", timeout as i64, "--timeout");
+            }
+            if let Some(retry) = self.flag_retry {
+                set_config_integer(&mut values, "net", "retry", i64::from(retry), "--retry");
+            }
+            config.set_values(values)?;
+        }
+        Ok(config)
+    }
+
+    /// Directory cargo-outdated keeps downloads and cached reports in
+    pub fn cache_dir(&self, config: &Config) -> PathBuf {
+        cache_dir(config, self.flag_cache_dir.as_ref().map(|dir| dir.as_str()))
+    }
+
+    /// Whether the phases of the run are reported, always with `--verbose`,
+    /// never with `--quiet` and otherwise when stderr is a terminal
+    pub fn shows_progress(&self) -> bool {
+        self.flag_verbose > 0 || (self.flag_quiet != Some(true) && atty::is(atty::Stream::Stderr))
+    }
+
+    /// Exit code of runs which found outdated dependencies, with `--exit-code`
+    pub fn exit_code(&self) -> i32 { self.flag_exit_code }
+
+    /// Whether the analysis is run again and again, with `--watch`
+    pub fn watches(&self) -> bool { self.flag_watch.is_some() }
+
+    /// Level to log up to, with `--log-level`
+    pub fn log_level(&self) -> Option<&str> { self.flag_log_level.as_ref().map(|l| l.as_str()) }
+
+    /// Fill in the options not given in `m` from the defaults of the
+    /// configuration of the project they point to, if there is one
+    ///
+    /// Failing to find or load the workspace is left to `execute` to report.
+    pub fn apply_project_defaults(&mut self, config: &Config, m: &ArgMatches) {
+        let project_config =
+            find_root_manifest_for_wd(self.flag_manifest_path.clone(), config.cwd())
+                .and_then(|manifest| Workspace::new(&manifest, config))
+                .and_then(|workspace| {
+                    let root = workspace.root().to_path_buf();
+                    ProjectConfig::load(&root).map(|project_config| (root, project_config))
+                });
+        if let Ok((root, project_config)) = project_config {
+            self.apply_defaults(&project_config.defaults, m);
+            self.project_config = Some((root, project_config));
+        }
+    }
+
+    /// Whether the reports of an analysis may come from the cache and go
+    /// into it, i.e. unless `--no-cache` or `--watch` is given or the
+    /// analysis does more than making reports
+    fn caches_reports(&self) -> bool {
+        !(self.flag_no_cache || self.flag_watch.is_some() || self.flag_stats
+            || self.flag_interactive || self.cmd_explain || self.cmd_tree
+            || self.flag_write.is_some() || self.flag_impact.is_some()
+            || self.flag_what_if.is_some() || self.flag_format == "dot")
+    }
+
+    /// What the reports of an analysis depend on, as the key of the report
+    /// cache: the options changing what is analyzed and how, leaving out
+    /// those which only change how the reports are printed or what is done
+    /// with them afterwards
+    fn cache_key(&self) -> String {
+        // sorted, as the order of a `HashMap` differs from run to run
+        let within: BTreeMap<_, _> = self.flag_within.iter().collect();
+        let key = vec![
+            ("batch", format!("{:?}", self.cmd_batch)),
+            ("consolidate", format!("{:?}", self.cmd_consolidate)),
+            ("projects", format!("{:?}", self.arg_projects)),
+            ("features", format!("{:?}", self.flag_features)),
+            ("all-features", format!("{:?}", self.flag_all_features)),
+            ("no-default-features", format!("{:?}", self.flag_no_default_features)),
+            ("manifest-path", format!("{:?}", self.flag_manifest_path)),
+            ("frozen", format!("{:?}", self.flag_frozen)),
+            ("locked", format!("{:?}", self.flag_locked)),
+            ("check-lockfile", format!("{:?}", self.flag_check_lockfile)),
+            ("no-exec", format!("{:?}", self.flag_no_exec)),
+            ("aggressive", format!("{:?}", self.flag_aggressive)),
+            ("precise", format!("{:?}", self.flag_precise)),
+            ("offline", format!("{:?}", self.flag_offline)),
+            ("broken-paths", format!("{:?}", self.flag_broken_paths)),
+            ("patched-as-current", format!("{:?}", self.flag_patched_as_current)),
+            ("packages", format!("{:?}", self.flag_packages)),
+            ("root", format!("{:?}", self.flag_root)),
+            ("depth", format!("{:?}", self.flag_depth)),
+            ("workspace", format!("{:?}", self.flag_workspace)),
+            ("members", format!("{:?}", self.flag_members)),
+            ("all-targets-platforms", format!("{:?}", self.flag_all_targets_platforms)),
+            ("target", format!("{:?}", self.flag_target)),
+            ("installed", format!("{:?}", self.flag_installed)),
+            ("include-submodules", format!("{:?}", self.flag_include_submodules)),
+            ("include-standalone", format!("{:?}", self.flag_include_standalone)),
+            ("include-members", format!("{:?}", self.flag_include_members)),
+            ("exclude-members-as-deps", format!("{:?}", self.flag_exclude_members_as_deps)),
+            ("unpublished-members", format!("{:?}", self.flag_unpublished_members)),
+            ("also", format!("{:?}", self.flag_also)),
+            ("ignore", format!("{:?}", self.flag_ignore)),
+            ("only", format!("{:?}", self.flag_only)),
+            ("kind", format!("{:?}", self.flag_kind)),
+            ("why", format!("{:?}", self.flag_why)),
+            ("duplicates-only", format!("{:?}", self.flag_duplicates_only)),
+            ("pre-release", format!("{:?}", self.flag_pre_release)),
+            ("rust-version", format!("{:?}", self.flag_rust_version)),
+            ("zero-major-as-breaking", format!("{:?}", self.flag_zero_major_as_breaking)),
+            ("cargo-home", format!("{:?}", self.flag_cargo_home)),
+            ("api-diff", format!("{:?}", self.flag_api_diff)),
+            ("check-publishers", format!("{:?}", self.flag_check_publishers)),
+            ("owners", format!("{:?}", self.flag_owners)),
+            ("security-only", format!("{:?}", self.flag_security_only)),
+            ("vulnerabilities", format!("{:?}", self.flag_vulnerabilities)),
+            ("vulnerable-only", format!("{:?}", self.flag_vulnerable_only)),
+            ("allow-license", format!("{:?}", self.flag_allow_license)),
+            ("deny-license", format!("{:?}", self.flag_deny_license)),
+            ("graph-changes", format!("{:?}", self.flag_graph_changes)),
+            ("git", format!("{:?}", self.flag_git)),
+            ("effort", format!("{:?}", self.flag_effort)),
+            ("series", format!("{:?}", self.flag_series)),
+            ("show-dates", format!("{:?}", self.flag_show_dates)),
+            ("show-links", format!("{:?}", self.flag_show_links)),
+            ("edition-gap", format!("{:?}", self.flag_edition_gap)),
+            ("sort", format!("{:?}", self.flag_sort)),
+            ("no-sort", format!("{:?}", self.flag_no_sort)),
+            ("within", format!("{:?}", within)),
+        ];
+        key.iter()
+            .map(|&(name, ref value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Whether rows are printed as JSON lines while the projects are
+    /// analyzed, rather than all at once at the end, as they are into a
+    /// file given with `--output`
+    fn streams_jsonl(&self) -> bool {
+        self.flag_format == "jsonl" && self.flag_since.is_none() && self.flag_output.is_none()
+    }
+
+    /// Fill in the options not given on the command line from the defaults
+    /// of the project configuration
+    fn apply_defaults(&mut self, defaults: &Defaults, m: &ArgMatches) {
+        let m = match m.subcommand() {
+            (_, Some(sub_m)) => sub_m,
+            _ => m,
+        };
+        self.flag_ignore.extend(defaults.ignore.iter().cloned());
+        let depth_given = ["depth", "root-deps-only", "transitive"]
+            .iter()
+            .any(|arg| m.occurrences_of(arg) > 0);
+        if !depth_given {
+            if defaults.root_deps_only == Some(true) {
+                self.flag_depth = 1;
+            } else if let Some(depth) = defaults.depth {
+                self.flag_depth = depth;
+            } else if defaults.root_deps_only == Some(false) {
+                self.flag_depth = -1;
+            }
+        }
+        if m.occurrences_of("exit-code") == 0 {
+            if let Some(exit_code) = defaults.exit_code {
+                self.flag_exit_code = exit_code;
+            }
+        }
+    }
+
+    /// Options from the matches of the `outdated` subcommand of [`app`](fn.app.html)
+    pub fn from_matches(m: &ArgMatches) -> OutdatedOptions {
+        let (cmd, m) = match m.subcommand() {
+            (cmd, Some(sub_m)) => (cmd, sub_m),
+            _ => ("", m),
+        };
+        OutdatedOptions {
+            cmd_audit_crate: cmd == "audit-crate",
+            cmd_batch: cmd == "batch",
+            cmd_completions: cmd == "completions",
+            cmd_consolidate: cmd == "consolidate",
+            cmd_diff_crate: cmd == "diff-crate",
+            cmd_explain: cmd == "explain",
+            cmd_self_version: cmd == "self-version",
+            cmd_tree: cmd == "tree",
+            arg_crate: m.value_of("crate").map(String::from),
+            arg_crate_version: m.value_of("crate-version").map(String::from),
+            arg_from: m.value_of("from").map(String::from),
+            arg_to: m.value_of("to").map(String::from),
+            arg_projects: m.value_of("projects").map(String::from),
+            arg_apply: m.is_present("apply"),
+            arg_shell: m.value_of("shell").map(String::from),
+            flag_color: m.value_of("color").map(String::from),
+            flag_features: m.values_of("features")
+                .map(|vals| vals.into_iter().map(String::from).collect())
+                .unwrap_or_default(),
+            // every optional dependency is checked unless features are chosen
+            flag_all_features: m.is_present("all-features")
+                || !(m.is_present("features") || m.is_present("no-default-features")),
+            flag_no_default_features: m.is_present("no-default-features"),
+            flag_manifest_path: m.value_of("manifest-path").map(String::from),
+            flag_quiet: if m.is_present("quiet") { Some(true) } else { None },
+            flag_verbose: m.occurrences_of("verbose") as u32,
+            flag_frozen: m.is_present("frozen"),
+            flag_locked: m.is_present("locked") || m.is_present("frozen"),
+            flag_check_lockfile: m.is_present("check-lockfile"),
+            flag_no_exec: m.is_present("no-exec"),
+            flag_keep_temp: m.is_present("keep-temp"),
+            flag_no_cache: m.is_present("no-cache"),
+            flag_cache_ttl: m.value_of("cache-ttl")
+                .and_then(|v| parse_duration(v).ok())
+                .unwrap_or(DEFAULT_CACHE_TTL_SECS),
+            flag_aggressive: m.is_present("aggressive"),
+            flag_precise: m.value_of("precise").and_then(|v| parse_crate_version(v).ok()),
+            flag_offline: m.is_present("offline") || m.is_present("frozen"),
+            flag_exec: m.value_of("exec").map(String::from),
+            flag_broken_paths: m.value_of("broken-paths").unwrap_or("skip").to_owned(),
+            flag_patched_as_current: m.is_present("patched-as-current"),
+            flag_exit_code: m.value_of("exit-code")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(|| 0_i32),
+            flag_packages: m.values_of("packages")
+                .map(|vals| vals.into_iter().map(String::from).collect())
+                .unwrap_or_default(),
+            flag_root: m.value_of("root").map(String::from),
+            flag_depth: if m.is_present("root-deps-only") {
+                1
+            } else {
+                m.value_of("depth")
+                    .as_ref()
+                    .and_then(|v| v.parse::<i32>().ok())
+                    .unwrap_or_else(|| default_depth(cmd, m))
+            },
+            flag_workspace: m.is_present("workspace"),
+            flag_members: m.values_of("member")
+                .map(|vals| vals.into_iter().map(String::from).collect())
+                .unwrap_or_default(),
+            flag_all_targets_platforms: m.is_present("all-targets-platforms"),
+            flag_target: m.value_of("target").map(String::from),
+            flag_installed: m.is_present("installed"),
+            flag_include_submodules: m.is_present("include-submodules"),
+            flag_include_standalone: m.is_present("include-standalone"),
+            flag_include_members: m.is_present("include-members"),
+            flag_exclude_members_as_deps: m.is_present("exclude-members-as-deps"),
+            flag_unpublished_members: m.value_of("unpublished-members")
+                .unwrap_or("compare")
+                .to_owned(),
+            flag_also: m.values_of("also")
+                .map(|vals| vals.into_iter().map(String::from).collect())
+                .unwrap_or_default(),
+            flag_ignore: m.values_of("ignore")
+                .map(|vals| vals.into_iter().map(String::from).collect())
+                .unwrap_or_default(),
+            flag_only: m.values_of("only")
+                .map(|vals| vals.into_iter().map(String::from).collect())
+                .unwrap_or_default(),
+            flag_why: m.is_present("why"),
+            flag_duplicates_only: m.is_present("duplicates-only"),
+            flag_no_table: m.is_present("no-table"),
+            flag_summary: m.is_present("summary"),
+            flag_pre_release: m.is_present("pre-release"),
+            flag_rust_version: m.is_present("rust-version"),
+            flag_kind: m.values_of("kind")
+                .map(|vals| vals.into_iter().map(String::from).collect())
+                .unwrap_or_default(),
+            flag_zero_major_as_breaking: m.value_of("zero-major-as-breaking") != Some("false"),
+            flag_timeout: m.value_of("timeout").and_then(|v| parse_duration(v).ok()),
+            flag_watch: m.value_of("watch")
+                .map(|v| parse_duration(v).expect("--watch validated as an interval")),
+            flag_retry: m.value_of("retry").and_then(|v| v.parse().ok()),
+            flag_cargo_home: m.value_of("cargo-home").map(String::from),
+            flag_cache_dir: m.value_of("cache-dir").map(String::from),
+            flag_temp_dir: m.value_of("temp-dir").map(String::from),
+            flag_log_level: m.value_of("log-level").map(String::from),
+            flag_stats: m.is_present("stats"),
+            flag_api_diff: m.is_present("api-diff"),
+            flag_check_publishers: m.is_present("check-publishers"),
+            flag_owners: m.is_present("owners"),
+            flag_security_only: m.is_present("security-only"),
+            flag_vulnerabilities: m.is_present("vulnerabilities")
+                || m.is_present("vulnerable-only"),
+            flag_vulnerable_only: m.is_present("vulnerable-only"),
+            flag_allow_license: m.values_of("allow-license")
+                .map(|vals| vals.into_iter().map(String::from).collect())
+                .unwrap_or_default(),
+            flag_deny_license: m.values_of("deny-license")
+                .map(|vals| vals.into_iter().map(String::from).collect())
+                .unwrap_or_default(),
+            flag_graph_changes: m.is_present("graph-changes"),
+            flag_git: m.is_present("git"),
+            flag_effort: m.is_present("effort"),
+            flag_series: m.is_present("series"),
+            flag_show_dates: m.is_present("show-dates"),
+            flag_show_links: m.is_present("show-links"),
+            flag_edition_gap: m.is_present("edition-gap"),
+            flag_sort: m.value_of("sort").unwrap_or("name").to_owned(),
+            flag_no_sort: m.is_present("no-sort"),
+            flag_impact: m.value_of("impact").map(String::from),
+            flag_what_if: m.value_of("what-if")
+                .and_then(|v| parse_crate_requirement(v, '=').ok()),
+            flag_within: m.values_of("within")
+                .map(|vals| {
+                    vals.filter_map(|v| parse_crate_requirement(v, ':').ok())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            flag_allow_network_failure: m.is_present("allow-network-failure"),
+            flag_strict: m.is_present("strict"),
+            flag_format: if m.is_present("porcelain") {
+                "porcelain".to_owned()
+            } else {
+                m.value_of("format").unwrap_or("list").to_owned()
+            },
+            flag_output: m.value_of("output").map(String::from),
+            flag_since: m.value_of("since").map(String::from),
+            flag_save_baseline: m.value_of("save-baseline").map(String::from),
+            flag_compare_baseline: m.value_of("compare-baseline").map(String::from),
+            flag_write: m.value_of("write").map(String::from),
+            flag_interactive: m.is_present("interactive"),
+            flag_generate_man: m.is_present("generate-man"),
+            project_config: None,
+        }
+    }
+}
+
+/// How deep the analysis goes without `--depth`, `--root-deps-only` or
+/// `--transitive`: the dependencies the manifests declare, or the whole graph
+/// for what is about the latter, like `tree` or `--duplicates-only`
+fn default_depth(cmd: &str, m: &ArgMatches) -> i32 {
+    let whole_graph = cmd == "tree" || cmd == "explain" || m.value_of("format") == Some("dot")
+        || WHOLE_GRAPH_FLAGS.iter().any(|flag| m.is_present(flag));
+    if whole_graph {
+        -1
+    } else {
+        1
+    }
+}
+
+/// Flags looking at the transitive dependencies as well by default
+const WHOLE_GRAPH_FLAGS: &[&str] = &[
+    "transitive",
+    "packages",
+    "impact",
+    "duplicates-only",
+    "stats",
+    "security-only",
+    "vulnerabilities",
+    "vulnerable-only",
+];
+
+/// Result of a run
+pub struct Outcome {
+    /// Number of outdated dependencies, or whatever else the exit code
+    /// depends on
+    pub outdated: i32,
+    /// Whether the latest versions could not be looked up
+    pub network_failed: bool,
+}
+
+impl Outcome {
+    fn outdated(outdated: i32) -> Outcome {
+        Outcome {
+            outdated: outdated,
+            network_failed: false,
+        }
+    }
+}
+
+/// Dependencies of the analyzed projects, as `analyze_workspace` found them
+#[derive(Debug, Clone, Serialize)]
+pub struct OutdatedReport {
+    /// One per analyzed root, i.e. per member in workspace mode
+    pub reports: Vec<Report>,
+    /// Whether every member of the workspace was analyzed, with `--workspace`
+    pub workspace_mode: bool,
+}
+
+/// Analyze the project `options` point to, like `cargo outdated` does, but
+/// without printing anything
+///
+/// The options which only change how the rows are printed, like `--format`,
+/// are ignored. Subcommands are too, except for `explain` and `tree` which,
+/// like `--impact`, `--what-if` and `--format dot`, produce something else
+/// than rows and are an error.
+pub fn analyze_workspace(
+    options: &OutdatedOptions,
+    config: &Config,
+) -> CargoResult<OutdatedReport> {
+    if options.cmd_explain || options.cmd_tree || options.flag_impact.is_some()
+        || options.flag_what_if.is_some() || options.flag_format == "dot"
+    {
+        return Err(CargoError::from_kind(CargoErrorKind::Msg(
+            "explain, tree, --impact, --what-if and --format dot have no report to return"
+                .to_owned(),
+        )));
+    }
+    configure(options, config)?;
+    let (outdated, _) = collect_reports(options, config, false)?
+        .expect("Analysis printed its own output");
+    Ok(outdated)
+}
+
+/// Configure cargo the way `options` say
+fn configure(options: &OutdatedOptions, config: &Config) -> CargoResult<()> {
+    config.configure(
+        options.flag_verbose,
+        options.flag_quiet,
+        &options.flag_color,
+        // the temporary projects get their own configuration, with crates.io
+        // replaced by its local copy
+        options.flag_frozen || options.flag_offline,
+        options.flag_locked,
+        &[],
+    )?;
+    debug!(config, format!("options: {:?}", options));
+    Ok(())
+}
+
+/// Run `cargo outdated` with `options`, printing what it found
+pub fn execute(options: OutdatedOptions, config: &Config) -> CargoResult<Outcome> {
+    if options.flag_generate_man {
+        let command = outdated_command()
+            .bin_name("cargo outdated")
+            .version(concat!("v", crate_version!()));
+        man::print_man_page(command, ABOUT)?;
+        return Ok(Outcome::outdated(0));
+    }
+
+    if options.cmd_completions {
+        let shell = options.arg_shell.as_ref().expect("Shell not found");
+        let shell = shell
+            .parse::<Shell>()
+            .map_err(|e| CargoError::from_kind(CargoErrorKind::Msg(e)))?;
+        app().gen_completions_to("cargo", shell, &mut io::stdout());
+        return Ok(Outcome::outdated(0));
+    }
+
+    configure(&options, config)?;
+
+    if options.cmd_audit_crate {
+        let name = options.arg_crate.as_ref().expect("Crate name not found");
+        verbose!(config, "Auditing...", name);
+        let audit = CrateAudit::fetch(
+            config,
+            name,
+            options.arg_crate_version.as_ref().map(|v| v.as_str()),
+            options.flag_zero_major_as_breaking,
+        )?;
+        match options.flag_format.as_str() {
+            "json" | "jsonl" => audit.print_json()?,
+            _ => audit.print_list()?,
+        }
+        return Ok(Outcome::outdated(audit.outdated.len() as i32));
+    }
+
+    if options.cmd_diff_crate {
+        let name = options.arg_crate.as_ref().expect("Crate name not found");
+        let from = options.arg_from.as_ref().expect("Version to compare from not found");
+        let to = options.arg_to.as_ref().expect("Version to compare to not found");
+        verbose!(config, "Comparing...", format!("{} {} and {}", name, from, to));
+        let diff = CrateDiff::fetch(config, name, from, to)?;
+        match options.flag_format.as_str() {
+            "json" | "jsonl" => diff.print_json()?,
+            _ => diff.print_list()?,
+        }
+        return Ok(Outcome::outdated(diff.changes.len() as i32));
+    }
+
+    if options.cmd_consolidate {
+        let manifest =
+            find_root_manifest_for_wd(options.flag_manifest_path.clone(), config.cwd())?;
+        let workspace = Workspace::new(&manifest, config)?;
+        let report = ConsolidationReport::check(&workspace)?;
+        match options.flag_format.as_str() {
+            "json" | "jsonl" => report.print_json()?,
+            _ => report.print_list()?,
+        }
+        if options.arg_apply {
+            report.apply(config, &workspace)?;
+        }
+        return Ok(Outcome::outdated(report.consolidations.len() as i32));
+    }
+
+    if options.cmd_self_version {
+        let manifest =
+            find_root_manifest_for_wd(options.flag_manifest_path.clone(), config.cwd())?;
+        let workspace = Workspace::new(&manifest, config)?;
+        verbose!(config, "Checking...", "versions of the workspace members");
+        let report = SelfVersionReport::check(&workspace)?;
+        match options.flag_format.as_str() {
+            "json" | "jsonl" => report.print_json()?,
+            _ => report.print_list()?,
+        }
+        return Ok(Outcome::outdated(report.members.len() as i32));
+    }
+
+    if options.cmd_batch {
+        let projects = options.arg_projects.as_ref().expect("Project list not found");
+        let mut combined = BatchReport::default();
+        for (key, dir) in discovery::projects(Path::new(projects))? {
+            verbose!(config, "Analyzing...", &key);
+            let result = find_root_manifest_for_wd(None, &dir)
+                .and_then(|manifest| analyze(&options, config, &manifest))
+                .map(|analysis| analysis.map(|a| a.reports).unwrap_or_default());
+            let result = match result {
+                Err(e) if options.flag_strict => return Err(e),
+                result => result,
+            };
+            if let Err(ref e) = result {
+                config.shell().warn(format!("failed to analyze {}: {}", key, e))?;
+            }
+            combined.insert(key, result);
+        }
+        verbose!(config, "Printing...", "batch report");
+        combined.print_json()?;
+        return Ok(Outcome::outdated(combined.count()));
+    }
+
+    let (outdated, stats) = match collect_reports(&options, config, options.streams_jsonl())? {
+        Some(collected) => collected,
+        None => return Ok(Outcome::outdated(0)),
+    };
+    let reports = &outdated.reports;
+    let workspace_mode = outdated.workspace_mode;
+    // loaded before a baseline at the same path is overwritten
+    let baseline_diff = match options.flag_compare_baseline {
+        Some(ref baseline) => Some(ReportDiff::between(&Report::load(baseline)?, reports)),
+        None => None,
+    };
+    let with_summary =
+        options.flag_format == "list" && !options.flag_no_table && !options.flag_summary;
+
+    let mut print = |out: &mut Write| -> CargoResult<()> {
+        if let Some(ref since) = options.flag_since {
+            verbose!(config, "Printing...", "changes since the previous report");
+            let diff = ReportDiff::between(&Report::load(since)?, reports);
+            match options.flag_format.as_str() {
+                "json" | "jsonl" => diff.print_json(out)?,
+                _ => diff.print_list(out)?,
+            }
+        } else if !options.streams_jsonl() {
+            verbose!(config, "Printing...", format!("{} format", options.flag_format));
+            printer(&options, config).print(out, reports, workspace_mode)?;
+        }
+        if options.flag_stats {
+            if let Some(ref stats) = stats {
+                verbose!(config, "Printing...", "statistics");
+                stats.print(out, config)?;
+            }
+        }
+        if with_summary {
+            report::print_summary(out, reports)?;
+        }
+        Ok(())
+    };
+    match options.flag_output {
+        Some(ref path) => {
+            write_atomically(&config.cwd().join(path), &mut print)?;
+            config.shell().status("Wrote", path)?;
+        }
+        None => print(&mut io::stdout())?,
+    }
+    if with_summary {
+        if let Some(age) = Report::index_age(reports) {
+            config.shell().warn(format!(
+                "the registry index was last updated {} ago, newer releases may be missing",
+                describe_age(&age)
+            ))?;
+        }
+    }
+    if let Some(ref command) = options.flag_exec {
+        verbose!(config, "Running...", command);
+        exec_per_dependency(config, command, reports)?;
+    }
+    if let Some(ref baseline) = options.flag_save_baseline {
+        config.shell().status("Saving", format!("baseline {}", baseline))?;
+        Report::save(reports, baseline)?;
+    }
+    let denied = Report::denied_licenses(reports);
+    if denied > 0 {
+        return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+            "{} upgrade(s) would pull in a disallowed license",
+            denied
+        ))));
+    }
+    let network_failed = reports.iter().any(|report| report.latest_unavailable);
+    if network_failed {
+        config.shell().warn(
+            "the latest versions could not be looked up, only the lock file was checked",
+        )?;
+    }
+    let count = match baseline_diff {
+        Some(diff) => {
+            let newly_outdated = diff.count_newly_outdated(options.flag_security_only);
+            config.shell().status(
+                "Compared",
+                format!("{} dependencies newly outdated since the baseline", newly_outdated),
+            )?;
+            newly_outdated
+        }
+        None if options.flag_security_only => Report::security_fixes(reports),
+        // every row listed is something to act on, upgrade or not
+        None if options.flag_duplicates_only || options.flag_only.iter().any(|o| o == "yanked") => {
+            Report::rows(reports)
+        }
+        None => Report::count(reports),
+    };
+    Ok(Outcome {
+        outdated: count,
+        network_failed: network_failed,
+    })
+}
+
+/// The printer of the reports in the format `options` ask for
+fn printer(options: &OutdatedOptions, config: &Config) -> Box<Printer> {
+    match options.flag_format.as_str() {
+        "json" => Box::new(JsonPrinter),
+        "jsonl" => Box::new(JsonlPrinter),
+        "porcelain" => Box::new(PorcelainPrinter),
+        "markdown" => Box::new(MarkdownPrinter),
+        "html" => Box::new(HtmlPrinter),
+        "badge" => Box::new(BadgePrinter),
+        _ if options.flag_summary => Box::new(CountsPrinter),
+        _ if options.flag_no_table => Box::new(TsvPrinter),
+        _ => Box::new(ListPrinter {
+            verbose: options.flag_verbose > 0,
+            // files only get colors if asked for
+            color: match options.flag_output {
+                Some(_) => options.flag_color.as_ref().map(|c| c.as_str()) == Some("always"),
+                None => report::colors_stdout(config),
+            },
+        }),
+    }
+}
+
+/// Run `cargo outdated` with `options` every `--watch` interval and
+/// whenever a manifest or the lock file of the workspace changes, until
+/// interrupted
+///
+/// On terminals the table is redrawn, other formats are printed once per
+/// run. The reports are never cached, for the runs to pick up new releases.
+/// A failing run is reported and the next one waited for, only not finding
+/// the project ends watching. As it never returns otherwise, `--exit-code`
+/// has no effect.
+pub fn watch(options: OutdatedOptions, config: &Config) -> CargoResult<Outcome> {
+    let interval = match options.flag_watch {
+        Some(secs) if secs > 0 => Duration::from_secs(secs),
+        _ => {
+            return Err(CargoError::from_kind(CargoErrorKind::Msg(
+                "--watch needs an interval of at least 1s".to_owned(),
+            )))
+        }
+    };
+    if options.flag_exit_code != 0 {
+        config
+            .shell()
+            .warn("--exit-code has no effect with --watch, which runs until interrupted")?;
+    }
+    let redraws = atty::is(atty::Stream::Stdout) && options.flag_format == "list"
+        && options.flag_output.is_none();
+    loop {
+        let watched = watched_files(&options, config)?;
+        let modified = modification_times(&watched);
+        if redraws {
+            // clear the screen and move to its top left corner
+            print!("\x1b[2J\x1b[H");
+        }
+        if let Err(e) = execute(options.clone(), config) {
+            cargo::handle_error(e, &mut *config.shell());
+        }
+        io::stdout().flush()?;
+        config.shell().status(
+            "Watching",
+            format!(
+                "{} files, running again in {}s or once one changes",
+                watched.len(),
+                interval.as_secs()
+            ),
+        )?;
+        let started = Instant::now();
+        while started.elapsed() < interval && modification_times(&watched) == modified {
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+}
+
+/// The files whose changes make `watch` run again, the manifests of the
+/// workspace and its lock file, or the list of installed packages
+fn watched_files(options: &OutdatedOptions, config: &Config) -> CargoResult<Vec<PathBuf>> {
+    if options.flag_installed {
+        return Ok(vec![config.home().as_path_unlocked().join(".crates.toml")]);
+    }
+    let manifest = find_root_manifest_for_wd(options.flag_manifest_path.clone(), config.cwd())?;
+    let mut files = vec![manifest.clone()];
+    // a manifest broken by an edit is still watched, for when it is fixed
+    match Workspace::new(&manifest, config) {
+        Ok(workspace) => {
+            files.extend(workspace.members().map(|m| m.manifest_path().to_path_buf()));
+            files.push(workspace.root().join("Cargo.toml"));
+            files.push(workspace.root().join("Cargo.lock"));
+        }
+        Err(_) => if let Some(dir) = manifest.parent() {
+            files.push(dir.join("Cargo.lock"));
+        },
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// When each of `files` was last modified, `None` for those missing
+fn modification_times(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|file| fs::metadata(file).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// The reports of the project `options` point to and of the ones they add,
+/// each one printed as a JSON line as soon as it is done if `stream`
+///
+/// Returns `None` when a subcommand already printed its own output
+fn collect_reports(
+    options: &OutdatedOptions,
+    config: &Config,
+    stream: bool,
+) -> CargoResult<Option<(OutdatedReport, Option<Stats>)>> {
+    if options.flag_installed {
+        verbose!(config, "Resolving...", "installed packages");
+        let report = installed_report(options, config)?;
+        if stream {
+            report.print_jsonl(&mut io::stdout())?;
+        }
+        let outdated = OutdatedReport {
+            reports: vec![report],
+            workspace_mode: false,
+        };
+        Ok(Some((outdated, None)))
+    } else {
+        // the Cargo.toml that we are actually working on
+        let curr_manifest =
+            find_root_manifest_for_wd(options.flag_manifest_path.clone(), config.cwd())?;
+        let analysis = match analyze(options, config, &curr_manifest)? {
+            Some(analysis) => analysis,
+            None => return Ok(None),
+        };
+        let mut reports = analysis.reports;
+        if stream {
+            for report in &reports {
+                report.print_jsonl(&mut io::stdout())?;
+            }
+        }
+        if options.flag_include_submodules {
+            let dir = curr_manifest.parent().expect("Manifest has no parent directory");
+            let submodules = discovery::submodule_projects(dir)?;
+            analyze_projects(options, config, submodules, stream, &mut reports)?;
+        }
+        if options.flag_include_standalone {
+            let workspace = Workspace::new(&curr_manifest, config)?;
+            let members = workspace
+                .members()
+                .map(|m| m.root().to_path_buf())
+                .collect::<HashSet<_>>();
+            let standalone = discovery::standalone_projects(workspace.root(), &members)?;
+            analyze_projects(options, config, standalone, stream, &mut reports)?;
+        }
+        if !options.flag_also.is_empty() {
+            let also = discovery::projects_at(config.cwd(), &options.flag_also)?;
+            analyze_projects(options, config, also, stream, &mut reports)?;
+        }
+        let outdated = OutdatedReport {
+            reports: reports,
+            workspace_mode: analysis.workspace_mode,
+        };
+        Ok(Some((outdated, Some(analysis.stats))))
+    }
+}
+
+/// Carry on after a failure to update one of the temporary workspaces if
+/// `--allow-network-failure` allows it
+fn tolerate_network_failure(
+    options: &OutdatedOptions,
+    config: &Config,
+    workspace: &str,
+    error: CargoError,
+) -> CargoResult<()> {
+    if !options.flag_allow_network_failure {
+        return Err(error);
+    }
+    config.shell().warn(format!(
+        "failed to update the {} workspace, carrying on without it: {}",
+        workspace, error
+    ))
+}
+
+/// Append the reports of other projects, labelled by their path
+///
+/// Failing to analyze one of them is only a warning, as they are not what
+/// the user is working on, unless `--strict` is given
+fn analyze_projects(
+    options: &OutdatedOptions,
+    config: &Config,
+    projects: Vec<(String, PathBuf)>,
+    stream: bool,
+    reports: &mut Vec<Report>,
+) -> CargoResult<()> {
+    for (path, dir) in projects {
+        verbose!(config, "Analyzing...", &path);
+        let analysis = match analyze(options, config, &dir.join("Cargo.toml")) {
+            Ok(analysis) => analysis,
+            Err(e) if options.flag_strict => return Err(e),
+            Err(e) => {
+                config
+                    .shell()
+                    .warn(format!("failed to analyze {}: {}", path, e))?;
+                continue;
+            }
+        };
+        for mut report in analysis.map(|a| a.reports).unwrap_or_default() {
+            report.project = Some(path.clone());
+            if stream {
+                report.print_jsonl(&mut io::stdout())?;
+            }
+            reports.push(report);
+        }
+    }
+    Ok(())
+}
+
+/// Reports and statistics of an analyzed workspace
+struct Analysis {
+    reports: Vec<Report>,
+    stats: Stats,
+    workspace_mode: bool,
+}
+
+/// Resolve the current, compat and latest workspaces of `curr_manifest`
+///
+/// Returns `None` when a subcommand already printed its own output
+fn analyze(
+    options: &OutdatedOptions,
+    config: &Config,
+    curr_manifest: &Path,
+) -> CargoResult<Option<Analysis>> {
+    progress!(options, config, "Parsing...", "current workspace");
+    let curr_workspace = Workspace::new(curr_manifest, config)?;
+    let caches_reports = options.caches_reports();
+    if caches_reports {
+        let cache_dir = options.cache_dir(config);
+        let cache = ReportCache::new(&cache_dir, config, &curr_workspace, &options.cache_key())?;
+        if let Some(cached) = cache.load(Duration::from_secs(options.flag_cache_ttl)) {
+            verbose!(config, "Fresh", "reports from the cache");
+            return Ok(Some(Analysis {
+                reports: cached.reports,
+                stats: Stats::default(),
+                workspace_mode: cached.workspace_mode,
+            }));
+        }
+    }
+    progress!(options, config, "Resolving...", "current workspace");
+    if options.flag_verbose == 0 {
+        config.shell().set_verbosity(Verbosity::Quiet);
+    }
+    fetch_locked_sources(&curr_workspace)?;
+    let mut ela_curr = ElaborateWorkspace::from_workspace(&curr_workspace, options)?;
+    if options.flag_verbose > 0 {
+        config.shell().set_verbosity(Verbosity::Verbose);
+    } else if options.flag_quiet == Some(true) {
+        config.shell().set_verbosity(Verbosity::Quiet);
+    } else {
+        config.shell().set_verbosity(Verbosity::Normal);
+    }
+    ela_curr.warn_wildcard_requirements(config)?;
+
+    if let Some(ref name) = options.flag_impact {
+        verbose!(config, "Resolving...", format!("impact of upgrading {}", name));
+        let impact = Impact::preview(&ela_curr, curr_manifest, options, name)?;
+        match options.flag_format.as_str() {
+            "json" | "jsonl" => impact.print_json()?,
+            _ => impact.print_list()?,
+        }
+        return Ok(None);
+    }
+    if let Some((ref name, ref requirement)) = options.flag_what_if {
+        verbose!(
+            config,
+            "Resolving...",
+            format!("{} with {} = \"{}\"", curr_workspace.root().display(), name, requirement)
+        );
+        let impact = Impact::what_if(&ela_curr, curr_manifest, options, name, requirement)?;
+        match options.flag_format.as_str() {
+            "json" | "jsonl" => impact.print_json()?,
+            _ => impact.print_list()?,
+        }
+        if impact.conflict.is_some() {
+            return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+                "{} = \"{}\" has no solution",
+                name, requirement
+            ))));
+        }
+        return Ok(None);
+    }
+
+    progress!(options, config, "Copying...", "manifests into the compat workspace");
+    let compat_proj = TempProject::from_workspace(&ela_curr, curr_manifest, options)?;
+    // the rows of skipped path dependencies say why they are Removed
+    ela_curr.skipped_paths = compat_proj.skipped_paths.clone();
+    compat_proj.write_manifest_semver()?;
+    progress!(options, config, "Updating...", "compat workspace");
+    // the compat workspace falls back to the lock file as is, and the latest
+    // versions become unknown
+    let mut network_failed = false;
+    // with -p, only the packages asked about are updated
+    let compat_update = if options.flag_packages.is_empty() {
+        compat_proj.cargo_update()
+    } else {
+        let specs = ela_curr.package_specs(&options.flag_packages);
+        for name in &options.flag_packages {
+            if !specs.iter().any(|spec| spec.starts_with(&format!("{}:", name))) {
+                config
+                    .shell()
+                    .warn(format!("package {} is not in the dependency graph", name))?;
+            }
+        }
+        if specs.is_empty() {
+            Ok(())
+        } else {
+            compat_proj.cargo_update_packages(&specs)
+        }
+    };
+    if let Err(e) = compat_update {
+        tolerate_network_failure(options, config, "compat", e)?;
+        network_failed = true;
+    }
+    if let Some((ref name, ref version)) = options.flag_precise {
+        // the lock file is used as is if the update failed
+        if !network_failed {
+            verbose!(config, "Updating...", format!("{} to {}", name, version));
+            compat_proj.cargo_update_precise(name, version)?;
+        }
+    }
+    progress!(options, config, "Resolving...", "compat workspace");
+    let compat_workspace = compat_proj.workspace.borrow();
+    let ela_compat =
+        ElaborateWorkspace::from_workspace(compat_workspace.as_ref().unwrap(), options)?;
+
+    progress!(options, config, "Copying...", "manifests into the latest workspace");
+    let latest_proj = TempProject::from_workspace(&ela_curr, curr_manifest, options)?;
+    if !network_failed {
+        progress!(options, config, "Updating...", "latest workspace");
+        let latest_update = latest_proj
+            .write_manifest_latest(&options.flag_within)
+            .and_then(|_| latest_proj.cargo_update());
+        if let Err(e) = latest_update {
+            tolerate_network_failure(options, config, "latest", e)?;
+            network_failed = true;
+        }
+    }
+    progress!(options, config, "Resolving...", "latest workspace");
+    let latest_workspace = latest_proj.workspace.borrow();
+    let ela_latest = if network_failed {
+        let mut ela =
+            ElaborateWorkspace::from_workspace(compat_workspace.as_ref().unwrap(), options)?;
+        ela.unknown_deps = ela_curr.pkgs.keys().map(|id| id.name().to_owned()).collect();
+        ela
+    } else {
+        let mut ela =
+            ElaborateWorkspace::from_workspace(latest_workspace.as_ref().unwrap(), options)?;
+        ela.unknown_deps = latest_proj.unknown_deps.borrow().clone();
+        ela.alternative_latest = latest_proj.alternative_latest.borrow().clone();
+        ela
+    };
+
+    let roots: Vec<_> = if ela_curr.workspace_mode {
+        ela_curr
+            .workspace
+            .members()
+            .filter(|m| {
+                options.flag_members.is_empty()
+                    || options.flag_members.contains(&m.name().to_string())
+            })
+            .map(|m| m.package_id().clone())
+            .collect()
+    } else {
+        vec![ela_curr.determine_root(options)?]
+    };
+
+    if roots.is_empty() {
+        return Err(CargoError::from_kind(CargoErrorKind::Msg(format!(
+            "None of {} is a member of the workspace",
+            options.flag_members.join(", ")
+        ))));
+    }
+
+    if options.cmd_explain {
+        verbose!(config, "Printing...", "Package explanation");
+        let name = options.arg_crate.as_ref().expect("Crate name not found");
+        ela_curr.explain(name, &ela_compat, &ela_latest)?;
+        return Ok(None);
+    } else if options.cmd_tree {
+        verbose!(config, "Printing...", "Package status in tree format");
+        for (i, root) in roots.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            ela_curr.resolve_status(&ela_compat, &ela_latest, options, config, root)?;
+            ela_curr.print_tree(options, root)?;
+        }
+        return Ok(None);
+    } else if options.flag_format == "dot" {
+        verbose!(config, "Printing...", "dependency graph in DOT format");
+        for root in &roots {
+            ela_curr.resolve_status(&ela_compat, &ela_latest, options, config, root)?;
+        }
+        ela_curr.print_dot(&roots, options.flag_depth)?;
+        return Ok(None);
+    }
+
+    let mut stats = Stats::default();
+    // without --strict, crates which can't be looked up become warnings
+    let mut crates_io = if options.flag_strict {
+        CratesIo::new(config)
+    } else {
+        CratesIo::tolerant(config)
+    };
+    let mut rustdoc = RustdocJson::new(config, options.cache_dir(config));
+    let mut rust_versions = RustVersions::load(&options.cache_dir(config));
+    let mut index = if options.flag_series {
+        Some(RegistryIndex::crates_io_updated(config)?)
+    } else {
+        None
+    };
+    let advisories = if options.flag_security_only || options.flag_vulnerabilities {
+        verbose!(config, "Fetching...", "advisory database");
+        Some(AdvisoryDatabase::fetch(config, &options.cache_dir(config))?)
+    } else {
+        None
+    };
+    let license_policy = LicensePolicy {
+        allow: options.flag_allow_license.clone(),
+        deny: options.flag_deny_license.clone(),
+    };
+    // projects other than the one the options point to have their own
+    let project_config = match options.project_config {
+        Some((ref root, ref project_config)) if root == curr_workspace.root() => {
+            project_config.clone()
+        }
+        _ => ProjectConfig::load(curr_workspace.root())?,
+    };
+    let index_age_secs = stale_index_secs(config);
+    let graph_edition = if options.flag_edition_gap {
+        Some(newest_edition(&ela_curr)?)
+    } else {
+        None
+    };
+    let mut reports = vec![];
+    let mut choices = HashMap::new();
+    for (i, root) in roots.iter().enumerate() {
+        progress!(
+            options,
+            config,
+            "Resolving...",
+            format!("status of {} ({}/{})", root.name(), i + 1, roots.len())
+        );
+        // without --strict, a member which can't be analyzed is reported as
+        // a warning along with the others
+        let mut errors = vec![];
+        let analyzed = (|| -> CargoResult<_> {
+            ela_curr.resolve_status(&ela_compat, &ela_latest, options, config, root)?;
+            let up_to_date = ela_curr.up_to_date_count(options);
+            let mut dependencies = ela_curr.list_lines(options, root)?;
+            if options.flag_git {
+                let strict = options.flag_strict;
+                dependencies.extend(git_lines(config, &ela_curr, root, strict, &mut errors)?);
+            }
+            let manifest_path = ela_curr.pkgs[root].manifest_path();
+            LocalAnnotations::new(options, manifest_path, advisories.as_ref(), &project_config)?
+                .annotate(&mut dependencies);
+            if options.flag_vulnerable_only {
+                dependencies.retain(|line| line.advisories.is_some());
+            }
+            annotate_rust_versions(
+                &mut crates_io,
+                &mut rust_versions,
+                manifest_path,
+                &mut dependencies,
+            )?;
+            if options.flag_rust_version {
+                annotate_newest_releases(
+                    &mut crates_io,
+                    &mut rust_versions,
+                    manifest_path,
+                    options.flag_pre_release,
+                    &mut dependencies,
+                )?;
+            }
+            annotate_licenses(&mut crates_io, &license_policy, &mut dependencies)?;
+            if let Some(ref graph_edition) = graph_edition {
+                annotate_editions(&mut crates_io, graph_edition, &mut dependencies)?;
+            }
+            if options.flag_show_dates {
+                annotate_dates(&mut crates_io, &mut dependencies)?;
+            }
+            if options.flag_show_links {
+                annotate_links(config, &mut crates_io, &mut dependencies)?;
+            }
+            if let Some(ref mut index) = index {
+                annotate_series(
+                    index,
+                    options.flag_zero_major_as_breaking,
+                    options.flag_pre_release,
+                    &mut dependencies,
+                )?;
+            }
+            if options.flag_check_publishers {
+                annotate_publishers(&mut crates_io, &mut dependencies)?;
+            }
+            if options.flag_owners {
+                annotate_owners(&mut crates_io, &mut dependencies)?;
+            }
+            if options.flag_api_diff {
+                let direct: HashSet<_> = ela_curr.pkg_deps[root]
+                    .keys()
+                    .map(|id| id.name().to_owned())
+                    .collect();
+                let message = format!("public APIs of {}", root.name());
+                progress!(options, config, "Diffing...", message);
+                annotate_breaking_changes(&mut rustdoc, &direct, &mut dependencies)?;
+            }
+            if options.flag_effort || options.flag_sort == "effort" {
+                let mut dependents = HashMap::new();
+                for deps in ela_curr.pkg_deps.values() {
+                    for id in deps.keys() {
+                        *dependents.entry(id.name().to_owned()).or_insert(0) += 1;
+                    }
+                }
+                annotate_effort(&mut crates_io, &dependents, &mut dependencies)?;
+                if options.flag_sort == "effort" && !options.flag_no_sort {
+                    // stable, so rows of equal effort stay sorted by name
+                    dependencies.sort_by(|a, b| b.effort.cmp(&a.effort));
+                }
+            }
+            let graph_changes = if options.flag_graph_changes && !network_failed {
+                let message = format!("dependency graph of {}", root.name());
+                progress!(options, config, "Diffing...", message);
+                ela_curr.graph_changes(&ela_latest, options, root)?
+            } else {
+                vec![]
+            };
+            ela_curr.collect_stats(&mut stats);
+            Ok((up_to_date, dependencies, graph_changes))
+        })();
+        let (up_to_date, dependencies, graph_changes) = match analyzed {
+            Ok(analyzed) => analyzed,
+            Err(e) if options.flag_strict => return Err(e),
+            Err(e) => {
+                let error = format!("failed to analyze {}: {}", root.name(), e);
+                config.shell().warn(&error)?;
+                errors.push(error);
+                (0, vec![], vec![])
+            }
+        };
+        errors.extend(crates_io.take_failures());
+        let manifest_path = ela_curr.pkgs[root].manifest_path();
+        let workspace_root = curr_workspace.root();
+        if let Some(ref versions) = options.flag_write {
+            write_requirements(config, manifest_path, workspace_root, &dependencies, &mut |line| {
+                Ok(Some(if versions == "compat" {
+                    line.compat.clone()
+                } else {
+                    line.latest.clone()
+                }))
+            })?;
+        } else if options.flag_interactive {
+            // members inheriting or sharing a dependency are asked about it once
+            write_requirements(config, manifest_path, workspace_root, &dependencies, &mut |line| {
+                let key = (line.name.clone(), line.project.clone());
+                if let Some(choice) = choices.get(&key) {
+                    return Ok(choice.clone());
+                }
+                let choice = prompt_upgrade(line)?;
+                choices.insert(key, choice.clone());
+                Ok(choice)
+            })?;
+        }
+        reports.push(Report {
+            crate_name: root.name().to_owned(),
+            project: None,
+            dependencies: dependencies,
+            graph_changes: graph_changes,
+            index_age_secs: index_age_secs,
+            latest_unavailable: network_failed,
+            up_to_date: up_to_date,
+            errors: errors,
+        });
+    }
+    rust_versions.store()?;
+    // problems like failed requests may well be gone by the next run
+    if caches_reports && !network_failed && reports.iter().all(|r| r.errors.is_empty()) {
+        // under the state the indexes were updated to by the resolution
+        let cache_dir = options.cache_dir(config);
+        let cache = ReportCache::new(&cache_dir, config, &curr_workspace, &options.cache_key())?;
+        cache.store(&reports, ela_curr.workspace_mode)?;
+    }
+    Ok(Some(Analysis {
+        reports: reports,
+        stats: stats,
+        workspace_mode: ela_curr.workspace_mode,
+    }))
+}
+
+/// Set `table.key` of configuration values to `value`, given by `flag`
+fn set_config_integer(
+    values: &mut HashMap<String, ConfigValue>,
+    table: &str,
+    key: &str,
+    value: i64,
+    flag: &str,
+) {
+    let definition = PathBuf::from(flag);
+    let entry = values
+        .entry(table.to_owned())
+        .or_insert_with(|| ConfigValue::Table(HashMap::new(), definition.clone()));
+    if let ConfigValue::Table(ref mut entries, _) = *entry {
+        entries.insert(key.to_owned(), ConfigValue::Integer(value, definition));
+    }
+}
+
+/// How long cached reports are used for unless `--cache-ttl` says otherwise,
+/// short enough for new releases to show up the same day
+const DEFAULT_CACHE_TTL_SECS: u64 = 10 * 60;
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use clap::ArgMatches;
+
+    use super::{app, Defaults, OutdatedOptions};
+
+    fn options(args: &[&str]) -> OutdatedOptions {
+        OutdatedOptions::from_args(args.to_vec()).unwrap()
+    }
+
+    /// The matches of the `outdated` subcommand with `args`
+    fn matches(args: &[&str]) -> ArgMatches<'static> {
+        let args = ["cargo", "outdated"].iter().chain(args).map(|arg| OsString::from(*arg));
+        let m = app().get_matches_from_safe(args).unwrap();
+        m.subcommand_matches("outdated").unwrap().clone()
+    }
+
+    /// The options with `args`, with `defaults` from the configuration
+    fn with_defaults(args: &[&str], defaults: &str) -> OutdatedOptions {
+        let defaults: Defaults = ::toml::from_str(defaults).unwrap();
+        let mut options = options(args);
+        options.apply_defaults(&defaults, &matches(args));
+        options
+    }
+
+    fn cache_key(args: &[&str]) -> String { options(args).cache_key() }
+
+    #[test]
+    fn root_dependencies_by_default() {
+        assert_eq!(options(&[]).flag_depth, 1);
+        assert_eq!(options(&["--transitive"]).flag_depth, -1);
+        assert_eq!(options(&["--depth", "3"]).flag_depth, 3);
+        assert_eq!(options(&["-R"]).flag_depth, 1);
+        assert!(OutdatedOptions::from_args(vec!["--transitive", "--depth", "2"]).is_err());
+        assert!(OutdatedOptions::from_args(vec!["--transitive", "-R"]).is_err());
+    }
+
+    #[test]
+    fn whole_graph_for_what_is_about_it() {
+        assert_eq!(options(&["tree"]).flag_depth, -1);
+        assert_eq!(options(&["--format", "dot"]).flag_depth, -1);
+        assert_eq!(options(&["--duplicates-only"]).flag_depth, -1);
+        assert_eq!(options(&["--stats"]).flag_depth, -1);
+        assert_eq!(options(&["-p", "serde"]).flag_depth, -1);
+        assert_eq!(options(&["--format", "json"]).flag_depth, 1);
+        // asked for explicitly after all
+        assert_eq!(options(&["--duplicates-only", "--depth", "2"]).flag_depth, 2);
+    }
+
+    #[test]
+    fn features_default_to_all() {
+        assert!(options(&[]).flag_all_features);
+        let options = options(&["--features", "tls"]);
+        assert!(!options.flag_all_features);
+        assert_eq!(options.flag_features, vec!["tls".to_owned()]);
+    }
+
+    #[test]
+    fn configured_depth() {
+        assert_eq!(with_defaults(&[], "root-deps-only = false").flag_depth, -1);
+        assert_eq!(with_defaults(&[], "depth = 2").flag_depth, 2);
+        assert_eq!(with_defaults(&["--transitive"], "root-deps-only = true").flag_depth, -1);
+        assert_eq!(with_defaults(&["--depth", "3"], "depth = 2").flag_depth, 3);
+        assert_eq!(with_defaults(&["-R"], "root-deps-only = false").flag_depth, 1);
+    }
+
+    #[test]
+    fn configured_ignore_and_exit_code() {
+        let options = with_defaults(&["--ignore", "rand"], "ignore = [\"openssl\"]\nexit-code = 1");
+        assert_eq!(options.flag_ignore, vec!["rand".to_owned(), "openssl".to_owned()]);
+        assert_eq!(options.flag_exit_code, 1);
+        assert_eq!(with_defaults(&["--exit-code", "2"], "exit-code = 1").flag_exit_code, 2);
+    }
+
+    #[test]
+    fn cache_keys_are_stable() {
+        assert_eq!(
+            cache_key(&["--within", "a:1", "--within", "b:2", "--within", "c:3"]),
+            cache_key(&["--within", "c:3", "--within", "b:2", "--within", "a:1"])
+        );
+        // printing the reports differently doesn't change them
+        assert_eq!(cache_key(&[]), cache_key(&["--format", "json", "--color", "never", "-v"]));
+        assert_ne!(cache_key(&[]), cache_key(&["--pre-release"]));
+    }
+}
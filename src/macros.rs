@@ -1,25 +1,45 @@
 macro_rules! verbose {
-    ($config: expr, $status: expr, $message: expr) => (
+    ($config: expr, $status: expr, $message: expr) => ({
+        let message = $message;
+        log!(::log::LogLevel::Info, "{} {}", $status, message);
         $config
             .shell()
             .verbose(
-                |sh| -> CargoResult<()> { sh.status($status, $message) },
+                |sh| -> CargoResult<()> { sh.status($status, &message) },
             )?
-    )
+    })
+}
+
+/// Report a phase of the run, with `--verbose` and otherwise when stderr is
+/// a terminal, so that long runs don't look stuck while logs stay clean
+macro_rules! progress {
+    ($options: expr, $config: expr, $status: expr, $message: expr) => ({
+        let message = $message;
+        log!(::log::LogLevel::Info, "{} {}", $status, message);
+        if $options.shows_progress() {
+            $config.shell().status($status, &message)?
+        }
+    })
 }
 
 #[cfg(feature = "debug")]
 macro_rules! debug {
-    ($config: expr, $message: expr) => (
-        $config.shell().status_with_color("DEBUG", $message, ::termcolor::Color::White)?
-    );
+    ($config: expr, $message: expr) => ({
+        let message = $message;
+        log!(::log::LogLevel::Debug, "{}", message);
+        $config.shell().status_with_color("DEBUG", message, ::termcolor::Color::White)?
+    });
     ($config: expr, $($arg: tt)*) => (
-        $config.shell().status_with_color("DEBUG", format!($($arg)*), ::termcolor::Color::White)?
+        debug!($config, format!($($arg)*))
     );
 }
 
 #[cfg(not(feature = "debug"))]
 macro_rules! debug {
-    ($config: expr, $message: expr) => ();
-    ($config: expr, $($arg: tt)*) => ();
+    ($config: expr, $message: expr) => (
+        log!(::log::LogLevel::Debug, "{}", $message)
+    );
+    ($config: expr, $($arg: tt)*) => (
+        log!(::log::LogLevel::Debug, $($arg)*)
+    );
 }
@@ -1,241 +1,245 @@
 /// Displays information about project dependency versions
 ///
 /// USAGE:
-///     cargo outdated [FLAGS] [OPTIONS]
+///     cargo outdated [FLAGS] [OPTIONS] [SUBCOMMAND]
 ///
 /// FLAGS:
+///         --aggressive             Also update the dependencies of the crate cargo update
+///                                  is run for
+///         --all-features           Activate all features (Default unless features are
+///                                  chosen)
+///         --all-targets-platforms  Include the dependencies of every [target] table
+///                                  rather than only those which apply to the host
+///                                  platform
+///         --allow-network-failure  If the latest versions can't be looked up, report the
+///                                  lock file with the latest versions unknown and exit
+///                                  with code 2
+///         --api-diff               Count the public items major upgrades of direct
+///                                  dependencies remove or change, from rustdoc JSON
+///         --check-lockfile         Only report the dependencies Cargo.lock holds back, i.e.
+///                                  those cargo update would upgrade within the requirements
+///         --check-publishers       Flag latest versions published by someone who
+///                                  published none of the earlier versions
+///         --duplicates-only        Only list the crates present in several versions in the
+///                                  dependency graph, every version of them, up to date or
+///                                  not
+///         --effort                 Add a column with a rough estimate of the work each
+///                                  upgrade takes
+///         --edition-gap            Mark latest versions written in an edition newer than
+///                                  any in the dependency graph
+///         --exclude-members-as-deps
+///                                  Never list workspace members as dependencies of other
+///                                  members
+///         --frozen                 Require Cargo.lock to be up to date, and resolve offline
+///                                  (Implies --locked)
+///         --git                    Check git dependencies for newer commits and tags,
+///                                  contacting their remotes
+///         --graph-changes          List the crates upgrading each direct dependency to
+///                                  its latest version would add to or remove from the
+///                                  dependency graph
 ///     -h, --help                   Prints help information
-///     -R, --root-deps-only         Only check root dependencies (Equivalent to --depth=1)
+///         --include-members        List workspace members other members depend on,
+///                                  compared against their releases on crates.io
+///         --include-standalone     Also check the packages under the workspace root
+///                                  which are not members, e.g. examples, tools or
+///                                  fuzz targets
+///         --include-submodules     Also check the workspaces inside git submodules
+///         --installed              Check the packages installed with cargo install instead
+///     -i, --interactive            Choose per dependency whether to rewrite its requirement
+///                                  to compat or latest
+///         --keep-temp              Keep the temporary projects the resolution runs in,
+///                                  printing their paths, for debugging
+///         --locked                 Require Cargo.lock to be up to date with the manifests
+///         --no-cache               Resolve again even if the workspace is unchanged since a
+///                                  previous run
+///         --no-default-features    Do not activate the default features
+///         --no-exec                Never run third-party code (build scripts are
+///                                  removed from the temporary manifests)
+///         --no-sort                Keep the rows in the order the dependency graph is
+///                                  walked in rather than sorting them
+///         --no-table               Print the rows as plain tab-separated values rather than
+///                                  a table
+///         --offline                Resolve against the local copy of the crates.io index
+///                                  only
+///         --owners                 Add a column with the owners of the crates on crates.io
+///         --patched-as-current     Regard crates overridden in the [patch] section,
+///                                  e.g. by a git fork carrying a fix, as up to date
+///         --porcelain              Print the stable format for scripts (Equivalent to
+///                                  --format=porcelain)
+///         --pre-release            Also consider pre-releases as latest versions, e.g.
+///                                  2.0.0-beta.1
+///     -q, --quiet                  No output printed to stderr but errors
+///     -R, --root-deps-only         Only check root dependencies, the default (Equivalent to
+///                                  --depth=1)
+///         --rust-version           Report the newest versions supporting the rust-version
+///                                  of the package as latest, marking newer ones needing a
+///                                  newer Rust
+///         --security-only          Only count outdated dependencies whose upgrade fixes
+///                                  a known security advisory towards the exit code
+///         --series                 Add a column with the newest release of the major
+///                                  series in use
+///         --show-dates             Add columns with the age of the version in use and the
+///                                  latest's release date
+///         --show-links             Add a column with the repository of each crate, or a URL
+///                                  comparing the version in use with the latest on GitHub
+///         --stats                  Print staleness statistics, broken down by
+///                                  dependency kind
+///         --strict                 Fail as soon as a member, dependency or project can't be
+///                                  analyzed rather than reporting it and carrying on
+///         --summary                Print the numbers of dependencies up to date and behind
+///                                  by a patch, minor or major version rather than the rows
+///         --transitive             Check the transitive dependencies too (Equivalent to
+///                                  leaving out --depth)
 ///     -V, --version                Prints version information
 ///     -v, --verbose                Use verbose output
+///         --vulnerabilities        Flag dependencies with known security advisories against
+///                                  the version in use
+///         --vulnerable-only        Only list the dependencies with known security
+///                                  advisories (Implies --vulnerabilities)
+///         --why                    Add a column with the chain of packages pulling in each
+///                                  dependency
 ///     -w, --workspace              Check updates for all workspace members rather
 ///                                  than only the root package
 ///
 /// OPTIONS:
+///         --allow-license <LICENSE>...
+///                                   Only allow upgrades to versions under these licenses,
+///                                   e.g. 'MIT' or 'Apache-2.0*'
+///         --also <PATH>...          Also check the project at this path, or the projects
+///                                   under it, e.g. examples with their own lock files
+///         --broken-paths <MODE>     What to do with path dependencies which can't be
+///                                   resolved, skip them with a warning or fail
+///                                   [default: skip] [values: skip, fail]
+///         --cache-dir <DIR>         Directory to keep downloads like rustdoc JSON in
+///                                   [default: $CARGO_OUTDATED_CACHE_DIR or
+///                                   ~/.cache/cargo-outdated]
+///         --cache-ttl <DURATION>    How long cached reports are used for, e.g. 1h, 0 not to
+///                                   use them [default: 10m]
+///         --cargo-home <DIR>        Cargo home to use instead of $CARGO_HOME, including
+///                                   the registry index
 ///         --color <color>           Coloring: auto, always, never [default: auto]
 ///                                   [values: auto, always, never]
+///         --compare-baseline <FILE>
+///                                   Only count the dependencies outdated since a baseline
+///                                   saved with --save-baseline towards the exit code
 ///     -d, --depth <NUM>             How deep in the dependency chain to search
-///                                   (Defaults to all dependencies when omitted)
+///                                   (Defaults to the root dependencies, see --transitive)
+///         --deny-license <LICENSE>...
+///                                   Fail if upgrading to the latest version of a dependency
+///                                   pulls in one of these licenses, e.g. 'GPL-3.0*'
+///         --exec <CMD>              Run a command once per outdated dependency, substituting
+///                                   {name}, {current}, {compat}, {latest}, {kind},
+///                                   {severity} and {root}
 ///         --exit-code <NUM>         The exit code to return on new versions found [default: 0]
 ///         --features <FEATURE>      Space-separated list of features
+///         --format <FORMAT>         Output format [default: list] [values: list, json,
+///                                   jsonl, porcelain, markdown, html, dot, badge]
+///         --ignore <CRATE>...       Dependencies to leave out of the report and the exit
+///                                   code
+///         --impact <CRATE>          Show which other packages change if only this one is
+///                                   upgraded to its latest version
+///         --kind <KIND>...          Only report the dependencies of these kinds, along with
+///                                   what they pull in [values: normal, dev, build]
+///         --log-level <LEVEL>       Log messages up to this level to stderr, RUST_LOG
+///                                   refines it per module [values: error, warn, info,
+///                                   debug, trace]
 ///     -m, --manifest-path <PATH>    An absolute path to the Cargo.toml file to use
 ///                                   (Defaults to Cargo.toml in project root)
-///     -p, --packages <PKG>...       Package to inspect for updates
+///         --member <NAME>...        Workspace members to check, implying --workspace
+///         --only <SEVERITY>...      Only report updates of these severities, or yanked for the
+///                                   dependencies whose version in use was yanked [values:
+///                                   major, minor, patch, yanked]
+///         --output <FILE>           Write the report to this file rather than stdout, replacing
+///                                   the file only once the report is complete
+///     -p, --packages <PKG>...       Only report on these dependencies, direct or transitive
+///         --precise <CRATE:VERSION>
+///                                   Resolve a crate to this version in the compat
+///                                   workspace, e.g. 'foo:1.2.3'
+///         --retry <NUM>             Number of times a failed network request is retried
 ///     -r, --root <ROOT>             Package to treat as the root package
+///         --save-baseline <FILE>    Save the report as a baseline for --compare-baseline,
+///                                   in the format of --format json
+///         --since <REPORT>          Only print the changes since a report previously saved
+///                                   with --format json
+///         --sort <KEY>              Order of the rows, effort puts the hardest upgrades
+///                                   first [default: name] [values: name, effort]
+///         --target <TRIPLE>         Only include the dependencies which apply to this
+///                                   platform rather than the host
+///         --temp-dir <DIR>          Directory to create the temporary projects in
+///                                   [default: $TMPDIR]
+///         --timeout <DURATION>      Timeout of network requests, e.g. 30s or 2m
+///         --unpublished-members <MODE>
+///                                   How --include-members treats members without a
+///                                   release: hide them, show them with ---, or compare
+///                                   them like published ones [default: compare]
+///                                   [values: hide, show, compare]
+///         --watch <INTERVAL>        Run again every INTERVAL, e.g. 5m, and whenever a
+///                                   manifest or the lock file changes, until interrupted
+///                                   (--exit-code has no effect)
+///         --what-if <CRATE=REQ>     Resolve as if the requirement on a direct dependency
+///                                   were REQ, showing what changes or conflicts
+///         --within <CRATE:REQ>...   Report the newest version of a crate satisfying a
+///                                   requirement as its latest one, e.g. 'foo:^1'
+///         --write <VERSIONS>        Rewrite the requirements on the direct dependencies in
+///                                   the manifests to their compat or latest versions
+///                                   [values: compat, latest]
+///         --zero-major-as-breaking <BOOL>
+///                                   Classify 0.x -> 0.y updates as major [default: true]
+///                                   [values: true, false]
+///
+/// SUBCOMMANDS:
+///     audit-crate    Checks how current the requirements of a published crate are
+///     batch          Analyzes many projects and prints a combined JSON report
+///     completions    Prints a completion script for a shell
+///     consolidate    Consolidates diverging requirements into [workspace.dependencies]
+///     diff-crate     Compares the dependencies of two published versions of a crate
+///     explain        Explains the status of a single dependency
+///     self-version   Compares the versions of the members with their releases
+///     tree           Displays the dependency tree with outdated packages annotated
 extern crate cargo;
-#[macro_use]
-extern crate clap;
+extern crate cargo_outdated;
 extern crate env_logger;
-extern crate semver;
-extern crate serde;
-#[macro_use]
-extern crate serde_derive;
-extern crate tabwriter;
-extern crate tempdir;
-#[cfg(feature = "debug")]
-extern crate termcolor;
-extern crate toml;
-
-#[macro_use]
-mod macros;
-mod cargo_ops;
-use cargo_ops::{ElaborateWorkspace, TempProject};
+extern crate log;
 
-use std::path::Path;
+use std::env;
 
-use cargo::core::Workspace;
-use cargo::util::important_paths::find_root_manifest_for_wd;
-use cargo::util::{CargoResult, CliError, Config};
 use cargo::core::shell::Verbosity;
-use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
-
-/// Options from CLI arguments
-#[derive(Deserialize, Debug)]
-pub struct Options {
-    flag_color: Option<String>,
-    flag_features: Vec<String>,
-    flag_all_features: bool,
-    flag_no_default_features: bool,
-    flag_manifest_path: Option<String>,
-    flag_quiet: Option<bool>,
-    flag_verbose: u32,
-    flag_frozen: bool,
-    flag_locked: bool,
-    flag_exit_code: i32,
-    flag_packages: Vec<String>,
-    flag_root: Option<String>,
-    flag_depth: i32,
-    flag_workspace: bool,
-}
-
-impl Options {
-    fn from_matches(m: &ArgMatches) -> Options {
-        Options {
-            flag_color: m.value_of("color").map(String::from),
-            flag_features: m.values_of("features")
-                .map(|vals| vals.into_iter().map(String::from).collect())
-                .unwrap_or_default(),
-            flag_all_features: !m.is_present("features"),
-            flag_no_default_features: !m.values_of("features")
-                .map(|ref mut features| features.any(|f| f == "default"))
-                .unwrap_or(true),
-            flag_manifest_path: m.value_of("manifest-path").map(String::from),
-            flag_quiet: None,
-            flag_verbose: m.occurrences_of("verbose") as u32,
-            flag_frozen: false,
-            flag_locked: false,
-            flag_exit_code: m.value_of("exit-code")
-                .and_then(|v| v.parse().ok())
-                .unwrap_or_else(|| 0_i32),
-            flag_packages: m.values_of("packages")
-                .map(|vals| vals.into_iter().map(String::from).collect())
-                .unwrap_or_default(),
-            flag_root: m.value_of("root").map(String::from),
-            flag_depth: if m.is_present("root-deps-only") {
-                1
-            } else {
-                m.value_of("depth")
-                    .as_ref()
-                    .and_then(|v| v.parse::<i32>().ok())
-                    .unwrap_or_else(|| -1_i32)
-            },
-            flag_workspace: m.is_present("workspace"),
-        }
-    }
-}
+use cargo::util::CliError;
+use cargo_outdated::{app, execute, watch, OutdatedOptions, Outcome};
+use env_logger::LogBuilder;
+use log::LogLevelFilter;
 
 fn main() {
-    env_logger::init().unwrap();
-
-    let config = match Config::default() {
+    let m = app().get_matches();
+    let m = m.subcommand_matches("outdated")
+        .expect("Subcommand outdated not found");
+    let mut options = OutdatedOptions::from_matches(m);
+    init_logger(options.log_level());
+    let config = match options.config() {
         Ok(cfg) => cfg,
         Err(e) => {
             let mut shell = cargo::core::Shell::new();
             cargo::exit_with_error(e.into(), &mut shell)
         }
     };
-
-    let m = App::new("cargo-outdated")
-        .author("Kevin K. <kbknapp@gmail.com>")
-        .about("Displays information about project dependency versions")
-        .version(concat!("v", crate_version!()))
-        .bin_name("cargo")
-        .settings(&[
-            AppSettings::GlobalVersion,
-            AppSettings::SubcommandRequired,
-        ])
-        .subcommand(
-            SubCommand::with_name("outdated")
-                .about("Displays information about project dependency versions")
-                .arg(
-                    Arg::with_name("color")
-                        .long("color")
-                        .help("Coloring: auto, always, never")
-                        .takes_value(true)
-                        .number_of_values(1)
-                        .possible_values(&["auto", "always", "never"])
-                        .default_value("auto"),
-                )
-                .arg(
-                    Arg::with_name("features")
-                        .long("features")
-                        .help("Space-separated list of features")
-                        .takes_value(true)
-                        .value_name("FEATURE")
-                        .value_delimiter(" ")
-                        .empty_values(true)
-                        .conflicts_with_all(&["all-features", "no-default-features"]),
-                )
-                .arg(
-                    Arg::with_name("packages")
-                        .long("packages")
-                        .short("p")
-                        .help("Package to inspect for updates")
-                        .takes_value(true)
-                        .value_name("PKG")
-                        .value_delimiter(" ")
-                        .multiple(true),
-                )
-                .arg(
-                    Arg::with_name("root")
-                        .long("root")
-                        .short("r")
-                        .help("Package to treat as the root package")
-                        .takes_value(true)
-                        .value_name("ROOT")
-                        .number_of_values(1),
-                )
-                .arg(
-                    Arg::with_name("verbose")
-                        .long("verbose")
-                        .short("v")
-                        .help("Use verbose output")
-                        .multiple(true),
-                )
-                .arg(
-                    Arg::with_name("depth")
-                        .long("depth")
-                        .short("d")
-                        .long_help(
-                            "How deep in the dependency chain to search \
-                             (Defaults to all dependencies when omitted)",
-                        )
-                        .takes_value(true)
-                        .value_name("NUM")
-                        .number_of_values(1),
-                )
-                .arg(
-                    Arg::with_name("exit-code")
-                        .long("exit-code")
-                        .help("The exit code to return on new versions found")
-                        .takes_value(true)
-                        .value_name("NUM")
-                        .number_of_values(1)
-                        .default_value("0"),
-                )
-                .arg(
-                    Arg::with_name("root-deps-only")
-                        .long("root-deps-only")
-                        .short("R")
-                        .help("Only check root dependencies (Equivalent to --depth=1)")
-                        .conflicts_with("depth"),
-                )
-                .arg(
-                    Arg::with_name("manifest-path")
-                        .long("manifest-path")
-                        .short("m")
-                        .long_help(
-                            "An absolute path to the Cargo.toml file to use \
-                             (Defaults to Cargo.toml in project root)",
-                        )
-                        .takes_value(true)
-                        .value_name("PATH")
-                        .number_of_values(1)
-                        .validator(is_file),
-                )
-                .arg(
-                    Arg::with_name("workspace")
-                        .long("workspace")
-                        .short("w")
-                        .long_help(
-                            "Check updates for all workspace members \
-                             rather than only the root package",
-                        ),
-                ),
-        )
-        .get_matches();
-    let m = m.subcommand_matches("outdated")
-        .expect("Subcommand outdated not found");
-    let options = Options::from_matches(m);
-    let exit_code = options.flag_exit_code;
-    let result = execute(options, &config);
+    options.apply_project_defaults(&config, m);
+    let exit_code = options.exit_code();
+    let result = if options.watches() {
+        watch(options, &config)
+    } else {
+        execute(options, &config)
+    };
     match result {
         Err(e) => {
             config.shell().set_verbosity(Verbosity::Normal);
             let cli_error = CliError::new(e, 1);
             cargo::exit_with_error(cli_error, &mut *config.shell())
         }
-        Ok(i) => if i > 0 {
+        Ok(Outcome {
+            network_failed: true,
+            ..
+        }) => std::process::exit(NETWORK_FAILURE_EXIT_CODE),
+        Ok(outcome) => if outcome.outdated > 0 {
             std::process::exit(exit_code);
         } else {
             std::process::exit(0);
@@ -243,90 +247,19 @@ fn main() {
     }
 }
 
-#[allow(unknown_lints)]
-#[allow(needless_pass_by_value)]
-pub fn execute(options: Options, config: &Config) -> CargoResult<i32> {
-    config.configure(
-        options.flag_verbose,
-        options.flag_quiet,
-        &options.flag_color,
-        options.flag_frozen,
-        options.flag_locked,
-        &[],
-    )?;
-    debug!(config, format!("options: {:?}", options));
+/// Exit code of runs which could not look up the latest versions, with
+/// `--allow-network-failure`
+const NETWORK_FAILURE_EXIT_CODE: i32 = 2;
 
-    verbose!(config, "Parsing...", "current workspace");
-    // the Cargo.toml that we are actually working on
-    let curr_manifest =
-        find_root_manifest_for_wd(options.flag_manifest_path.clone(), config.cwd())?;
-    let curr_workspace = Workspace::new(&curr_manifest, config)?;
-    verbose!(config, "Resolving...", "current workspace");
-    if options.flag_verbose == 0 {
-        config.shell().set_verbosity(Verbosity::Quiet);
-    }
-    let mut ela_curr = ElaborateWorkspace::from_workspace(&curr_workspace, &options)?;
-    if options.flag_verbose > 0 {
-        config.shell().set_verbosity(Verbosity::Verbose);
-    } else {
-        config.shell().set_verbosity(Verbosity::Normal);
+/// Log to stderr, everything up to `level` if given, refined by the
+/// directives in `RUST_LOG`, e.g. `cargo::ops=trace`
+fn init_logger(level: Option<&str>) {
+    let mut builder = LogBuilder::new();
+    if let Some(level) = level.and_then(|l| l.parse::<LogLevelFilter>().ok()) {
+        builder.filter(None, level);
     }
-
-    verbose!(config, "Parsing...", "compat workspace");
-    let compat_proj =
-        TempProject::from_workspace(&ela_curr, &curr_manifest.to_string_lossy(), &options)?;
-    compat_proj.write_manifest_semver()?;
-    verbose!(config, "Updating...", "compat workspace");
-    compat_proj.cargo_update()?;
-    verbose!(config, "Resolving...", "compat workspace");
-    let compat_workspace = compat_proj.workspace.borrow();
-    let ela_compat =
-        ElaborateWorkspace::from_workspace(compat_workspace.as_ref().unwrap(), &options)?;
-
-    verbose!(config, "Parsing...", "latest workspace");
-    let latest_proj =
-        TempProject::from_workspace(&ela_curr, &curr_manifest.to_string_lossy(), &options)?;
-    latest_proj.write_manifest_latest()?;
-    verbose!(config, "Updating...", "latest workspace");
-    latest_proj.cargo_update()?;
-    verbose!(config, "Resolving...", "latest workspace");
-    let latest_workspace = latest_proj.workspace.borrow();
-    let ela_latest =
-        ElaborateWorkspace::from_workspace(latest_workspace.as_ref().unwrap(), &options)?;
-
-    if ela_curr.workspace_mode {
-        let mut sum = 0;
-        verbose!(config, "Printing...", "Package status in list format");
-        for member in ela_curr.workspace.members() {
-            ela_curr.resolve_status(
-                &ela_compat,
-                &ela_latest,
-                &options,
-                config,
-                member.package_id(),
-            )?;
-            sum += ela_curr.print_list(&options, member.package_id(), sum > 0)?;
-        }
-        if sum == 0 {
-            println!("All dependencies are up to date, yay!");
-        }
-        Ok(sum)
-    } else {
-        verbose!(config, "Resolving...", "package status");
-        let root = ela_curr.determine_root(&options)?;
-        ela_curr.resolve_status(&ela_compat, &ela_latest, &options, config, &root)?;
-        verbose!(config, "Printing...", "list format");
-        let count = ela_curr.print_list(&options, &root, false)?;
-        Ok(count)
-    }
-}
-
-#[allow(unknown_lints)]
-#[allow(needless_pass_by_value)]
-fn is_file(s: String) -> Result<(), String> {
-    let p = Path::new(&*s);
-    if p.file_name().is_none() {
-        return Err(format!("'{}' doesn't appear to be a valid file name", &*s));
+    if let Ok(directives) = env::var("RUST_LOG") {
+        builder.parse(&directives);
     }
-    Ok(())
+    builder.init().unwrap();
 }
@@ -0,0 +1,55 @@
+use std::io::{self, Write};
+
+use cargo::util::{CargoError, CargoErrorKind, CargoResult};
+use clap::App;
+
+/// Print a man page of `command` in roff, with the sections of its long
+/// help, e.g. `FLAGS` and `OPTIONS`, kept as they are laid out there
+pub fn print_man_page(mut command: App, about: &str) -> CargoResult<()> {
+    let mut help = vec![];
+    command
+        .write_long_help(&mut help)
+        .map_err(|e| CargoError::from_kind(CargoErrorKind::Msg(e.to_string())))?;
+    let help = String::from_utf8_lossy(&help);
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    writeln!(
+        stdout,
+        ".TH CARGO-OUTDATED 1 \"\" \"cargo-outdated {}\"",
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(stdout, ".SH NAME\ncargo\\-outdated \\- {}", escape(about))?;
+    let mut in_section = false;
+    for line in help.lines() {
+        // headings are the lines which are not indented, like `OPTIONS:`
+        if !line.starts_with(' ') && line.ends_with(':') {
+            if in_section {
+                writeln!(stdout, ".fi")?;
+            }
+            let heading = match line.trim_right_matches(':') {
+                "USAGE" => "SYNOPSIS",
+                heading => heading,
+            };
+            writeln!(stdout, ".SH {}\n.nf", heading)?;
+            in_section = true;
+        } else if in_section {
+            writeln!(stdout, "{}", escape(line))?;
+        }
+    }
+    if in_section {
+        writeln!(stdout, ".fi")?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Escape text for roff, so that backslashes, hyphens and lines starting
+/// like requests come out as they are
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}